@@ -3,14 +3,21 @@
 //! 定义前后端通信的命令，包括音频控制、网络连接、输入注入等
 
 use crate::error::{AppError, AudioError, NetworkError, InputError, ConfigError};
-use crate::modules::audio::{AudioCapturer, VoiceActivityDetector, VadLevel};
+use crate::events::EventEmitter;
+use crate::modules::audio::{
+    record_phase, AudioCapturer, CalibrationPhase, CalibrationResult, Calibrator, DeviceWatcher,
+    LevelDistribution, VoiceActivityDetector, VadLevel,
+};
 use crate::modules::network::scribe_client::ScribeClient;
 use crate::modules::input::{InputManager, InputConfig, InjectionMethod, ActiveWindowInfo};
+use crate::modules::lifecycle::AppConfig;
 use crate::modules::shortcut::{HotkeyManager, HotkeyState};
-use crate::modules::config::{ConfigManager, UserConfig};
+use crate::modules::config::{ChannelRouting, ConfigManager, UserConfig};
+use crate::modules::session::{clean_transcript, DictationSession, SessionConfig, SessionSnapshot};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{command, AppHandle, Manager, State};
 use tauri::async_runtime::Mutex as TauriMutex;
 
@@ -24,6 +31,21 @@ pub struct AudioDeviceInfo {
     pub is_default: bool,
     pub channels: u16,
     pub sample_rate: u32,
+    pub host_api: String,
+    pub sample_rates: Vec<u32>,
+    pub sample_rate_ranges: Vec<ValueRange>,
+    pub sample_formats: Vec<String>,
+    pub channel_counts: Vec<u16>,
+    pub buffer_size_ranges: Vec<ValueRange>,
+    /// 是否为系统音频回环设备
+    pub is_loopback: bool,
+}
+
+/// 数值范围 (闭区间)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValueRange {
+    pub min: u32,
+    pub max: u32,
 }
 
 /// 录音状态
@@ -122,25 +144,68 @@ pub fn get_app_name() -> String {
 /// 获取可用的音频输入设备
 #[command]
 pub async fn get_audio_devices(app: AppHandle) -> Result<Vec<AudioDeviceInfo>, String> {
-    let devices = AudioCapturer::available_devices()
-        .map_err(|e| format!("Failed to list devices: {}", e))?;
+    // 设备能力由热插拔监听在设备列表变化时查询并缓存，这里只重新查询监听源
+    let mut devices = app.state::<TauriMutex<DeviceWatcher>>().lock().await.capabilities();
+    let ids: Vec<String> = devices.iter().map(|d| d.id.clone()).collect();
+    devices.extend(AudioCapturer::monitor_devices(&ids));
 
     let default_device = AudioCapturer::default_device()
         .ok()
-        .map(|d| d.name);
+        .map(|d| d.id);
 
     Ok(devices
         .into_iter()
         .map(|d| AudioDeviceInfo {
-            name: d.name.clone(),
+            name: d.name,
+            is_default: Some(&d.id) == default_device.as_ref(),
             device_id: d.id,
-            is_default: Some(&d.name) == default_device.as_ref(),
             channels: d.channels,
-            sample_rate: d.sample_rates.first().copied().unwrap_or(44100),
+            sample_rate: d.default_sample_rate,
+            host_api: d.host,
+            sample_rates: d.sample_rates,
+            sample_rate_ranges: d.sample_rate_ranges
+                .iter()
+                .map(|r| ValueRange { min: r.min, max: r.max })
+                .collect(),
+            sample_formats: d.sample_formats.iter().map(|f| f.to_string()).collect(),
+            channel_counts: d.channel_counts,
+            buffer_size_ranges: d.buffer_size_ranges
+                .iter()
+                .map(|r| ValueRange { min: r.min, max: r.max })
+                .collect(),
+            is_loopback: d.is_loopback,
         })
         .collect())
 }
 
+/// 获取设备的通道路由，未配置时为平均所有通道
+#[command]
+pub fn get_channel_routing(app: AppHandle, device_id: String) -> Result<ChannelRouting, String> {
+    let config = app.state::<ConfigManager>()
+        .load()
+        .map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok(config.audio.channel_routing.get(&device_id).cloned().unwrap_or_default())
+}
+
+/// 保存设备的通道路由，下次录音生效
+#[command]
+pub fn set_channel_routing(
+    app: AppHandle,
+    device_id: String,
+    routing: ChannelRouting,
+) -> Result<(), String> {
+    app.state::<ConfigManager>()
+        .update(|config| {
+            // 平均所有通道为默认行为，无需保存
+            if routing == ChannelRouting::Average {
+                config.audio.channel_routing.remove(&device_id);
+            } else {
+                config.audio.channel_routing.insert(device_id, routing);
+            }
+        })
+        .map_err(|e| format!("Failed to save channel routing: {}", e))
+}
+
 /// 启动录音
 #[command]
 pub async fn start_listen(app: AppHandle) -> Result<RecordingStatus, String> {
@@ -150,14 +215,21 @@ pub async fn start_listen(app: AppHandle) -> Result<RecordingStatus, String> {
         return Err("Already recording".to_string());
     }
 
+    let settings = app.state::<ConfigManager>()
+        .load()
+        .map(|config| config.audio)
+        .unwrap_or_default();
+
+    let session = app.state::<TauriMutex<DictationSession>>();
+    let mut guard = session.lock().await;
+    let app_config = app.state::<AppConfig>();
+    guard.start(&app, SessionConfig::from_settings(&settings, &app_config.data_dir))
+        .await
+        .map_err(|e| format!("Failed to start listening: {}", e))?;
+
     state.set_recording(true);
 
-    Ok(RecordingStatus {
-        state: RecordingState::Listening,
-        duration_ms: 0,
-        volume_level: 0.0,
-        is_speech: false,
-    })
+    Ok(recording_status(&guard.snapshot()))
 }
 
 /// 停止录音
@@ -169,31 +241,115 @@ pub async fn stop_listen(app: AppHandle) -> Result<RecordingStatus, String> {
         return Err("Not recording".to_string());
     }
 
+    let session = app.state::<TauriMutex<DictationSession>>();
+    let snapshot = session.lock().await.stop().await;
+
     state.set_recording(false);
 
     Ok(RecordingStatus {
         state: RecordingState::Idle,
-        duration_ms: 0,
-        volume_level: 0.0,
-        is_speech: false,
+        ..recording_status(&snapshot)
     })
 }
 
 /// 获取当前录音状态
 #[command]
 pub async fn get_recording_status(app: AppHandle) -> Result<RecordingStatus, String> {
-    let state = app.state::<AppState>();
+    let session = app.state::<TauriMutex<DictationSession>>();
+    let guard = session.lock().await;
 
-    Ok(RecordingStatus {
-        state: if state.is_recording() {
-            RecordingState::Listening
-        } else {
-            RecordingState::Idle
-        },
-        duration_ms: 0,
-        volume_level: 0.0,
-        is_speech: false,
+    Ok(recording_status(&guard.snapshot()))
+}
+
+/// 将会话快照转换为录音状态
+fn recording_status(snapshot: &SessionSnapshot) -> RecordingStatus {
+    let state = match (snapshot.is_active, snapshot.is_speech) {
+        (false, _) => RecordingState::Idle,
+        (true, false) => RecordingState::Listening,
+        (true, true) => RecordingState::Recording,
+    };
+
+    RecordingStatus {
+        state,
+        duration_ms: snapshot.duration_ms,
+        volume_level: snapshot.volume_level,
+        is_speech: snapshot.is_speech,
+    }
+}
+
+// ============ 校准命令 ============
+
+/// 单个校准阶段的最长录制时长 (秒)
+const MAX_CALIBRATION_SECONDS: u32 = 30;
+
+/// 录制一个校准阶段
+///
+/// 使用当前输入设备录制指定时长，期间发送 `calibration_progress` 事件，
+/// 返回该阶段的电平分布。录音进行中时不可校准
+#[command]
+pub async fn calibrate_phase(
+    app: AppHandle,
+    phase: CalibrationPhase,
+    seconds: u32,
+) -> Result<LevelDistribution, String> {
+    if app.state::<AppState>().is_recording() {
+        return Err("Cannot calibrate while recording".to_string());
+    }
+    if seconds == 0 || seconds > MAX_CALIBRATION_SECONDS {
+        return Err(format!("Calibration duration must be 1-{} seconds", MAX_CALIBRATION_SECONDS));
+    }
+
+    let settings = app.state::<ConfigManager>()
+        .load()
+        .map(|config| config.audio)
+        .unwrap_or_default();
+    let audio_config = SessionConfig::from_settings(&settings, &app.state::<AppConfig>().data_dir).audio;
+    let emitter = EventEmitter::new(app.clone());
+
+    let collector = tauri::async_runtime::spawn_blocking(move || {
+        let mut capturer = AudioCapturer::new();
+        capturer.configure(audio_config)?;
+        capturer.start()?;
+        let result = record_phase(
+            &mut capturer,
+            Duration::from_secs(seconds as u64),
+            |progress, level_db| emitter.emit_calibration_progress(phase, progress, level_db),
+        );
+        if let Err(e) = capturer.stop() {
+            tracing::warn!("Failed to stop calibration capture: {}", e);
+        }
+        result
     })
+    .await
+    .map_err(|e| format!("Calibration task failed: {}", e))?
+    .map_err(|e| format!("Calibration failed: {}", e))?;
+
+    let distribution = LevelDistribution::from_levels(collector.levels())
+        .ok_or_else(|| "No audio recorded".to_string())?;
+
+    let calibrator = app.state::<TauriMutex<Calibrator>>();
+    calibrator.lock().await.set_phase(phase, collector.levels().to_vec());
+
+    Ok(distribution)
+}
+
+/// 根据已录制的两个阶段计算校准结果
+///
+/// `apply` 为 true 时将推荐设置写入配置，下次录音生效
+#[command]
+pub async fn finish_calibration(app: AppHandle, apply: bool) -> Result<CalibrationResult, String> {
+    let calibrator = app.state::<TauriMutex<Calibrator>>();
+    let mut guard = calibrator.lock().await;
+    let result = guard.analyze().map_err(|e| e.to_string())?;
+
+    if apply {
+        app.state::<ConfigManager>()
+            .update(|config| config.audio.calibration = Some(result.recommended))
+            .map_err(|e| format!("Failed to save calibration: {}", e))?;
+        guard.reset();
+    }
+
+    Ok(result)
 }
 
 // ============ 网络命令 ============
@@ -215,6 +371,11 @@ pub async fn connect_scribe(
         language_code,
         ..Default::default()
     });
+    let vad_level = app.state::<ConfigManager>()
+        .load()
+        .map(|config| config.audio.vad_level)
+        .unwrap_or_default();
+    guard.set_vad_level(vad_level);
 
     match guard.connect().await {
         Ok(_) => {
@@ -279,15 +440,10 @@ pub async fn receive_transcription(app: AppHandle) -> Result<Option<Transcriptio
 
     match guard.receive_response().await {
         Ok(Some(response)) => {
-            let text = response.text;
             let is_final = response.is_final;
 
             // 清理文本
-            let text = text
-                .replace("【SPEECH_CHANGE】", "")
-                .replace("【SILENCE】", "")
-                .trim()
-                .to_string();
+            let text = clean_transcript(&response.text);
 
             if text.is_empty() {
                 Ok(None)
@@ -479,6 +635,8 @@ pub fn set_api_key(app: AppHandle, api_key: String) -> Result<(), String> {
 // ============ VAD 命令 ============
 
 /// 设置 VAD 级别
+///
+/// 保存到配置，立即应用到运行中会话的本地 VAD，服务端 VAD 参数在下次连接时生效
 #[command]
 pub async fn set_vad_level(app: AppHandle, level: String) -> Result<(), String> {
     let vad_level = match level.as_str() {
@@ -488,19 +646,30 @@ pub async fn set_vad_level(app: AppHandle, level: String) -> Result<(), String>
         _ => VadLevel::Balanced,
     };
 
+    let config_manager = app.state::<ConfigManager>();
+    config_manager.update(|config| config.audio.vad_level = vad_level)
+        .map_err(|e| format!("Failed to save VAD level: {}", e))?;
+
     let client = app.state::<TauriMutex<ScribeClient>>();
-    let mut guard = client.lock().await;
-    guard.set_vad_level(vad_level);
+    client.lock().await.set_vad_level(vad_level);
+
+    // 与会话启动时相同的方式构建，保留校准阈值
+    let settings = config_manager.load()
+        .map(|config| config.audio)
+        .unwrap_or_default();
+    let session = app.state::<TauriMutex<DictationSession>>();
+    let vad = SessionConfig::from_settings(&settings, &app.state::<AppConfig>().data_dir).vad;
+    session.lock().await.set_vad_config(vad);
     Ok(())
 }
 
 /// 获取 VAD 级别
 #[command]
 pub async fn get_vad_level(app: AppHandle) -> Result<String, String> {
-    let client = app.state::<TauriMutex<ScribeClient>>();
-    let guard = client.lock().await;
-
-    let level = guard.vad_level();
+    let level = app.state::<ConfigManager>()
+        .load()
+        .map(|config| config.audio.vad_level)
+        .unwrap_or_default();
     let level_str = match level {
         VadLevel::Aggressive => "aggressive",
         VadLevel::Balanced => "balanced",
@@ -526,6 +695,23 @@ mod tests {
         assert!(json.contains("Recording"));
     }
 
+    #[test]
+    fn test_recording_status_from_snapshot() {
+        let snapshot = SessionSnapshot {
+            is_active: true,
+            duration_ms: 1500,
+            volume_level: 0.2,
+            is_speech: true,
+        };
+        let status = recording_status(&snapshot);
+        assert_eq!(status.state, RecordingState::Recording);
+        assert_eq!(status.duration_ms, 1500);
+        assert!(status.is_speech);
+
+        let idle = recording_status(&SessionSnapshot { is_active: false, ..snapshot });
+        assert_eq!(idle.state, RecordingState::Idle);
+    }
+
     #[test]
     fn test_window_info_serialization() {
         let info = WindowInfo {
@@ -108,6 +108,12 @@ pub enum AudioError {
 
     #[error("Resampling failed: {0}")]
     ResamplingFailed(String),
+
+    #[error("Audio source failed: {0}")]
+    SourceFailed(String),
+
+    #[error("Recording failed: {0}")]
+    RecordingFailed(String),
 }
 
 /// 网络相关错误
@@ -195,6 +201,8 @@ impl AppError {
                 AudioError::StreamCreationFailed(_) => ErrorCode::AudioStreamFailed,
                 AudioError::CaptureFailed(_) => ErrorCode::AudioCaptureFailed,
                 AudioError::ResamplingFailed(_) => ErrorCode::AudioStreamFailed,
+                AudioError::SourceFailed(_) => ErrorCode::AudioCaptureFailed,
+                AudioError::RecordingFailed(_) => ErrorCode::AudioCaptureFailed,
             },
             AppError::Network(e) => match e {
                 NetworkError::ConnectionFailed(_) => ErrorCode::NetworkConnectFailed,
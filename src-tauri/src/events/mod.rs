@@ -2,6 +2,7 @@
 //!
 //! 定义应用事件和状态，以及事件分发器
 
+use crate::modules::audio::{AutoStopReason, CalibrationPhase, DeviceSummary, LevelReading};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::fmt;
@@ -9,6 +10,9 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 
+/// 悬浮窗标签
+const OVERLAY_WINDOW: &str = "overlay";
+
 /// 应用状态
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum AppState {
@@ -70,8 +74,12 @@ impl EventEmitter {
         });
     }
 
-    pub fn emit_audio_level(&self, level: f32, peak: f32) {
-        self.emit("audio_level", AudioLevelPayload { level, peak });
+    /// 发送输入电平，只发给悬浮窗
+    pub fn emit_audio_level(&self, reading: &LevelReading) {
+        let payload = AudioLevelPayload::from(reading);
+        if let Err(e) = self.app.emit_to(OVERLAY_WINDOW, "audio_level", payload) {
+            tracing::debug!("Failed to emit audio level: {}", e);
+        }
     }
 
     pub fn emit_partial_transcript(&self, text: &str) {
@@ -81,10 +89,12 @@ impl EventEmitter {
         });
     }
 
-    pub fn emit_committed_transcript(&self, text: &str, confidence: f64) {
+    /// `speaker` 为多路输入时说话方所在通道的标签
+    pub fn emit_committed_transcript(&self, text: &str, confidence: f64, speaker: Option<&str>) {
         self.emit("committed_transcript", CommittedTranscriptPayload {
             text: text.to_string(),
             confidence,
+            speaker: speaker.map(str::to_string),
             timestamp: Utc::now(),
         });
     }
@@ -100,6 +110,46 @@ impl EventEmitter {
     pub fn emit_recording_state(&self, is_recording: bool) {
         self.emit("recording_state", RecordingStatePayload { is_recording });
     }
+
+    pub fn emit_audio_devices_changed(
+        &self,
+        added: &[DeviceSummary],
+        removed: &[DeviceSummary],
+        devices: &[DeviceSummary],
+    ) {
+        self.emit("audio_devices_changed", AudioDevicesChangedPayload {
+            added: added.to_vec(),
+            removed: removed.to_vec(),
+            devices: devices.to_vec(),
+        });
+    }
+
+    pub fn emit_audio_device_switched(&self, from: Option<&str>, to: Option<&str>, name: &str) {
+        self.emit("audio_device_switched", AudioDeviceSwitchedPayload {
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            name: name.to_string(),
+        });
+    }
+
+    /// 发送自动停止倒计时，`remaining_ms` 为 None 表示倒计时因重新说话而取消
+    pub fn emit_auto_stop_countdown(&self, remaining_ms: Option<u64>, reason: Option<AutoStopReason>) {
+        self.emit("auto_stop_countdown", AutoStopCountdownPayload { remaining_ms, reason });
+    }
+
+    /// 发送会话已自动停止
+    pub fn emit_auto_stopped(&self, reason: AutoStopReason) {
+        self.emit("auto_stopped", AutoStoppedPayload { reason });
+    }
+
+    /// 发送校准录制进度
+    pub fn emit_calibration_progress(&self, phase: CalibrationPhase, progress: f32, level_db: f32) {
+        self.emit("calibration_progress", CalibrationProgressPayload {
+            phase,
+            progress,
+            level_db,
+        });
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -110,8 +160,34 @@ pub struct StateChangePayload {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct AudioLevelPayload {
+    /// RMS (线性, 0.0 - 1.0)
     pub level: f32,
+    /// 峰值 (线性)
     pub peak: f32,
+    /// RMS (dBFS)
+    pub rms_db: f32,
+    /// 峰值 (dBFS)
+    pub peak_db: f32,
+    /// 峰值保持指示 (dBFS)
+    pub peak_hold_db: f32,
+    /// 会话开始以来的削波样本数
+    pub clipped_samples: u64,
+    /// 最近一个间隔内是否削波
+    pub clipping: bool,
+}
+
+impl From<&LevelReading> for AudioLevelPayload {
+    fn from(reading: &LevelReading) -> Self {
+        Self {
+            level: reading.rms,
+            peak: reading.peak,
+            rms_db: reading.rms_db,
+            peak_db: reading.peak_db,
+            peak_hold_db: reading.peak_hold_db,
+            clipped_samples: reading.clipped_samples,
+            clipping: reading.clipping,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -124,6 +200,8 @@ pub struct PartialTranscriptPayload {
 pub struct CommittedTranscriptPayload {
     pub text: String,
     pub confidence: f64,
+    /// 说话方标签 (如 "me"、"remote")，单路输入时为 None
+    pub speaker: Option<String>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -139,6 +217,45 @@ pub struct RecordingStatePayload {
     pub is_recording: bool,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDevicesChangedPayload {
+    pub added: Vec<DeviceSummary>,
+    pub removed: Vec<DeviceSummary>,
+    pub devices: Vec<DeviceSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceSwitchedPayload {
+    /// 原设备 ID
+    pub from: Option<String>,
+    /// 新设备 ID
+    pub to: Option<String>,
+    /// 新设备名称
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoStopCountdownPayload {
+    /// 距自动停止的剩余时长 (毫秒)，None 表示倒计时取消
+    pub remaining_ms: Option<u64>,
+    pub reason: Option<AutoStopReason>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoStoppedPayload {
+    pub reason: AutoStopReason,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationProgressPayload {
+    /// 当前录制阶段
+    pub phase: CalibrationPhase,
+    /// 完成比例 (0.0 - 1.0)
+    pub progress: f32,
+    /// 当前电平 (dBFS)
+    pub level_db: f32,
+}
+
 /// 事件分发器
 ///
 /// 将内部事件转发到前端
@@ -12,9 +12,12 @@ mod modules;
 
 use anyhow::Result;
 use commands::*;
+use modules::audio::{Calibrator, DeviceWatcher};
 use modules::config::ConfigManager;
 use modules::input::InputManager;
+use modules::lifecycle::AppConfig;
 use modules::network::scribe_client::ScribeClient;
+use modules::session::{DictationSession, SessionConfig};
 use modules::shortcut::HotkeyManager;
 use tauri::Manager;
 
@@ -22,17 +25,10 @@ pub use commands::*;
 pub use error::{AppError as AudioFlowError, ErrorCode};
 pub use state::AppState;
 
-const APP_DIR: &str = "audio-flow";
-
 /// 运行应用
 pub fn run() -> Result<()> {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join(APP_DIR);
-
-    if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir)?;
-    }
+    let app_config = AppConfig::default();
+    app_config.ensure_dirs()?;
 
     let runtime_state = AppState::new();
 
@@ -57,6 +53,8 @@ pub fn run() -> Result<()> {
             get_app_name,
             // 音频
             get_audio_devices,
+            get_channel_routing,
+            set_channel_routing,
             start_listen,
             stop_listen,
             get_recording_status,
@@ -84,13 +82,19 @@ pub fn run() -> Result<()> {
             // VAD
             set_vad_level,
             get_vad_level,
+            // 校准
+            calibrate_phase,
+            finish_calibration,
         ])
         .setup(move |app| {
             // 管理工作状态
             app.manage(runtime_state);
 
             // 管理配置
-            let config_manager = ConfigManager::new(config_dir.clone());
+            let config_manager = ConfigManager::new(app_config.config_dir.clone());
+            let audio_settings = config_manager.load()
+                .map(|config| config.audio)
+                .unwrap_or_default();
             app.manage(config_manager);
 
             // 管理 Scribe 客户端
@@ -101,6 +105,25 @@ pub fn run() -> Result<()> {
             let input_manager = tauri::async_runtime::Mutex::new(InputManager::new());
             app.manage(input_manager);
 
+            // 监听音频设备热插拔
+            let mut device_watcher = DeviceWatcher::new();
+            if let Err(e) = device_watcher.start(app.handle().clone()) {
+                tracing::warn!("Failed to start device watcher: {}", e);
+            }
+            app.manage(tauri::async_runtime::Mutex::new(device_watcher));
+
+            // 管理麦克风校准状态
+            app.manage(tauri::async_runtime::Mutex::new(Calibrator::new()));
+
+            // 管理听写会话，开启待机采集时在首次热键前打开麦克风
+            let mut dictation_session = DictationSession::new();
+            dictation_session.set_standby(&SessionConfig::from_settings(&audio_settings, &app_config.data_dir));
+            let dictation_session = tauri::async_runtime::Mutex::new(dictation_session);
+            app.manage(dictation_session);
+
+            // 管理应用目录，会话录音保存在数据目录下
+            app.manage(app_config);
+
             // 初始化快捷键管理器
             let shortcut_manager = HotkeyManager::new();
             app.manage(shortcut_manager);
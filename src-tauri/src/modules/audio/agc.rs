@@ -0,0 +1,237 @@
+//! 自动增益控制模块
+//!
+//! 按帧 RMS 将音量拉向目标响度：电平上升时按起音时间快速降低增益，
+//! 下降时按释放时间缓慢恢复；低于噪声门限的帧保持增益不变，且放大后不超过门限，
+//! 避免把底噪抬到 VAD 阈值以上。输出经峰值限幅，保证不会削波
+
+/// AGC 配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgcConfig {
+    /// 目标 RMS 电平 (dBFS)
+    pub target_dbfs: f32,
+    /// 最大增益 (dB)
+    pub max_gain_db: f32,
+    /// 最小增益 (dB)，负值表示允许衰减过响的输入
+    pub min_gain_db: f32,
+    /// 起音时间 (毫秒)，电平上升时增益下降的时间常数
+    pub attack_ms: f32,
+    /// 释放时间 (毫秒)，电平下降时增益恢复的时间常数
+    pub release_ms: f32,
+    /// 噪声门限 (dBFS)，低于此电平的帧不调整增益，输出也不超过此电平。
+    /// 位于 VAD 之前时按 [`AgcConfig::with_vad_threshold`] 推导
+    pub noise_gate_dbfs: f32,
+    /// 限幅电平 (dBFS)，输出峰值不超过此值
+    pub limiter_dbfs: f32,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        Self {
+            target_dbfs: -20.0,
+            max_gain_db: 30.0,
+            min_gain_db: -10.0,
+            attack_ms: 10.0,
+            release_ms: 300.0,
+            // VAD 默认阈值 (-25dBFS) 减去最大增益
+            noise_gate_dbfs: -55.0,
+            limiter_dbfs: -1.0,
+        }
+    }
+}
+
+impl AgcConfig {
+    /// 按 VAD 阈值 (dBFS) 推导噪声门限
+    ///
+    /// 最大增益也抬不到 VAD 阈值的电平视为底噪，门限 = VAD 阈值 - 最大增益
+    pub fn with_vad_threshold(self, threshold_dbfs: f32) -> Self {
+        Self { noise_gate_dbfs: threshold_dbfs - self.max_gain_db, ..self }
+    }
+}
+
+/// 自动增益控制器
+///
+/// 增益在帧内线性过渡，避免帧边界处的增益跳变
+#[derive(Debug, Clone)]
+pub struct AutoGainControl {
+    config: AgcConfig,
+    sample_rate: u32,
+    /// 当前增益 (dB)
+    gain_db: f32,
+    /// 上一帧末尾实际应用的增益 (dB)，低于门限的帧可能小于 `gain_db`
+    applied_db: f32,
+}
+
+impl AutoGainControl {
+    /// 创建 AGC，初始增益为 0dB
+    pub fn new(config: AgcConfig, sample_rate: u32) -> Self {
+        Self { config, sample_rate, gain_db: 0.0, applied_db: 0.0 }
+    }
+
+    /// 当前增益 (dB)
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// 重置增益
+    pub fn reset(&mut self) {
+        self.gain_db = 0.0;
+        self.applied_db = 0.0;
+    }
+
+    /// 处理一帧单声道音频
+    pub fn process(&mut self, frame: &[f32]) -> Vec<f32> {
+        if frame.is_empty() {
+            return Vec::new();
+        }
+
+        let start_db = self.applied_db;
+        let level_db = rms_dbfs(frame);
+        if level_db > self.config.noise_gate_dbfs {
+            let desired_db = (self.config.target_dbfs - level_db)
+                .clamp(self.config.min_gain_db, self.config.max_gain_db);
+            let time_ms = if desired_db < self.gain_db {
+                self.config.attack_ms
+            } else {
+                self.config.release_ms
+            };
+            let frame_ms = frame.len() as f32 * 1000.0 / self.sample_rate.max(1) as f32;
+            let coefficient = 1.0 - (-frame_ms / time_ms.max(f32::EPSILON)).exp();
+            self.gain_db += coefficient * (desired_db - self.gain_db);
+            self.applied_db = self.gain_db;
+        } else {
+            // 语音间隙中保持增益，但不把底噪放大到门限以上
+            self.applied_db = self.gain_db.min(self.config.noise_gate_dbfs - level_db);
+        }
+
+        // 帧内从上一增益线性过渡到新增益
+        let start = db_to_linear(start_db);
+        let end = db_to_linear(self.applied_db);
+        let step = (end - start) / frame.len() as f32;
+        let mut output: Vec<f32> = frame
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| x * (start + step * (i + 1) as f32))
+            .collect();
+
+        // 限幅：峰值超限时整帧按比例压低，并把增益同步下调
+        let limit = db_to_linear(self.config.limiter_dbfs);
+        let peak = output.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+        if peak > limit {
+            let scale = limit / peak;
+            output.iter_mut().for_each(|s| *s *= scale);
+            self.gain_db += 20.0 * scale.log10();
+            self.applied_db += 20.0 * scale.log10();
+        }
+        output
+    }
+}
+
+/// 帧 RMS 电平 (dBFS)
+fn rms_dbfs(frame: &[f32]) -> f32 {
+    let mean_square = frame.iter().map(|&x| x * x).sum::<f32>() / frame.len().max(1) as f32;
+    if mean_square <= 0.0 {
+        return f32::NEG_INFINITY;
+    }
+    10.0 * mean_square.log10()
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::test_signals;
+
+    const RATE: u32 = 16000;
+    const FRAME: usize = 320;
+
+    /// 440Hz 正弦波，按帧切分
+    fn tone(amplitude: f32, frames: usize) -> Vec<Vec<f32>> {
+        test_signals::tone(440.0, amplitude, RATE, frames * FRAME)
+            .chunks(FRAME)
+            .map(<[f32]>::to_vec)
+            .collect()
+    }
+
+    fn run(agc: &mut AutoGainControl, frames: &[Vec<f32>]) -> Vec<Vec<f32>> {
+        frames.iter().map(|f| agc.process(f)).collect()
+    }
+
+    #[test]
+    fn test_quiet_input_is_boosted_to_target() {
+        let mut agc = AutoGainControl::new(AgcConfig::default(), RATE);
+        // 约 -40dBFS RMS 的安静输入
+        let output = run(&mut agc, &tone(0.014, 150));
+
+        let level = rms_dbfs(output.last().unwrap());
+        assert!((level + 20.0).abs() < 1.0, "unexpected level {:.1}dBFS", level);
+    }
+
+    #[test]
+    fn test_gain_limited_by_max_gain() {
+        let config = AgcConfig { max_gain_db: 12.0, ..Default::default() };
+        let mut agc = AutoGainControl::new(config, RATE);
+        run(&mut agc, &tone(0.003, 200));
+        assert!((agc.gain_db() - 12.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_noise_gate_holds_gain() {
+        let mut agc = AutoGainControl::new(AgcConfig::default(), RATE);
+        let output = run(&mut agc, &tone(0.0005, 50));
+        assert_eq!(agc.gain_db(), 0.0);
+        assert_eq!(output[10], tone(0.0005, 50)[10]);
+    }
+
+    #[test]
+    fn test_gate_derived_from_vad_threshold() {
+        let config = AgcConfig::default().with_vad_threshold(-25.0);
+        assert_eq!(config.noise_gate_dbfs, AgcConfig::default().noise_gate_dbfs);
+        let config = AgcConfig { max_gain_db: 20.0, ..Default::default() }.with_vad_threshold(-22.5);
+        assert_eq!(config.noise_gate_dbfs, -42.5);
+    }
+
+    #[test]
+    fn test_noise_after_speech_stays_below_gate() {
+        let config = AgcConfig::default();
+        let mut agc = AutoGainControl::new(config, RATE);
+        run(&mut agc, &tone(0.01, 150));
+        let boosted = agc.gain_db();
+        assert!(boosted > 20.0);
+
+        // 语音间隙的底噪不会被已抬高的增益放大到门限以上 (首帧为增益过渡)，增益本身保持
+        let output = run(&mut agc, &tone(0.0005, 50));
+        assert!(output[1..].iter().all(|f| rms_dbfs(f) < config.noise_gate_dbfs + 0.1));
+        assert_eq!(agc.gain_db(), boosted);
+    }
+
+    #[test]
+    fn test_loud_input_is_attenuated_quickly() {
+        let mut agc = AutoGainControl::new(AgcConfig::default(), RATE);
+        run(&mut agc, &tone(0.01, 150));
+        let boosted = agc.gain_db();
+        assert!(boosted > 20.0);
+
+        // 突然变响时 (如靠近麦克风) 增益在几帧内降下来，输出始终不削波
+        let output = run(&mut agc, &tone(0.9, 5));
+        let limit = db_to_linear(AgcConfig::default().limiter_dbfs);
+        assert!(output.iter().flatten().all(|s| s.abs() <= limit + 1e-6));
+        assert!(agc.gain_db() < 0.0, "gain still {:.1}dB", agc.gain_db());
+    }
+
+    #[test]
+    fn test_release_is_slower_than_attack() {
+        let mut agc = AutoGainControl::new(AgcConfig::default(), RATE);
+        run(&mut agc, &tone(0.1, 100));
+        let settled = agc.gain_db();
+
+        // 一帧内下降 (起音) 的幅度远大于上升 (释放)
+        let mut down = agc.clone();
+        down.process(&tone(0.5, 1)[0]);
+        let mut up = agc.clone();
+        up.process(&tone(0.02, 1)[0]);
+        assert!(settled - down.gain_db() > 5.0 * (up.gain_db() - settled));
+    }
+}
@@ -0,0 +1,300 @@
+//! 自动停止模块
+//!
+//! 免手动停止 (hands-free) 听写：根据 VAD 状态跟踪语音结束后的静音时长，
+//! 达到设定时长或会话超过最长时长时结束会话，结束前给出倒计时
+
+use super::vad::VadState;
+use crate::modules::config::HandsFreeSettings;
+use serde::Serialize;
+use std::time::Duration;
+
+/// 倒计时事件的最小发送间隔
+const COUNTDOWN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 自动停止原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStopReason {
+    /// 静音达到设定时长
+    Silence,
+    /// 会话达到最长时长
+    MaxDuration,
+}
+
+/// 自动停止事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoStopEvent {
+    /// 即将自动停止，`remaining` 后停止
+    Countdown { remaining: Duration, reason: AutoStopReason },
+    /// 重新检测到语音，倒计时取消
+    Cancelled,
+    /// 应当结束会话
+    Stop(AutoStopReason),
+}
+
+/// 自动停止配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoStopConfig {
+    /// 语音结束后自动停止前的静音时长
+    pub trailing_silence: Duration,
+    /// 一直未检测到语音时自动停止的时长
+    pub initial_silence: Duration,
+    /// 会话最长时长，None 表示不限制
+    pub max_session: Option<Duration>,
+    /// 倒计时时长
+    pub countdown: Duration,
+    /// VAD 的静音超时 (语音结束后保持语音状态的时长)，计入语音结束后的静音
+    pub vad_hang: Duration,
+}
+
+impl AutoStopConfig {
+    /// 根据用户设置构建，未启用时返回 None
+    pub fn from_settings(settings: &HandsFreeSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        let ms = |ms: u32| Duration::from_millis(ms as u64);
+        Some(Self {
+            trailing_silence: ms(settings.trailing_silence_ms),
+            initial_silence: ms(settings.initial_silence_ms),
+            max_session: (settings.max_session_ms > 0).then(|| ms(settings.max_session_ms)),
+            countdown: ms(settings.countdown_ms),
+            vad_hang: Duration::ZERO,
+        })
+    }
+
+    /// 按 VAD 的静音超时设置 `vad_hang`
+    pub fn with_vad_hang(self, vad_hang: Duration) -> Self {
+        Self { vad_hang, ..self }
+    }
+}
+
+/// 自动停止判定
+///
+/// 逐段输入 VAD 状态及其对应的音频时长。静音按 VAD 状态机计：
+/// VAD 在静音持续 `vad_hang` 后才离开语音状态，离开时静音从 `vad_hang` 起累计，
+/// 停止时间与实际的静音时长一致
+#[derive(Debug, Clone)]
+pub struct AutoStop {
+    config: AutoStopConfig,
+    /// 会话已处理的音频时长
+    elapsed: Duration,
+    /// 当前连续静音时长
+    silence: Duration,
+    /// 是否检测到过语音
+    heard_speech: bool,
+    /// 上一段是否处于语音状态
+    in_speech: bool,
+    /// 进行中的倒计时原因及上次发送时的剩余时长
+    countdown: Option<(AutoStopReason, Duration)>,
+    /// 是否已给出停止事件
+    stopped: bool,
+}
+
+impl AutoStop {
+    /// 创建判定器
+    pub fn new(config: AutoStopConfig) -> Self {
+        Self {
+            config,
+            elapsed: Duration::ZERO,
+            silence: Duration::ZERO,
+            heard_speech: false,
+            in_speech: false,
+            countdown: None,
+            stopped: false,
+        }
+    }
+
+    /// 输入一段音频的 VAD 状态
+    ///
+    /// # Arguments
+    /// * `state` - 该段音频结束时的 VAD 状态
+    /// * `duration` - 该段音频的时长
+    ///
+    /// # Returns
+    /// 需要通知的事件，停止事件只给出一次
+    pub fn update(&mut self, state: VadState, duration: Duration) -> Option<AutoStopEvent> {
+        if self.stopped {
+            return None;
+        }
+        self.elapsed += duration;
+        match state {
+            VadState::Speech => {
+                self.silence = Duration::ZERO;
+                self.heard_speech = true;
+                self.in_speech = true;
+            }
+            // 语音结束时已静音 `vad_hang`
+            VadState::Ending => {
+                self.silence = self.config.vad_hang;
+                self.heard_speech = true;
+                self.in_speech = false;
+            }
+            // 采集段可能跨过只持续一帧的 Ending
+            VadState::Silence if self.in_speech => {
+                self.silence = self.config.vad_hang + duration;
+                self.in_speech = false;
+            }
+            VadState::Silence => self.silence += duration,
+        }
+
+        let silence_limit = if self.heard_speech {
+            self.config.trailing_silence
+        } else {
+            self.config.initial_silence
+        };
+        let silence_remaining = silence_limit.saturating_sub(self.silence);
+        let (remaining, reason) = match self.config.max_session {
+            Some(max) if max.saturating_sub(self.elapsed) <= silence_remaining => {
+                (max.saturating_sub(self.elapsed), AutoStopReason::MaxDuration)
+            }
+            _ => (silence_remaining, AutoStopReason::Silence),
+        };
+
+        if remaining.is_zero() {
+            self.stopped = true;
+            self.countdown = None;
+            return Some(AutoStopEvent::Stop(reason));
+        }
+        if remaining > self.config.countdown {
+            return self.countdown.take().map(|_| AutoStopEvent::Cancelled);
+        }
+        match self.countdown {
+            Some((last_reason, last_remaining))
+                if last_reason == reason && last_remaining.saturating_sub(remaining) < COUNTDOWN_INTERVAL =>
+            {
+                None
+            }
+            _ => {
+                self.countdown = Some((reason, remaining));
+                Some(AutoStopEvent::Countdown { remaining, reason })
+            }
+        }
+    }
+
+    /// 更新 VAD 的静音超时 (如切换 VAD 级别)
+    pub fn set_vad_hang(&mut self, vad_hang: Duration) {
+        self.config.vad_hang = vad_hang;
+    }
+
+    /// 会话已处理的音频时长
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// 是否已给出停止事件
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(20);
+
+    fn config() -> AutoStopConfig {
+        AutoStopConfig::from_settings(&HandsFreeSettings {
+            enabled: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// 输入指定时长的同一状态，返回期间的全部事件
+    fn feed(auto_stop: &mut AutoStop, state: VadState, ms: u64) -> Vec<AutoStopEvent> {
+        (0..ms / STEP.as_millis() as u64)
+            .filter_map(|_| auto_stop.update(state, STEP))
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_stop_after_trailing_silence() {
+        let mut auto_stop = AutoStop::new(config());
+        assert!(feed(&mut auto_stop, VadState::Speech, 1000).is_empty());
+
+        // 静音 1 秒后开始倒计时，每 200ms 更新一次，2 秒时停止
+        let events = feed(&mut auto_stop, VadState::Silence, 2000);
+        assert_eq!(events.first(), Some(&AutoStopEvent::Countdown {
+            remaining: Duration::from_millis(1000),
+            reason: AutoStopReason::Silence,
+        }));
+        assert_eq!(events.len(), 1 + 5);
+        assert_eq!(events.last(), Some(&AutoStopEvent::Stop(AutoStopReason::Silence)));
+        assert!(auto_stop.is_stopped());
+        assert!(auto_stop.update(VadState::Silence, STEP).is_none());
+    }
+
+    #[test]
+    fn test_auto_stop_speech_cancels_countdown() {
+        let mut auto_stop = AutoStop::new(config());
+        feed(&mut auto_stop, VadState::Speech, 500);
+        let events = feed(&mut auto_stop, VadState::Silence, 1500);
+        assert!(matches!(events[0], AutoStopEvent::Countdown { .. }));
+
+        assert_eq!(auto_stop.update(VadState::Speech, STEP), Some(AutoStopEvent::Cancelled));
+        assert!(feed(&mut auto_stop, VadState::Speech, 500).is_empty());
+        assert!(!auto_stop.is_stopped());
+    }
+
+    #[test]
+    fn test_auto_stop_counts_vad_hang_as_silence() {
+        let hang = Duration::from_millis(300);
+        let mut auto_stop = AutoStop::new(config().with_vad_hang(hang));
+        feed(&mut auto_stop, VadState::Speech, 1000);
+
+        // VAD 在 300ms 静音后才回到 Silence，其后只需再静音 1.7 秒
+        let events = feed(&mut auto_stop, VadState::Silence, 1700);
+        assert_eq!(events.last(), Some(&AutoStopEvent::Stop(AutoStopReason::Silence)));
+
+        // 经过 Ending 时同样计入
+        let mut auto_stop = AutoStop::new(config().with_vad_hang(hang));
+        feed(&mut auto_stop, VadState::Speech, 1000);
+        assert!(auto_stop.update(VadState::Ending, STEP).is_none());
+        let events = feed(&mut auto_stop, VadState::Silence, 1700);
+        assert_eq!(events.last(), Some(&AutoStopEvent::Stop(AutoStopReason::Silence)));
+    }
+
+    #[test]
+    fn test_auto_stop_waits_longer_before_first_speech() {
+        let mut auto_stop = AutoStop::new(config());
+        assert!(feed(&mut auto_stop, VadState::Silence, 6000).is_empty());
+        let events = feed(&mut auto_stop, VadState::Silence, 2000);
+        assert_eq!(events.first(), Some(&AutoStopEvent::Countdown {
+            remaining: Duration::from_millis(1000),
+            reason: AutoStopReason::Silence,
+        }));
+        assert_eq!(events.last(), Some(&AutoStopEvent::Stop(AutoStopReason::Silence)));
+        assert_eq!(auto_stop.elapsed(), Duration::from_millis(8000));
+    }
+
+    #[test]
+    fn test_auto_stop_max_session_cap() {
+        let mut auto_stop = AutoStop::new(AutoStopConfig {
+            max_session: Some(Duration::from_secs(3)),
+            ..config()
+        });
+        // 持续说话也在最长时长处停止，倒计时不会被语音取消
+        let events = feed(&mut auto_stop, VadState::Speech, 3000);
+        assert_eq!(events[0], AutoStopEvent::Countdown {
+            remaining: Duration::from_millis(1000),
+            reason: AutoStopReason::MaxDuration,
+        });
+        assert!(!events.contains(&AutoStopEvent::Cancelled));
+        assert_eq!(events.last(), Some(&AutoStopEvent::Stop(AutoStopReason::MaxDuration)));
+    }
+
+    #[test]
+    fn test_auto_stop_config_from_settings() {
+        assert!(AutoStopConfig::from_settings(&HandsFreeSettings::default()).is_none());
+        let config = AutoStopConfig::from_settings(&HandsFreeSettings {
+            enabled: true,
+            max_session_ms: 0,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(config.max_session, None);
+        assert_eq!(config.trailing_silence, Duration::from_millis(2000));
+    }
+}
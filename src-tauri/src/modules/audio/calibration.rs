@@ -0,0 +1,430 @@
+//! 麦克风校准模块
+//!
+//! 分别录制一段环境静音与一段正常说话，按 VAD 帧长统计电平分布，
+//! 据此推荐 VAD 阈值、平滑因子与输入增益。
+//! 推荐值假定自动增益关闭，增益在 VAD 之前生效
+
+use super::capture::AudioFrame;
+use super::source::AudioSource;
+use crate::error::AudioError;
+use crate::modules::config::CalibrationSettings;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 统计帧长 (毫秒)，与会话 VAD 帧长一致
+const FRAME_MS: u32 = 20;
+/// 每个阶段至少需要的帧数 (0.5 秒)
+const MIN_FRAMES: usize = 25;
+/// 语音帧判定：高于噪声底的 dB 数
+const SPEECH_MARGIN_DB: f32 = 3.0;
+/// 可用的最低信噪比 (dB)
+const MIN_SNR_DB: f32 = 6.0;
+/// 增益目标：语音中位电平 (dBFS)
+const TARGET_SPEECH_DB: f32 = -26.0;
+/// 增益范围 (dB)
+const GAIN_RANGE_DB: (f32, f32) = (-10.0, 20.0);
+/// 增益后最响帧的 RMS 上限 (dBFS)，为峰值留出余量
+const HEADROOM_DB: f32 = -12.0;
+/// 电平下限 (dBFS)
+const FLOOR_DB: f32 = -100.0;
+/// 读取无数据时的等待间隔
+const IDLE_POLL: Duration = Duration::from_millis(5);
+/// 进度回调间隔 (帧数，约 100ms)
+const PROGRESS_FRAMES: usize = 5;
+/// 超过录制时长后仍无足够数据的等待上限
+const RECORD_GRACE: Duration = Duration::from_secs(2);
+
+/// 校准阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalibrationPhase {
+    /// 环境静音
+    Silence,
+    /// 正常说话
+    Speech,
+}
+
+/// 帧电平分布 (dBFS)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LevelDistribution {
+    /// 帧数
+    pub frames: usize,
+    pub min_db: f32,
+    pub p10_db: f32,
+    pub median_db: f32,
+    pub p90_db: f32,
+    pub max_db: f32,
+    /// 标准差 (dB)
+    pub std_db: f32,
+}
+
+impl LevelDistribution {
+    /// 统计帧电平，没有数据时返回 None
+    pub fn from_levels(levels: &[f32]) -> Option<Self> {
+        if levels.is_empty() {
+            return None;
+        }
+        let mut sorted = levels.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let mean = sorted.iter().sum::<f32>() / sorted.len() as f32;
+        let variance = sorted.iter().map(|l| (l - mean).powi(2)).sum::<f32>() / sorted.len() as f32;
+        Some(Self {
+            frames: sorted.len(),
+            min_db: sorted[0],
+            p10_db: percentile(&sorted, 0.1),
+            median_db: percentile(&sorted, 0.5),
+            p90_db: percentile(&sorted, 0.9),
+            max_db: sorted[sorted.len() - 1],
+            std_db: variance.sqrt(),
+        })
+    }
+}
+
+/// 已排序数据的分位数 (最近秩)
+fn percentile(sorted: &[f32], q: f32) -> f32 {
+    let index = ((sorted.len() - 1) as f32 * q).round() as usize;
+    sorted[index]
+}
+
+/// 帧电平采集器
+///
+/// 多通道先混合为单声道，按 20ms 分帧计算 RMS 电平
+#[derive(Debug, Clone, Default)]
+pub struct LevelCollector {
+    /// 尚未凑满一帧的样本
+    pending: Vec<f32>,
+    /// 当前帧长对应的采样率
+    sample_rate: u32,
+    /// 各帧电平 (dBFS)
+    levels: Vec<f32>,
+}
+
+impl LevelCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 送入一帧采集数据
+    pub fn push(&mut self, frame: &AudioFrame) {
+        if frame.sample_rate != self.sample_rate {
+            self.pending.clear();
+            self.sample_rate = frame.sample_rate;
+        }
+        let frame_size = (self.sample_rate * FRAME_MS / 1000).max(1) as usize;
+        self.pending.extend_from_slice(&frame.to_mono().samples);
+        while self.pending.len() >= frame_size {
+            let level = rms_db(&self.pending[..frame_size]);
+            self.levels.push(level);
+            self.pending.drain(..frame_size);
+        }
+    }
+
+    /// 各帧电平 (dBFS)
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// 已统计的音频时长
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.levels.len() as u64 * FRAME_MS as u64)
+    }
+
+    /// 最近一帧的电平
+    pub fn last_level(&self) -> f32 {
+        self.levels.last().copied().unwrap_or(FLOOR_DB)
+    }
+
+    /// 电平分布
+    pub fn distribution(&self) -> Option<LevelDistribution> {
+        LevelDistribution::from_levels(&self.levels)
+    }
+}
+
+/// 从已启动的音频源录制一个阶段
+///
+/// # Arguments
+/// * `source` - 已配置并启动的音频源
+/// * `duration` - 录制时长
+/// * `on_progress` - 进度回调 (完成比例 0.0 - 1.0, 当前电平 dBFS)，约每 100ms 一次
+pub fn record_phase(
+    source: &mut dyn AudioSource,
+    duration: Duration,
+    mut on_progress: impl FnMut(f32, f32),
+) -> Result<LevelCollector, AudioError> {
+    let mut collector = LevelCollector::new();
+    let deadline = Instant::now() + duration + RECORD_GRACE;
+    let read_size = 1024 * source.channels().max(1) as usize;
+    let mut reported = 0;
+
+    while collector.duration() < duration {
+        match source.read_frame(read_size) {
+            Some(frame) => collector.push(&frame),
+            None if source.is_finished() => break,
+            None if Instant::now() > deadline => {
+                return Err(AudioError::CaptureFailed("No audio received during calibration".to_string()));
+            }
+            None => std::thread::sleep(IDLE_POLL),
+        }
+        if source.is_stream_lost() {
+            return Err(AudioError::CaptureFailed("Audio device lost during calibration".to_string()));
+        }
+        if collector.levels().len() >= reported + PROGRESS_FRAMES {
+            reported = collector.levels().len();
+            let progress = collector.duration().as_secs_f32() / duration.as_secs_f32().max(f32::EPSILON);
+            on_progress(progress.min(1.0), collector.last_level());
+        }
+    }
+    on_progress(1.0, collector.last_level());
+    Ok(collector)
+}
+
+/// 校准结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CalibrationResult {
+    /// 静音阶段电平分布
+    pub noise: LevelDistribution,
+    /// 语音阶段中说话帧的电平分布
+    pub speech: LevelDistribution,
+    /// 噪声底 (dBFS，静音阶段 90 分位)
+    pub noise_floor_db: f32,
+    /// 语音电平 (dBFS，说话帧中位数)
+    pub speech_level_db: f32,
+    /// 信噪比 (dB)
+    pub snr_db: f32,
+    /// 推荐设置
+    pub recommended: CalibrationSettings,
+}
+
+/// 根据两个阶段的帧电平计算推荐设置
+///
+/// 阈值取噪声底与语音电平之间靠近噪声的一点 (加上推荐增益)，
+/// 并换算到 [`super::VoiceActivityDetector`] 的能量刻度 (均方值的 20·log10，即 RMS dBFS 的两倍)。
+/// 噪声起伏越大，平滑因子越小，当前帧在平滑能量中的权重越低
+pub fn analyze(silence: &[f32], speech: &[f32]) -> Result<CalibrationResult, AudioError> {
+    let noise = LevelDistribution::from_levels(silence)
+        .filter(|d| d.frames >= MIN_FRAMES)
+        .ok_or_else(|| calibration_error("Not enough silence recorded"))?;
+    let noise_floor_db = noise.p90_db;
+
+    // 语音阶段中的停顿不参与统计
+    let active: Vec<f32> = speech
+        .iter()
+        .copied()
+        .filter(|&l| l > noise_floor_db + SPEECH_MARGIN_DB)
+        .collect();
+    let speech_dist = LevelDistribution::from_levels(&active)
+        .filter(|d| speech.len() >= MIN_FRAMES && d.frames >= speech.len() / 4)
+        .ok_or_else(|| calibration_error("No speech detected, speak closer to the microphone"))?;
+    let speech_level_db = speech_dist.median_db;
+
+    let snr_db = speech_level_db - noise_floor_db;
+    if snr_db < MIN_SNR_DB {
+        return Err(calibration_error("Speech is barely louder than the background noise"));
+    }
+
+    let gain_db = (TARGET_SPEECH_DB - speech_level_db)
+        .clamp(GAIN_RANGE_DB.0, GAIN_RANGE_DB.1)
+        .min(HEADROOM_DB - speech_dist.max_db);
+    let threshold_dbfs = noise_floor_db + (snr_db * 0.35).max(SPEECH_MARGIN_DB) + gain_db;
+    let smoothing_factor = (0.5 - 0.05 * noise.std_db).clamp(0.1, 0.5);
+
+    Ok(CalibrationResult {
+        noise,
+        speech: speech_dist,
+        noise_floor_db,
+        speech_level_db,
+        snr_db,
+        recommended: CalibrationSettings {
+            threshold_db: 2.0 * threshold_dbfs,
+            smoothing_factor,
+            gain_db,
+        },
+    })
+}
+
+fn calibration_error(message: &str) -> AudioError {
+    AudioError::ConfigurationFailed(format!("Calibration failed: {}", message))
+}
+
+/// 校准向导状态，保存已录制阶段的帧电平
+#[derive(Debug, Default)]
+pub struct Calibrator {
+    silence: Option<Vec<f32>>,
+    speech: Option<Vec<f32>>,
+}
+
+impl Calibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存一个阶段的录制结果 (覆盖之前的同阶段结果)
+    pub fn set_phase(&mut self, phase: CalibrationPhase, levels: Vec<f32>) {
+        match phase {
+            CalibrationPhase::Silence => self.silence = Some(levels),
+            CalibrationPhase::Speech => self.speech = Some(levels),
+        }
+    }
+
+    /// 两个阶段均已录制时计算结果
+    pub fn analyze(&self) -> Result<CalibrationResult, AudioError> {
+        match (&self.silence, &self.speech) {
+            (Some(silence), Some(speech)) => analyze(silence, speech),
+            (None, _) => Err(calibration_error("Silence phase not recorded")),
+            (_, None) => Err(calibration_error("Speech phase not recorded")),
+        }
+    }
+
+    /// 清空已录制的阶段
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// 帧 RMS 电平 (dBFS)，不低于 [`FLOOR_DB`]
+fn rms_db(samples: &[f32]) -> f32 {
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32;
+    if mean_square <= 0.0 {
+        return FLOOR_DB;
+    }
+    (10.0 * mean_square.log10()).max(FLOOR_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::{FileSource, PlaybackSpeed, Signal, SyntheticSource, VadConfig, VoiceActivityDetector};
+
+    /// 在 `center` 附近交替起伏的帧电平
+    fn levels(center: f32, spread: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| center + spread * ((i % 5) as f32 - 2.0) / 2.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_level_distribution() {
+        let dist = LevelDistribution::from_levels(&[-50.0, -40.0, -30.0, -20.0, -10.0]).unwrap();
+        assert_eq!(dist.frames, 5);
+        assert_eq!(dist.min_db, -50.0);
+        assert_eq!(dist.median_db, -30.0);
+        assert_eq!(dist.max_db, -10.0);
+        assert!((dist.std_db - 14.142).abs() < 0.01);
+        assert!(LevelDistribution::from_levels(&[]).is_none());
+    }
+
+    #[test]
+    fn test_collector_frames_and_levels() {
+        let mut collector = LevelCollector::new();
+        // 立体声 50ms @ 16kHz，幅度 0.1 的方波
+        let samples: Vec<f32> = (0..1600).map(|n| if (n / 2) % 2 == 0 { 0.1 } else { -0.1 }).collect();
+        collector.push(&AudioFrame::new(samples, 16000, 2, 0));
+        assert_eq!(collector.levels().len(), 2);
+        assert_eq!(collector.duration(), Duration::from_millis(40));
+        assert!((collector.last_level() + 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_analyze_recommends_settings() {
+        let silence = levels(-60.0, 4.0, 100);
+        let mut speech = levels(-36.0, 6.0, 100);
+        // 语音中的停顿
+        speech.extend(levels(-60.0, 4.0, 30));
+
+        let result = analyze(&silence, &speech).unwrap();
+        assert_eq!(result.noise_floor_db, -56.0);
+        assert_eq!(result.speech.frames, 100);
+        assert_eq!(result.speech_level_db, -36.0);
+        assert_eq!(result.snr_db, 20.0);
+
+        let recommended = result.recommended;
+        assert_eq!(recommended.gain_db, 10.0);
+        // 阈值 (dBFS) 位于增益后的噪声底与语音电平之间
+        let threshold_dbfs = recommended.threshold_db / 2.0;
+        assert!(threshold_dbfs > result.noise_floor_db + 10.0 + SPEECH_MARGIN_DB);
+        assert!(threshold_dbfs < result.speech.p10_db + 10.0);
+        assert!((0.1..=0.5).contains(&recommended.smoothing_factor));
+    }
+
+    #[test]
+    fn test_analyze_limits_gain_by_headroom() {
+        let silence = levels(-70.0, 2.0, 50);
+        // 语音偏轻但偶有 -14dBFS 的响帧
+        let mut speech = vec![-40.0; 80];
+        speech.extend(vec![-14.0; 20]);
+        let result = analyze(&silence, &speech).unwrap();
+        assert_eq!(result.speech_level_db, -40.0);
+        assert_eq!(result.recommended.gain_db, 2.0);
+        assert!(result.speech.max_db + result.recommended.gain_db <= HEADROOM_DB);
+    }
+
+    #[test]
+    fn test_noisy_room_lowers_smoothing_factor() {
+        let speech = levels(-30.0, 4.0, 100);
+        let steady = analyze(&levels(-60.0, 1.0, 100), &speech).unwrap();
+        let fluctuating = analyze(&levels(-60.0, 12.0, 100), &speech).unwrap();
+        assert!(fluctuating.recommended.smoothing_factor < steady.recommended.smoothing_factor);
+    }
+
+    #[test]
+    fn test_analyze_rejects_bad_recordings() {
+        let silence = levels(-60.0, 2.0, 100);
+        // 静音过短
+        assert!(analyze(&silence[..10], &levels(-30.0, 4.0, 100)).is_err());
+        // 没有说话
+        assert!(analyze(&silence, &levels(-60.0, 2.0, 100)).is_err());
+        // 信噪比不足
+        assert!(analyze(&silence, &levels(-54.0, 1.0, 100)).is_err());
+    }
+
+    #[test]
+    fn test_calibrator_requires_both_phases() {
+        let mut calibrator = Calibrator::new();
+        calibrator.set_phase(CalibrationPhase::Silence, levels(-60.0, 2.0, 100));
+        assert!(calibrator.analyze().is_err());
+        calibrator.set_phase(CalibrationPhase::Speech, levels(-30.0, 4.0, 100));
+        assert!(calibrator.analyze().is_ok());
+        calibrator.reset();
+        assert!(calibrator.analyze().is_err());
+    }
+
+    #[test]
+    fn test_record_and_calibrate_vad() {
+        fn record(signal: Signal) -> Vec<f32> {
+            let mut generator = SyntheticSource::new(signal, 16000, 1, None, PlaybackSpeed::AsFastAsPossible);
+            let samples = generator.generate(16000);
+            let mut source = FileSource::from_samples(samples, 16000, 1, PlaybackSpeed::AsFastAsPossible);
+            source.start().unwrap();
+            let mut progress = Vec::new();
+            let collector = record_phase(&mut source, Duration::from_secs(1), |p, _| progress.push(p)).unwrap();
+            assert_eq!(progress.last(), Some(&1.0));
+            assert!(progress.windows(2).all(|w| w[1] >= w[0]));
+            collector.levels().to_vec()
+        }
+
+        let silence = record(Signal::WhiteNoise { amplitude: 0.003, seed: 7 });
+        let speech = record(Signal::Tone { frequency: 300.0, amplitude: 0.05 });
+        assert_eq!(silence.len(), 50);
+        let result = analyze(&silence, &speech).unwrap();
+
+        // 按推荐阈值，底噪判为静音，放大后的语音判为语音
+        let recommended = result.recommended;
+        let config = VadConfig {
+            threshold_db: recommended.threshold_db,
+            smoothing_factor: recommended.smoothing_factor,
+            ..Default::default()
+        };
+        let gain = 10.0f32.powf(recommended.gain_db / 20.0);
+        let frame = |amplitude: f32, signal: fn(usize) -> f32| -> Vec<f32> {
+            (0..320).map(|n| amplitude * gain * signal(n)).collect()
+        };
+        let mut vad = VoiceActivityDetector::new(config);
+        for _ in 0..20 {
+            vad.detect(&frame(0.003, |n| if n % 3 == 0 { 1.0 } else { -0.5 }));
+        }
+        assert!(!vad.is_speaking());
+        for _ in 0..20 {
+            vad.detect(&frame(0.05, |n| (2.0 * std::f32::consts::PI * 300.0 * n as f32 / 16000.0).sin()));
+        }
+        assert!(vad.is_speaking());
+    }
+}
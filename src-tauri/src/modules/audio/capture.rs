@@ -2,11 +2,22 @@
 //!
 //! 使用 cpal 进行音频设备枚举和流采集
 
+use super::loopback::{
+    MonitorStream, cached_monitor_sources, is_loopback_device_name, monitor_source_name,
+    refresh_monitor_sources,
+};
 use crate::error::AudioError;
-use cpal::{Device, Host, Stream, StreamConfig};
+use cpal::{
+    BufferSize, Device, DeviceDescription, DeviceId, FromSample, Host, InputStreamTimestamp, Sample,
+    SampleFormat, SizedSample, Stream, StreamConfig, StreamError, StreamInstant, SupportedBufferSize,
+    SupportedStreamConfigRange,
+};
 use cpal::traits::{HostTrait, DeviceTrait, StreamTrait};
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{fence, AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
 /// 音频帧数据
 #[derive(Debug, Clone)]
@@ -17,7 +28,7 @@ pub struct AudioFrame {
     pub sample_rate: u32,
     /// 通道数
     pub channels: u16,
-    /// 时间戳 (纳秒)
+    /// 首个样本的采集时间 (纳秒，相对会话开始的单调时间)
     pub timestamp_ns: u128,
 }
 
@@ -26,7 +37,7 @@ impl AudioFrame {
         Self { samples, sample_rate, channels, timestamp_ns }
     }
 
-    /// 将多通道音频混合为单通道
+    /// 平均所有通道混合为单通道
     pub fn to_mono(&self) -> Self {
         if self.channels == 1 {
             return self.clone();
@@ -40,23 +51,215 @@ impl AudioFrame {
             .collect();
         Self::new(mono, self.sample_rate, 1, self.timestamp_ns)
     }
+
+    /// 叠加所有通道为单通道并限幅到 [-1, 1]
+    ///
+    /// 用于各通道为独立音源 (如本地麦克风与远端音频) 的情形，各音源保持原有响度
+    pub fn sum_channels(&self) -> Self {
+        if self.channels == 1 {
+            return self.clone();
+        }
+        let mono: Vec<f32> = self.samples
+            .chunks(self.channels as usize)
+            .map(|chunk| chunk.iter().sum::<f32>().clamp(-1.0, 1.0))
+            .collect();
+        Self::new(mono, self.sample_rate, 1, self.timestamp_ns)
+    }
+
+    /// 平均指定通道 (从 0 开始) 混合为单通道
+    ///
+    /// 超出通道数的索引被忽略，没有有效通道时退化为平均所有通道
+    pub fn mix_channels(&self, channels: &[u16]) -> Self {
+        let mut selected: Vec<usize> = channels
+            .iter()
+            .filter(|&&c| c < self.channels)
+            .map(|&c| c as usize)
+            .collect();
+        selected.sort_unstable();
+        selected.dedup();
+        if selected.is_empty() || selected.len() == self.channels as usize {
+            return self.to_mono();
+        }
+        let scale = 1.0 / selected.len() as f32;
+        let mono: Vec<f32> = self.samples
+            .chunks_exact(self.channels as usize)
+            .map(|chunk| selected.iter().map(|&c| chunk[c]).sum::<f32>() * scale)
+            .collect();
+        Self::new(mono, self.sample_rate, 1, self.timestamp_ns)
+    }
+
+    /// 提取单个通道，索引超出通道数时退化为平均所有通道
+    pub fn channel(&self, index: u16) -> Self {
+        self.mix_channels(&[index])
+    }
+
+    /// 各通道的均方能量
+    pub fn channel_energy(&self) -> Vec<f32> {
+        let channels = self.channels.max(1) as usize;
+        let mut energy = vec![0.0f32; channels];
+        for chunk in self.samples.chunks_exact(channels) {
+            for (e, s) in energy.iter_mut().zip(chunk) {
+                *e += s * s;
+            }
+        }
+        let frames = self.frames().max(1) as f32;
+        energy.iter_mut().for_each(|e| *e /= frames);
+        energy
+    }
+
+    /// 帧数 (每帧包含所有通道)
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    /// 时长 (纳秒)
+    pub fn duration_ns(&self) -> u128 {
+        frames_to_ns(self.frames() as u64, self.sample_rate)
+    }
+
+    /// 末尾之后下一个样本的采集时间 (纳秒)
+    pub fn end_timestamp_ns(&self) -> u128 {
+        self.timestamp_ns + self.duration_ns()
+    }
+}
+
+/// 将帧数换算为纳秒
+pub fn frames_to_ns(frames: u64, sample_rate: u32) -> u128 {
+    frames as u128 * 1_000_000_000 / sample_rate.max(1) as u128
 }
 
+/// 采样率范围 (Hz，闭区间)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleRateRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+/// 缓冲区大小范围 (帧，闭区间)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferSizeRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+/// 枚举设备时探测的常用采样率
+const COMMON_SAMPLE_RATES: [u32; 11] = [
+    8000, 11025, 16000, 22050, 32000, 44100, 48000, 88200, 96000, 176400, 192000,
+];
+/// 监听源可请求的采样率范围 (由 PulseAudio 重采样)
+const MONITOR_MIN_SAMPLE_RATE: u32 = 8000;
+const MONITOR_MAX_SAMPLE_RATE: u32 = 192000;
+/// 监听源的默认采样率
+const MONITOR_DEFAULT_SAMPLE_RATE: u32 = 48000;
+
 /// 音频设备信息
 #[derive(Debug, Clone)]
 pub struct AudioDeviceInfo {
     /// 设备名称
     pub name: String,
     /// 设备 ID (用于选择设备)
+    ///
+    /// 优先使用宿主 API 提供的稳定 ID (`host:device`)，重启后保持不变
     pub id: String,
-    /// 支持的采样率
+    /// 宿主音频 API 名称 (如 ALSA、CoreAudio、WASAPI)
+    pub host: String,
+    /// 默认采样率
+    pub default_sample_rate: u32,
+    /// 支持的常用采样率
     pub sample_rates: Vec<u32>,
+    /// 支持的采样率范围
+    pub sample_rate_ranges: Vec<SampleRateRange>,
+    /// 支持的采样格式
+    pub sample_formats: Vec<SampleFormat>,
+    /// 支持的通道数
+    pub channel_counts: Vec<u16>,
     /// 最大通道数
     pub channels: u16,
+    /// 支持的缓冲区大小范围，设备未报告时为空
+    pub buffer_size_ranges: Vec<BufferSizeRange>,
+    /// 是否为系统音频回环设备 (监听源等)
+    pub is_loopback: bool,
+}
+
+impl AudioDeviceInfo {
+    /// 根据设备支持的配置范围汇总能力信息
+    pub fn from_configs(
+        name: String,
+        id: String,
+        host: String,
+        default_sample_rate: u32,
+        ranges: &[SupportedStreamConfigRange],
+    ) -> Self {
+        let mut sample_rate_ranges = Vec::new();
+        let mut sample_formats = Vec::new();
+        let mut channel_counts = Vec::new();
+        let mut buffer_size_ranges = Vec::new();
+
+        for range in ranges {
+            push_unique(&mut sample_rate_ranges, SampleRateRange {
+                min: range.min_sample_rate(),
+                max: range.max_sample_rate(),
+            });
+            push_unique(&mut sample_formats, range.sample_format());
+            push_unique(&mut channel_counts, range.channels());
+            if let SupportedBufferSize::Range { min, max } = *range.buffer_size() {
+                push_unique(&mut buffer_size_ranges, BufferSizeRange { min, max });
+            }
+        }
+
+        sample_rate_ranges.sort_by_key(|r| (r.min, r.max));
+        channel_counts.sort_unstable();
+        buffer_size_ranges.sort_by_key(|r| (r.min, r.max));
+
+        let mut sample_rates: Vec<u32> = COMMON_SAMPLE_RATES
+            .iter()
+            .copied()
+            .filter(|rate| sample_rate_ranges.iter().any(|r| (r.min..=r.max).contains(rate)))
+            .collect();
+        if !sample_rates.contains(&default_sample_rate) {
+            sample_rates.push(default_sample_rate);
+            sample_rates.sort_unstable();
+        }
+
+        let channels = channel_counts.last().copied().unwrap_or(1);
+        let is_loopback = is_loopback_device_name(&name);
+
+        Self {
+            name,
+            id,
+            host,
+            default_sample_rate,
+            sample_rates,
+            sample_rate_ranges,
+            sample_formats,
+            channel_counts,
+            channels,
+            buffer_size_ranges,
+            is_loopback,
+        }
+    }
+}
+
+/// 输入设备摘要
+///
+/// 用于热插拔检测，仅包含无需打开设备即可获取的信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceSummary {
+    /// 设备 ID，与 [`AudioDeviceInfo::id`] 一致
+    pub id: String,
+    /// 设备名称
+    pub name: String,
+}
+
+/// 去重追加
+fn push_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
 }
 
 /// 音频配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AudioConfig {
     /// 设备 ID，None 表示使用默认设备
     pub device_id: Option<String>,
@@ -79,84 +282,487 @@ impl Default for AudioConfig {
     }
 }
 
-/// 环形缓冲区用于线程间音频数据传输
-#[derive(Debug)]
+/// 协商后的采集配置
+///
+/// 记录设备实际使用的采样格式、采样率和通道数，下游重采样以此为准
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedConfig {
+    /// 实际采样率 (Hz)
+    pub sample_rate: u32,
+    /// 实际通道数
+    pub channels: u16,
+    /// 设备原生采样格式 (回调中统一转换为 f32)
+    pub sample_format: SampleFormat,
+    /// 缓冲区大小
+    pub buffer_size: BufferSize,
+}
+
+impl NegotiatedConfig {
+    /// 转换为 cpal 流配置
+    pub fn stream_config(&self) -> StreamConfig {
+        StreamConfig {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            buffer_size: self.buffer_size,
+        }
+    }
+}
+
+/// 从设备支持的配置范围中选择最匹配请求的配置
+///
+/// 优先级：采样率差距 → 通道数匹配 → 采样格式 (f32 > i16 > i32 > 其他)
+pub fn negotiate_config(
+    ranges: &[SupportedStreamConfigRange],
+    requested: &AudioConfig,
+) -> Option<NegotiatedConfig> {
+    ranges
+        .iter()
+        .map(|range| {
+            let sample_rate = requested
+                .sample_rate
+                .clamp(range.min_sample_rate(), range.max_sample_rate());
+            let rate_distance = sample_rate.abs_diff(requested.sample_rate);
+            let score = (
+                rate_distance,
+                channel_penalty(range.channels(), requested.channels),
+                format_rank(range.sample_format()),
+            );
+            (score, range, sample_rate)
+        })
+        .min_by_key(|(score, _, _)| *score)
+        .map(|(_, range, sample_rate)| NegotiatedConfig {
+            sample_rate,
+            channels: range.channels(),
+            sample_format: range.sample_format(),
+            buffer_size: buffer_size_for(range.buffer_size(), sample_rate, requested.buffer_size_ms),
+        })
+}
+
+/// 通道数偏差，通道不足的配置排在最后
+fn channel_penalty(available: u16, requested: u16) -> u32 {
+    if available >= requested {
+        (available - requested) as u32
+    } else {
+        1000 + (requested - available) as u32
+    }
+}
+
+/// 采样格式优先级，数值越小越好
+fn format_rank(format: SampleFormat) -> u8 {
+    match format {
+        SampleFormat::F32 => 0,
+        SampleFormat::I16 => 1,
+        SampleFormat::I32 => 2,
+        SampleFormat::I24 => 3,
+        SampleFormat::U16 => 4,
+        SampleFormat::F64 => 5,
+        _ => 6,
+    }
+}
+
+/// 根据期望时长计算缓冲区大小，并限制在设备支持范围内
+fn buffer_size_for(supported: &SupportedBufferSize, sample_rate: u32, buffer_size_ms: u32) -> BufferSize {
+    let frames = sample_rate * buffer_size_ms / 1000;
+    match supported {
+        SupportedBufferSize::Range { min, max } if frames > 0 => {
+            BufferSize::Fixed(frames.clamp(*min, *max))
+        }
+        _ => BufferSize::Default,
+    }
+}
+
+/// 环形缓冲区溢出策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// 缓冲区满时丢弃新写入的样本
+    #[default]
+    DropNewest,
+    /// 缓冲区满时丢弃最旧的未读样本，为新数据腾出空间
+    DropOldest,
+}
+
+/// 读取位置中的消费者占用标记位
+const READ_LOCKED: usize = 1;
+
+/// 无锁单生产者/单消费者环形缓冲区
+///
+/// 生产者 (cpal 回调) 的写入是 wait-free 的，不会阻塞音频线程。
+/// 读写位置为单调递增的 `usize` 样本计数，读取位置最低位用作消费者占用标记：
+/// 消费者持有 [`ReadChunk`] 期间，`DropOldest` 策略不会淘汰正在读取的数据，
+/// 而是退化为丢弃新样本。多余的生产者/消费者会直接放弃操作而不是等待。
+/// 溢出时按整帧 (所有通道的一组样本) 丢弃和写入，交错的通道顺序不会错位。
+///
+/// 容量按整帧取整而不是取 2 的幂，存储下标用 `% capacity` 计算，
+/// 因此假定计数永不溢出：读取位置左移一位后仍有 63 位，
+/// 在 64 位平台上即使 192kHz 8 通道也需要十万年以上才会用尽
 pub struct RingBuffer {
-    buffer: Arc<Mutex<Vec<f32>>>,
+    buffer: Box<[UnsafeCell<f32>]>,
     capacity: usize,
-    write_pos: Arc<AtomicUsize>,
-    read_pos: Arc<AtomicUsize>,
+    policy: OverflowPolicy,
+    /// 每帧的样本数 (通道数)
+    frame_size: usize,
+    /// 写入位置 (仅生产者修改)
+    write_pos: AtomicUsize,
+    /// 读取位置 << 1 | 占用标记
+    read_state: AtomicUsize,
+    /// 生产者占用标记
+    writing: AtomicBool,
+    /// 因溢出丢弃的样本数
+    overruns: AtomicU64,
+}
+
+// SAFETY: 样本存储只通过读写位置划分出的不相交区域访问，
+// 区域的所有权由原子位置的 Release/Acquire 配对传递
+unsafe impl Send for RingBuffer {}
+unsafe impl Sync for RingBuffer {}
+
+impl std::fmt::Debug for RingBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity)
+            .field("policy", &self.policy)
+            .field("available", &self.available())
+            .field("overruns", &self.overrun_count())
+            .finish()
+    }
 }
 
 impl RingBuffer {
-    /// 创建新的环形缓冲区
+    /// 创建新的环形缓冲区 (溢出时丢弃新样本)
     pub fn new(capacity_samples: usize) -> Self {
+        Self::with_policy(capacity_samples, OverflowPolicy::default())
+    }
+
+    /// 创建指定溢出策略的环形缓冲区
+    pub fn with_policy(capacity_samples: usize, policy: OverflowPolicy) -> Self {
+        Self::with_frame_size(capacity_samples, policy, 1)
+    }
+
+    /// 创建按 `frame_size` 个样本为一帧写入与淘汰的环形缓冲区
+    ///
+    /// 容量向下取整为整帧；写入的数据应由整帧组成
+    pub fn with_frame_size(capacity_samples: usize, policy: OverflowPolicy, frame_size: usize) -> Self {
+        let frame_size = frame_size.max(1);
+        let capacity = (capacity_samples / frame_size).max(1) * frame_size;
         Self {
-            buffer: Arc::new(Mutex::new(vec![0.0; capacity_samples])),
-            capacity: capacity_samples,
-            write_pos: Arc::new(AtomicUsize::new(0)),
-            read_pos: Arc::new(AtomicUsize::new(0)),
+            buffer: (0..capacity).map(|_| UnsafeCell::new(0.0)).collect(),
+            capacity,
+            policy,
+            frame_size,
+            write_pos: AtomicUsize::new(0),
+            read_state: AtomicUsize::new(0),
+            writing: AtomicBool::new(false),
+            overruns: AtomicU64::new(0),
         }
     }
 
-    /// 写入数据
+    /// 写入数据 (生产者端，wait-free)
+    ///
+    /// 返回实际写入的样本数，未写入的样本计入溢出计数
     pub fn write(&self, data: &[f32]) -> usize {
-        let mut buffer = self.buffer.lock().unwrap();
-        let write_pos = self.write_pos.load(Ordering::SeqCst);
-        let read_pos = self.read_pos.load(Ordering::SeqCst);
+        if self.writing.swap(true, Ordering::Acquire) {
+            // 已有生产者在写入，放弃本次写入以避免阻塞
+            self.record_overrun(data.len());
+            return 0;
+        }
+        let written = self.write_exclusive(data);
+        self.writing.store(false, Ordering::Release);
+        written
+    }
 
-        let available = self.capacity.saturating_sub(
-            (write_pos as i64 - read_pos as i64 + self.capacity as i64) as usize % self.capacity
-        );
-        let to_write = data.len().min(available.saturating_sub(1));
+    fn write_exclusive(&self, data: &[f32]) -> usize {
+        let write_pos = self.write_pos.load(Ordering::Relaxed);
+        let mut state = self.read_state.load(Ordering::Acquire);
+        let mut free = self.capacity - write_pos.wrapping_sub(state >> 1);
+        let mut data = data;
+
+        if data.len() > free && self.policy == OverflowPolicy::DropOldest {
+            // 超过容量的部分只保留最新的整帧
+            if data.len() > self.capacity {
+                let skip = data.len() - self.capacity;
+                self.record_overrun(skip);
+                data = &data[skip..];
+            }
+
+            // 消费者未占用时，尝试一次性推进读取位置淘汰旧样本，
+            // 读取位置对齐到帧边界 (写入位置总是整帧)
+            let read_pos = state >> 1;
+            let evicted_pos = self.align_up(read_pos.wrapping_add(data.len() - free));
+            if state & READ_LOCKED == 0 {
+                match self.read_state.compare_exchange(
+                    state,
+                    evicted_pos << 1,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => {
+                        let evict = evicted_pos.wrapping_sub(read_pos);
+                        self.record_overrun(evict);
+                        free += evict;
+                    }
+                    Err(current) => {
+                        // 消费者刚刚读取或占用，按最新读取位置退化为丢弃新样本
+                        state = current;
+                        free = self.capacity - write_pos.wrapping_sub(state >> 1);
+                    }
+                }
+            }
+        }
 
-        for i in 0..to_write {
-            let idx = (write_pos + i) % self.capacity;
-            buffer[idx] = data[i];
+        // 只写入整帧，不足一帧的剩余空间留空
+        let to_write = data.len().min(free) / self.frame_size * self.frame_size;
+        self.record_overrun(data.len() - to_write);
+        if to_write == 0 {
+            return 0;
         }
 
-        if to_write > 0 {
-            self.write_pos.store((write_pos + to_write) % self.capacity, Ordering::SeqCst);
+        let start = write_pos % self.capacity;
+        let first = to_write.min(self.capacity - start);
+        // SAFETY: [write_pos, write_pos + to_write) 位于空闲区域，消费者不会访问
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.storage().add(start), first);
+            std::ptr::copy_nonoverlapping(data.as_ptr().add(first), self.storage(), to_write - first);
         }
+
+        self.write_pos.store(write_pos.wrapping_add(to_write), Ordering::Release);
         to_write
     }
 
-    /// 读取数据
-    pub fn read(&self, size: usize) -> Option<Vec<f32>> {
-        let mut buffer = self.buffer.lock().unwrap();
-        let write_pos = self.write_pos.load(Ordering::SeqCst);
-        let read_pos = self.read_pos.load(Ordering::SeqCst);
-
-        let available = (write_pos as i64 - read_pos as i64 + self.capacity as i64) as usize % self.capacity;
-        if available == 0 {
+    /// 零拷贝读取 (消费者端)
+    ///
+    /// 返回最多 `max_samples` 个样本的只读视图，视图释放时这些样本被消费。
+    /// 缓冲区为空或已有消费者占用时返回 None
+    pub fn read_chunk(&self, max_samples: usize) -> Option<ReadChunk<'_>> {
+        let state = self.read_state.fetch_or(READ_LOCKED, Ordering::Acquire);
+        if state & READ_LOCKED != 0 {
             return None;
         }
 
-        let to_read = size.min(available);
-        let mut result = Vec::with_capacity(to_read);
+        let read_pos = state >> 1;
+        let write_pos = self.write_pos.load(Ordering::Acquire);
+        let len = write_pos.wrapping_sub(read_pos).min(max_samples);
 
-        for i in 0..to_read {
-            let idx = (read_pos + i) % self.capacity;
-            result.push(buffer[idx]);
+        if len == 0 {
+            // 释放占用标记
+            self.read_state.store(state, Ordering::Release);
+            return None;
         }
+        Some(ReadChunk { buffer: self, read_pos, len })
+    }
+
+    /// 读取数据
+    pub fn read(&self, size: usize) -> Option<Vec<f32>> {
+        self.read_chunk(size).map(|chunk| chunk.to_vec())
+    }
 
-        self.read_pos.store((read_pos + to_read) % self.capacity, Ordering::SeqCst);
-        Some(result)
+    /// 写入位置 (自创建起累计写入的样本数)
+    pub fn write_position(&self) -> usize {
+        self.write_pos.load(Ordering::Acquire)
     }
 
     /// 获取可用数据量
     pub fn available(&self) -> usize {
-        let write_pos = self.write_pos.load(Ordering::SeqCst);
-        let read_pos = self.read_pos.load(Ordering::SeqCst);
-        (write_pos as i64 - read_pos as i64 + self.capacity as i64) as usize % self.capacity
+        let read_pos = self.read_state.load(Ordering::Acquire) >> 1;
+        let write_pos = self.write_pos.load(Ordering::Acquire);
+        write_pos.wrapping_sub(read_pos)
     }
 
-    /// 清空缓冲区
+    /// 清空缓冲区 (消费者端)
     pub fn clear(&self) {
-        let mut buffer = self.buffer.lock().unwrap();
-        self.write_pos.store(0, Ordering::SeqCst);
-        self.read_pos.store(0, Ordering::SeqCst);
-        buffer.fill(0.0);
+        drop(self.read_chunk(usize::MAX));
+    }
+
+    /// 获取容量
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 获取溢出策略
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// 获取累计因溢出丢弃的样本数
+    pub fn overrun_count(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    fn record_overrun(&self, samples: usize) {
+        if samples > 0 {
+            self.overruns.fetch_add(samples as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// 把样本位置向上对齐到帧边界
+    fn align_up(&self, position: usize) -> usize {
+        position.div_ceil(self.frame_size).wrapping_mul(self.frame_size)
+    }
+
+    fn storage(&self) -> *mut f32 {
+        // UnsafeCell<f32> 与 f32 内存布局相同
+        self.buffer.as_ptr() as *mut f32
+    }
+}
+
+/// 环形缓冲区的零拷贝读取视图
+///
+/// 持有期间生产者不会覆盖其中的样本，释放时自动消费
+pub struct ReadChunk<'a> {
+    buffer: &'a RingBuffer,
+    read_pos: usize,
+    len: usize,
+}
+
+impl ReadChunk<'_> {
+    /// 获取样本切片 (环绕时分为两段)
+    pub fn as_slices(&self) -> (&[f32], &[f32]) {
+        let capacity = self.buffer.capacity;
+        let start = self.read_pos % capacity;
+        let first = self.len.min(capacity - start);
+        // SAFETY: [read_pos, read_pos + len) 已由生产者发布，且在占用期间不会被覆盖
+        unsafe {
+            let storage = self.buffer.storage();
+            (
+                std::slice::from_raw_parts(storage.add(start), first),
+                std::slice::from_raw_parts(storage, self.len - first),
+            )
+        }
+    }
+
+    /// 复制为连续的样本数组
+    pub fn to_vec(&self) -> Vec<f32> {
+        let (first, second) = self.as_slices();
+        let mut result = Vec::with_capacity(self.len);
+        result.extend_from_slice(first);
+        result.extend_from_slice(second);
+        result
+    }
+
+    /// 首个样本的位置 (与 [`RingBuffer::write_position`] 同一计数)
+    pub fn position(&self) -> usize {
+        self.read_pos
+    }
+
+    /// 样本数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 只消费前 `count` 个样本，其余样本保留到下次读取
+    pub fn consume(mut self, count: usize) {
+        self.len = self.len.min(count);
+    }
+}
+
+impl Drop for ReadChunk<'_> {
+    fn drop(&mut self) {
+        let read_pos = self.read_pos.wrapping_add(self.len);
+        self.buffer.read_state.store(read_pos << 1, Ordering::Release);
+    }
+}
+
+/// 采集时钟
+///
+/// 音频回调记录本次数据的采集时刻及其在环形缓冲区中的写入位置 (锚点)，
+/// 消费者据此把读取位置换算为会话相对的单调时间戳。
+/// 锚点通过序列锁发布，回调端不会阻塞
+#[derive(Debug)]
+pub struct CaptureClock {
+    /// 会话时间零点
+    epoch: Instant,
+    /// 首次回调的采集时刻及其相对零点的偏移 (纳秒)
+    origin: OnceLock<(StreamInstant, u64)>,
+    /// 序列号，奇数表示锚点正在更新
+    seq: AtomicU64,
+    /// 锚点写入位置 (样本)
+    anchor_pos: AtomicU64,
+    /// 锚点采集时间 (纳秒)
+    anchor_ns: AtomicU64,
+}
+
+impl CaptureClock {
+    /// 创建以 `epoch` 为零点的时钟
+    pub fn new(epoch: Instant) -> Self {
+        Self {
+            epoch,
+            origin: OnceLock::new(),
+            seq: AtomicU64::new(0),
+            anchor_pos: AtomicU64::new(0),
+            anchor_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录一次回调 (生产者端，写入环形缓冲区之前调用)
+    ///
+    /// # Arguments
+    /// * `timestamp` - cpal 回调时间戳
+    /// * `write_pos` - 本次数据首个样本的写入位置
+    pub fn record(&self, timestamp: InputStreamTimestamp, write_pos: usize) {
+        // 首次回调时把设备时钟对齐到会话零点：
+        // 采集时刻 = 当前时间 - (回调时刻 - 采集时刻)
+        let (origin, origin_ns) = *self.origin.get_or_init(|| {
+            let delay = timestamp
+                .callback
+                .duration_since(&timestamp.capture)
+                .unwrap_or_default();
+            (timestamp.capture, self.epoch.elapsed().saturating_sub(delay).as_nanos() as u64)
+        });
+        let offset = timestamp.capture.duration_since(&origin).unwrap_or_default();
+        self.publish(write_pos, origin_ns + offset.as_nanos() as u64);
+    }
+
+    /// 记录一批不带设备时间戳的数据 (如从 `parec` 管道读取的监听源数据)
+    ///
+    /// 以收到数据的时刻减去这批数据的时长作为采集时刻
+    pub fn record_received(&self, frames: usize, sample_rate: u32, write_pos: usize) {
+        let received_ns = self.epoch.elapsed().as_nanos() as u64;
+        let duration_ns = frames as u64 * 1_000_000_000 / sample_rate.max(1) as u64;
+        self.publish(write_pos, received_ns.saturating_sub(duration_ns));
+    }
+
+    /// 通过序列锁发布锚点
+    fn publish(&self, write_pos: usize, capture_ns: u64) {
+        let seq = self.seq.load(Ordering::Relaxed);
+        self.seq.store(seq.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        self.anchor_pos.store(write_pos as u64, Ordering::Relaxed);
+        self.anchor_ns.store(capture_ns, Ordering::Relaxed);
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    /// 计算指定位置样本的采集时间 (纳秒)，尚无回调时返回 None
+    ///
+    /// # Arguments
+    /// * `position` - 样本位置 (与写入位置同一计数)
+    /// * `sample_rate` - 采样率
+    /// * `channels` - 通道数
+    pub fn timestamp_ns(&self, position: usize, sample_rate: u32, channels: u16) -> Option<u128> {
+        let (anchor_pos, anchor_ns) = loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq == 0 {
+                return None;
+            }
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let pos = self.anchor_pos.load(Ordering::Relaxed);
+            let ns = self.anchor_ns.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == seq {
+                break (pos as usize, ns);
+            }
+        };
+
+        // 位置差可能为负 (读取的数据早于最新锚点)
+        let delta_frames = position.wrapping_sub(anchor_pos) as isize as i128 / channels.max(1) as i128;
+        let delta_ns = delta_frames * 1_000_000_000 / sample_rate.max(1) as i128;
+        Some((anchor_ns as i128 + delta_ns).max(0) as u128)
     }
 }
 
@@ -165,9 +771,22 @@ pub struct AudioCapturer {
     host: Host,
     device: Option<Device>,
     stream: Option<Stream>,
+    /// 监听源的 `parec` 采集流
+    monitor_stream: Option<MonitorStream>,
     config: AudioConfig,
+    negotiated: Option<NegotiatedConfig>,
     is_running: Arc<AtomicBool>,
     ring_buffer: Arc<RingBuffer>,
+    /// 当前设备 ID
+    device_id: Option<String>,
+    /// 当前选择的监听源名称，非监听源为 None
+    monitor: Option<String>,
+    /// 流因设备移除或失效而中断
+    stream_lost: Arc<AtomicBool>,
+    /// 时间戳零点，设备切换后保持不变
+    epoch: Instant,
+    /// 当前流的采集时钟
+    clock: Arc<CaptureClock>,
 }
 
 impl Default for AudioCapturer {
@@ -179,112 +798,196 @@ impl Default for AudioCapturer {
 impl AudioCapturer {
     /// 创建新的音频采集器
     pub fn new() -> Self {
+        Self::with_epoch(Instant::now())
+    }
+
+    /// 创建以 `epoch` 为时间戳零点的音频采集器
+    ///
+    /// 多个采集器共用同一零点时，各自的时间戳可直接比较
+    pub fn with_epoch(epoch: Instant) -> Self {
         let host = cpal::default_host();
         Self {
             host,
             device: None,
             stream: None,
+            monitor_stream: None,
             config: AudioConfig::default(),
+            negotiated: None,
             is_running: Arc::new(AtomicBool::new(false)),
-            ring_buffer: Arc::new(RingBuffer::new(48000 * 2)), // 2秒缓冲
+            ring_buffer: Arc::new(RingBuffer::with_policy(48000 * 2, OverflowPolicy::DropOldest)), // 2秒缓冲
+            device_id: None,
+            monitor: None,
+            stream_lost: Arc::new(AtomicBool::new(false)),
+            epoch,
+            clock: Arc::new(CaptureClock::new(epoch)),
         }
     }
 
     /// 获取所有可用输入设备
     pub fn available_devices() -> Result<Vec<AudioDeviceInfo>, AudioError> {
+        let mut result = Self::probe_input_devices()?;
+        let ids: Vec<String> = result.iter().map(|info| info.id.clone()).collect();
+        result.extend(Self::monitor_devices(&ids));
+        Ok(result)
+    }
+
+    /// 查询普通输入设备 (不含监听源) 的能力
+    ///
+    /// 需要逐个打开设备查询支持的配置，开销较高，不适合周期性轮询
+    pub fn probe_input_devices() -> Result<Vec<AudioDeviceInfo>, AudioError> {
         let host = cpal::default_host();
-        let devices = host.input_devices()
-            .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
+        Ok(input_devices_with_ids(&host)?
+            .into_iter()
+            .filter_map(|entry| build_device_info(&host, &entry.device, entry.id).ok())
+            .collect())
+    }
 
-        let mut result = Vec::new();
-        for device in devices {
-            if let Ok(info) = build_device_info(&device) {
-                result.push(info);
-            }
-        }
+    /// 重新查询监听源并转换为设备信息
+    ///
+    /// 设备选择界面打开时调用，顺带刷新轮询使用的缓存
+    pub fn monitor_devices(device_ids: &[String]) -> Vec<AudioDeviceInfo> {
+        let host_name = cpal::default_host().id().name().to_string();
+        refresh_monitor_sources(device_ids).into_iter().map(|monitor| {
+            let mut info = AudioDeviceInfo::from_configs(
+                monitor.description.clone(),
+                monitor.device_id(),
+                host_name.clone(),
+                MONITOR_DEFAULT_SAMPLE_RATE,
+                &monitor_ranges(),
+            );
+            info.is_loopback = true;
+            info
+        }).collect()
+    }
+
+    /// 获取所有输入设备的摘要
+    ///
+    /// 不查询设备能力，开销低，适合周期性轮询。
+    /// 监听源取自缓存，仅在普通输入设备集合变化时重新查询
+    pub fn list_devices() -> Result<Vec<DeviceSummary>, AudioError> {
+        let host = cpal::default_host();
+        let mut result: Vec<DeviceSummary> = input_devices_with_ids(&host)?
+            .into_iter()
+            .map(|entry| DeviceSummary { id: entry.id, name: entry.name })
+            .collect();
+        let ids: Vec<String> = result.iter().map(|d| d.id.clone()).collect();
+        result.extend(cached_monitor_sources(&ids).iter().map(|monitor| monitor.summary()));
         Ok(result)
     }
 
     /// 获取默认输入设备
+    ///
+    /// ID 与设备列表中的条目一致，便于界面标记默认设备
     pub fn default_device() -> Result<AudioDeviceInfo, AudioError> {
         let host = cpal::default_host();
         let device = host.default_input_device()
             .ok_or(AudioError::NoDevice)?;
-        build_device_info(&device)
+        let id = default_device_id(&host, &device)?;
+        build_device_info(&host, &device, id)
     }
 
     /// 配置采集器
     pub fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError> {
         self.config = config.clone();
 
-        // 选择设备
-        self.device = if let Some(id) = &self.config.device_id {
-            let mut devices = self.host.input_devices()
-                .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
-            devices.find(|d| {
-                d.description()
-                    .map(|desc| desc.name() == id.as_str())
-                    .unwrap_or(false)
-            })
+        // 选择设备，监听源由 parec 子进程录制，不经过 cpal 设备
+        self.monitor = self.config.device_id
+            .as_deref()
+            .and_then(monitor_source_name)
+            .map(str::to_string);
+        // 记录与设备列表一致的 ID，设备移除检测和通道路由都以此为键
+        let ranges = if self.monitor.is_some() {
+            self.device = None;
+            self.device_id = self.config.device_id.clone();
+            monitor_ranges()
         } else {
-            self.host.default_input_device()
+            let selected = match &self.config.device_id {
+                Some(id) => find_input_device(&self.host, id)?,
+                None => match self.host.default_input_device() {
+                    Some(device) => {
+                        let id = default_device_id(&self.host, &device)?;
+                        Some((device, id))
+                    }
+                    None => None,
+                },
+            };
+            let (device, device_id) = selected.ok_or(AudioError::NoDevice)?;
+            self.device_id = Some(device_id);
+            supported_ranges(self.device.insert(device))?
         };
 
-        let device = self.device.as_ref()
-            .ok_or(AudioError::NoDevice)?;
+        // 在设备支持的配置中协商格式、采样率和通道数
+
+        let negotiated = negotiate_config(&ranges, &self.config)
+            .ok_or_else(|| AudioError::ConfigurationFailed("No supported input configuration".to_string()))?;
 
-        // 验证设备支持配置
-        let _supported_config = device.default_input_config()
-            .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
+        tracing::info!(
+            "Negotiated input config: {}Hz, {} channels, {:?} (requested {}Hz, {} channels)",
+            negotiated.sample_rate, negotiated.channels, negotiated.sample_format,
+            self.config.sample_rate, self.config.channels
+        );
 
-        // 简化：直接使用默认配置
-        // 在实际应用中可能需要更复杂的采样率协商
+        // 按实际配置分配 2 秒缓冲
+        let capacity = negotiated.sample_rate as usize * negotiated.channels as usize * 2;
+        self.ring_buffer = Arc::new(RingBuffer::with_frame_size(
+            capacity,
+            OverflowPolicy::DropOldest,
+            negotiated.channels as usize,
+        ));
+        self.negotiated = Some(negotiated);
 
         Ok(())
     }
 
     /// 启动音频采集
     pub fn start(&mut self) -> Result<(), AudioError> {
-        if self.stream.is_some() {
+        if self.stream.is_some() || self.monitor_stream.is_some() {
             return Ok(());
         }
 
-        let device = self.device.as_ref()
-            .ok_or(AudioError::NoDevice)?;
+        let negotiated = self.negotiated
+            .ok_or_else(|| AudioError::ConfigurationFailed("Capturer not configured".to_string()))?;
 
-        let config = StreamConfig {
-            channels: self.config.channels,
-            sample_rate: self.config.sample_rate,
-            buffer_size: cpal::BufferSize::Fixed(
-                (self.config.sample_rate * self.config.buffer_size_ms / 1000) as u32
-            ),
+        // 每个流的设备时钟单独对齐到会话零点
+        self.clock = Arc::new(CaptureClock::new(self.epoch));
+        let shared = StreamShared {
+            is_running: self.is_running.clone(),
+            ring_buffer: self.ring_buffer.clone(),
+            stream_lost: self.stream_lost.clone(),
+            clock: self.clock.clone(),
         };
+        self.stream_lost.store(false, Ordering::SeqCst);
 
-        let is_running = self.is_running.clone();
-        let ring_buffer = self.ring_buffer.clone();
-
-        let stream = device.build_input_stream(
-            &config,
-            move |data: &[f32], _| {
-                if is_running.load(Ordering::SeqCst) {
-                    // 将数据写入环形缓冲区
-                    ring_buffer.write(data);
-                }
-            },
-            move |err| {
-                tracing::error!("Audio stream error: {}", err);
-            },
-            None,
-        ).map_err(|e| AudioError::StreamCreationFailed(e.to_string()))?;
-
-        stream.play()
-            .map_err(|e| AudioError::StreamCreationFailed(e.to_string()))?;
+        match &self.monitor {
+            Some(source) => {
+                self.monitor_stream = Some(build_monitor_stream(source, &negotiated, shared)?);
+            }
+            None => {
+                let device = self.device.as_ref()
+                    .ok_or(AudioError::NoDevice)?;
+                let config = negotiated.stream_config();
+                let stream = match negotiated.sample_format {
+                    SampleFormat::F32 => build_input_stream::<f32>(device, &config, shared),
+                    SampleFormat::I16 => build_input_stream::<i16>(device, &config, shared),
+                    SampleFormat::U16 => build_input_stream::<u16>(device, &config, shared),
+                    SampleFormat::I32 => build_input_stream::<i32>(device, &config, shared),
+                    SampleFormat::I24 => build_input_stream::<cpal::I24>(device, &config, shared),
+                    SampleFormat::I8 => build_input_stream::<i8>(device, &config, shared),
+                    SampleFormat::U8 => build_input_stream::<u8>(device, &config, shared),
+                    SampleFormat::U32 => build_input_stream::<u32>(device, &config, shared),
+                    SampleFormat::F64 => build_input_stream::<f64>(device, &config, shared),
+                    format => Err(AudioError::StreamCreationFailed(format!("Unsupported sample format: {}", format))),
+                }?;
+                stream.play()
+                    .map_err(|e| AudioError::StreamCreationFailed(e.to_string()))?;
+                self.stream = Some(stream);
+            }
+        }
 
         self.is_running.store(true, Ordering::SeqCst);
-        self.stream = Some(stream);
 
-        tracing::info!("Audio capture started: {}Hz, {} channels",
-            self.config.sample_rate, self.config.channels);
+        tracing::info!("Audio capture started: {}Hz, {} channels, {:?}",
+            negotiated.sample_rate, negotiated.channels, negotiated.sample_format);
 
         Ok(())
     }
@@ -293,8 +996,11 @@ impl AudioCapturer {
     pub fn stop(&mut self) -> Result<(), AudioError> {
         self.is_running.store(false, Ordering::SeqCst);
 
-        if let Some(stream) = self.stream.take() {
+        let stream = self.stream.take();
+        let monitor_stream = self.monitor_stream.take();
+        if stream.is_some() || monitor_stream.is_some() {
             drop(stream);
+            drop(monitor_stream);
             tracing::info!("Audio capture stopped");
         }
 
@@ -306,16 +1012,38 @@ impl AudioCapturer {
         self.is_running.load(Ordering::SeqCst)
     }
 
+    /// 当前采集配置
+    pub fn config(&self) -> &AudioConfig {
+        &self.config
+    }
+
+    /// 当前设备 ID
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    /// 流是否因设备移除或失效而中断，需要重建
+    pub fn is_stream_lost(&self) -> bool {
+        self.stream_lost.load(Ordering::SeqCst)
+    }
+
+    /// 获取协商后的实际采集配置
+    pub fn negotiated_config(&self) -> Option<NegotiatedConfig> {
+        self.negotiated
+    }
+
     /// 读取音频帧
+    ///
+    /// 时间戳为首个样本的采集时间，由回调记录的采集时刻与样本位置推算
     pub fn read_frame(&self, max_samples: usize) -> Option<AudioFrame> {
-        let samples = self.ring_buffer.read(max_samples)?;
-        let now = std::time::Instant::now();
-        Some(AudioFrame::new(
-            samples,
-            self.config.sample_rate,
-            self.config.channels,
-            now.elapsed().as_nanos(),
-        ))
+        let chunk = self.ring_buffer.read_chunk(max_samples)?;
+        let (sample_rate, channels) = self.negotiated
+            .map(|n| (n.sample_rate, n.channels))
+            .unwrap_or((self.config.sample_rate, self.config.channels));
+        let timestamp_ns = self.clock
+            .timestamp_ns(chunk.position(), sample_rate, channels)
+            .unwrap_or_else(|| self.epoch.elapsed().as_nanos());
+        Some(AudioFrame::new(chunk.to_vec(), sample_rate, channels, timestamp_ns))
     }
 
     /// 获取环形缓冲区引用（用于外部访问）
@@ -324,24 +1052,260 @@ impl AudioCapturer {
     }
 }
 
+/// 输入流回调共享的状态
+struct StreamShared {
+    is_running: Arc<AtomicBool>,
+    ring_buffer: Arc<RingBuffer>,
+    stream_lost: Arc<AtomicBool>,
+    clock: Arc<CaptureClock>,
+}
+
+/// 构建输入流，将设备原生格式转换为 f32 写入环形缓冲区
+fn build_input_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    shared: StreamShared,
+) -> Result<Stream, AudioError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    // 预分配转换缓冲，避免在实时回调中频繁分配
+    let mut converted: Vec<f32> = Vec::with_capacity(8192);
+    let StreamShared { is_running, ring_buffer, stream_lost, clock } = shared;
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], info: &cpal::InputCallbackInfo| {
+            if is_running.load(Ordering::SeqCst) {
+                converted.clear();
+                converted.extend(data.iter().map(|&sample| f32::from_sample(sample)));
+                // 先发布时间锚点，消费者读到这些样本时必然能看到对应锚点
+                clock.record(info.timestamp(), ring_buffer.write_position());
+                // 将数据写入环形缓冲区
+                ring_buffer.write(&converted);
+            }
+        },
+        move |err| match err {
+            StreamError::DeviceNotAvailable | StreamError::StreamInvalidated => {
+                tracing::warn!("Audio stream lost: {}", err);
+                stream_lost.store(true, Ordering::SeqCst);
+            }
+            _ => tracing::error!("Audio stream error: {}", err),
+        },
+        None,
+    ).map_err(|e| AudioError::StreamCreationFailed(e.to_string()))
+}
+
+/// 查询设备支持的输入配置，查询失败时退化为默认配置
+fn supported_ranges(device: &Device) -> Result<Vec<SupportedStreamConfigRange>, AudioError> {
+    match device.supported_input_configs() {
+        Ok(configs) => Ok(configs.collect()),
+        Err(e) => {
+            tracing::warn!("Failed to query supported configs, using default: {}", e);
+            let default = device.default_input_config()
+                .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
+            Ok(vec![SupportedStreamConfigRange::new(
+                default.channels(),
+                default.sample_rate(),
+                default.sample_rate(),
+                *default.buffer_size(),
+                default.sample_format(),
+            )])
+        }
+    }
+}
+
+/// 启动监听源采集，`parec` 输出已是 f32，直接写入环形缓冲区
+fn build_monitor_stream(
+    source: &str,
+    negotiated: &NegotiatedConfig,
+    shared: StreamShared,
+) -> Result<MonitorStream, AudioError> {
+    let StreamShared { is_running, ring_buffer, stream_lost, clock } = shared;
+    let sample_rate = negotiated.sample_rate;
+    let channels = negotiated.channels.max(1) as usize;
+
+    MonitorStream::spawn(
+        source,
+        negotiated.sample_rate,
+        negotiated.channels,
+        move |data| {
+            if is_running.load(Ordering::SeqCst) {
+                clock.record_received(data.len() / channels, sample_rate, ring_buffer.write_position());
+                ring_buffer.write(data);
+            }
+        },
+        move || stream_lost.store(true, Ordering::SeqCst),
+    )
+}
+
+/// 监听源支持的输入配置
+///
+/// PulseAudio 按请求的格式重采样和混音，因此声明常见范围内的任意采样率和单/双声道
+fn monitor_ranges() -> Vec<SupportedStreamConfigRange> {
+    [1, 2]
+        .into_iter()
+        .map(|channels| SupportedStreamConfigRange::new(
+            channels,
+            MONITOR_MIN_SAMPLE_RATE,
+            MONITOR_MAX_SAMPLE_RATE,
+            SupportedBufferSize::Unknown,
+            SampleFormat::F32,
+        ))
+        .collect()
+}
+
 /// 构建设备信息
-fn build_device_info(device: &Device) -> Result<AudioDeviceInfo, AudioError> {
+fn build_device_info(host: &Host, device: &Device, id: String) -> Result<AudioDeviceInfo, AudioError> {
     let description = device.description()
         .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
     let device_name = description.name().to_string();
+    let host_name = host.id().name().to_string();
 
     let default_config = device.default_input_config()
         .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
 
-    let channels = default_config.channels();
-    let sample_rate: u32 = default_config.sample_rate();
+    let mut ranges: Vec<SupportedStreamConfigRange> = device.supported_input_configs()
+        .map(|configs| configs.collect())
+        .unwrap_or_default();
+    if ranges.is_empty() {
+        ranges.push(SupportedStreamConfigRange::new(
+            default_config.channels(),
+            default_config.sample_rate(),
+            default_config.sample_rate(),
+            *default_config.buffer_size(),
+            default_config.sample_format(),
+        ));
+    }
+
+    Ok(AudioDeviceInfo::from_configs(
+        device_name,
+        id,
+        host_name,
+        default_config.sample_rate(),
+        &ranges,
+    ))
+}
+
+/// 设备 ID 的组成部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeviceKey {
+    /// 宿主稳定 ID，宿主不提供时退化为 `host:name`
+    base: String,
+    /// 区分同名回退 ID 的硬件属性 (设备地址或驱动标识)
+    hardware: Option<String>,
+}
+
+impl DeviceKey {
+    fn of(host_name: &str, device: &Device, description: &DeviceDescription) -> Self {
+        match device.id() {
+            // 宿主 ID 本身唯一 (如 ALSA 的 `hw:CARD=...,DEV=...`)，无需再区分
+            Ok(id) => Self { base: id.to_string(), hardware: None },
+            Err(_) => Self {
+                base: format!("{}:{}", host_name, description.name()),
+                hardware: description.address()
+                    .or_else(|| description.driver())
+                    .map(str::to_string),
+            },
+        }
+    }
+}
+
+/// 带 ID 的输入设备
+struct DeviceEntry {
+    device: Device,
+    name: String,
+    key: DeviceKey,
+    id: String,
+}
+
+/// 枚举输入设备并分配 ID
+fn input_devices_with_ids(host: &Host) -> Result<Vec<DeviceEntry>, AudioError> {
+    let host_name = host.id().name();
+    let devices = host.input_devices()
+        .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
+
+    let described: Vec<(Device, DeviceDescription)> = devices
+        .filter_map(|device| {
+            let description = device.description().ok()?;
+            Some((device, description))
+        })
+        .collect();
+    let keys: Vec<DeviceKey> = described.iter()
+        .map(|(device, description)| DeviceKey::of(host_name, device, description))
+        .collect();
+    let ids = assign_device_ids(&keys);
+
+    Ok(described.into_iter()
+        .zip(keys)
+        .zip(ids)
+        .map(|(((device, description), key), id)| DeviceEntry {
+            device,
+            name: description.name().to_string(),
+            key,
+            id,
+        })
+        .collect())
+}
+
+/// 为设备分配唯一 ID
+///
+/// 不重名的设备直接使用基础 ID；重名时追加硬件属性 (`base@address`)，
+/// 该属性由硬件位置决定，不随枚举顺序变化。缺少硬件属性的重名设备只能按
+/// 枚举顺序追加序号 (`#2`、`#3`)，这类 ID 在插拔或重启后可能在同型号设备间对调
+fn assign_device_ids(keys: &[DeviceKey]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::with_capacity(keys.len());
+    for key in keys {
+        let duplicated = keys.iter().filter(|other| other.base == key.base).count() > 1;
+        let candidate = match (&key.hardware, duplicated) {
+            (Some(hardware), true) => format!("{}@{}", key.base, hardware),
+            _ => key.base.clone(),
+        };
+        ids.push(unique_device_id(&candidate, ids.iter().map(String::as_str)));
+    }
+    ids
+}
 
-    Ok(AudioDeviceInfo {
-        name: device_name.clone(),
-        id: device_name,
-        sample_rates: vec![sample_rate],
-        channels,
-    })
+/// 生成在已有 ID 中唯一的设备 ID
+fn unique_device_id<'a>(id: &str, existing: impl Iterator<Item = &'a str> + Clone) -> String {
+    let mut candidate = id.to_string();
+    let mut n = 2;
+    while existing.clone().any(|e| e == candidate) {
+        candidate = format!("{}#{}", id, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// 默认输入设备在设备列表中的 ID
+///
+/// 按设备 ID 组成部分匹配列表条目；同型号设备缺少硬件属性时无法区分，
+/// 此时取第一个匹配的条目
+fn default_device_id(host: &Host, device: &Device) -> Result<String, AudioError> {
+    let description = device.description()
+        .map_err(|e| AudioError::ConfigurationFailed(e.to_string()))?;
+    let key = DeviceKey::of(host.id().name(), device, &description);
+    Ok(input_devices_with_ids(host)?
+        .into_iter()
+        .find(|entry| entry.key == key)
+        .map(|entry| entry.id)
+        .unwrap_or(key.base))
+}
+
+/// 按 ID 查找输入设备，同时返回该设备在设备列表中的 ID
+///
+/// 依次匹配宿主稳定 ID、设备列表中分配的 ID 以及旧版配置保存的设备名称
+fn find_input_device(host: &Host, id: &str) -> Result<Option<(Device, String)>, AudioError> {
+    // 宿主稳定 ID 本身唯一，即为列表中的 ID
+    if let Some(device) = id.parse::<DeviceId>().ok().and_then(|id| host.device_by_id(&id)) {
+        return Ok(Some((device, id.to_string())));
+    }
+
+    Ok(input_devices_with_ids(host)?
+        .into_iter()
+        .find(|entry| entry.id == id || entry.name == id)
+        .map(|entry| (entry.device, entry.id)))
 }
 
 impl Drop for AudioCapturer {
@@ -399,6 +1363,33 @@ mod tests {
         assert!((mono.samples[1] - (-0.375)).abs() < 0.001);
     }
 
+    #[test]
+    fn test_audio_frame_mix_channels() {
+        // 4 通道，每帧 [0.8, 0.0, 0.4, 0.2]
+        let frame = AudioFrame::new([0.8, 0.0, 0.4, 0.2].repeat(3), 16000, 4, 1000);
+
+        let first = frame.channel(0);
+        assert_eq!(first.channels, 1);
+        assert_eq!(first.samples, vec![0.8; 3]);
+        assert_eq!(first.timestamp_ns, 1000);
+
+        let subset = frame.mix_channels(&[2, 0, 2]);
+        assert!(subset.samples.iter().all(|s| (s - 0.6).abs() < 1e-6));
+
+        // 无效索引被忽略，全部无效时平均所有通道
+        assert_eq!(frame.mix_channels(&[3, 9]).samples, vec![0.2; 3]);
+        assert_eq!(frame.channel(7).samples, frame.to_mono().samples);
+    }
+
+    #[test]
+    fn test_audio_frame_channel_energy() {
+        let frame = AudioFrame::new(vec![0.5, 0.0, -0.5, 0.1], 16000, 2, 0);
+        let energy = frame.channel_energy();
+        assert_eq!(energy.len(), 2);
+        assert!((energy[0] - 0.25).abs() < 1e-6);
+        assert!((energy[1] - 0.005).abs() < 1e-6);
+    }
+
     #[test]
     fn test_audio_config_default() {
         let config = AudioConfig::default();
@@ -422,11 +1413,93 @@ mod tests {
         assert_eq!(config.buffer_size_ms, 40);
     }
 
+    fn range(channels: u16, min: u32, max: u32, format: SampleFormat) -> SupportedStreamConfigRange {
+        SupportedStreamConfigRange::new(
+            channels,
+            min,
+            max,
+            SupportedBufferSize::Range { min: 64, max: 4096 },
+            format,
+        )
+    }
+
+    #[test]
+    fn test_negotiate_exact_match() {
+        let ranges = vec![
+            range(2, 44100, 44100, SampleFormat::I16),
+            range(1, 8000, 96000, SampleFormat::F32),
+        ];
+        let config = negotiate_config(&ranges, &AudioConfig::default()).unwrap();
+        assert_eq!(config.sample_rate, 48000);
+        assert_eq!(config.channels, 1);
+        assert_eq!(config.sample_format, SampleFormat::F32);
+        // 20ms @ 48kHz
+        assert_eq!(config.buffer_size, BufferSize::Fixed(960));
+    }
+
+    #[test]
+    fn test_negotiate_i16_only_device() {
+        let ranges = vec![range(1, 48000, 48000, SampleFormat::I16)];
+        let config = negotiate_config(&ranges, &AudioConfig::default()).unwrap();
+        assert_eq!(config.sample_format, SampleFormat::I16);
+        assert_eq!(config.sample_rate, 48000);
+    }
+
+    #[test]
+    fn test_negotiate_44k_only_device() {
+        let ranges = vec![
+            range(2, 44100, 44100, SampleFormat::F32),
+            range(2, 22050, 22050, SampleFormat::F32),
+        ];
+        let config = negotiate_config(&ranges, &AudioConfig::default()).unwrap();
+        assert_eq!(config.sample_rate, 44100);
+        assert_eq!(config.channels, 2);
+    }
+
+    #[test]
+    fn test_negotiate_prefers_matching_channels_then_format() {
+        let ranges = vec![
+            range(4, 48000, 48000, SampleFormat::F32),
+            range(1, 48000, 48000, SampleFormat::U16),
+            range(1, 48000, 48000, SampleFormat::I16),
+        ];
+        let config = negotiate_config(&ranges, &AudioConfig::default()).unwrap();
+        assert_eq!(config.channels, 1);
+        assert_eq!(config.sample_format, SampleFormat::I16);
+    }
+
+    #[test]
+    fn test_negotiate_buffer_size_clamped() {
+        let ranges = vec![SupportedStreamConfigRange::new(
+            1, 48000, 48000, SupportedBufferSize::Range { min: 1024, max: 2048 }, SampleFormat::F32,
+        )];
+        let config = negotiate_config(&ranges, &AudioConfig::default()).unwrap();
+        assert_eq!(config.buffer_size, BufferSize::Fixed(1024));
+
+        let ranges = vec![SupportedStreamConfigRange::new(
+            1, 48000, 48000, SupportedBufferSize::Unknown, SampleFormat::F32,
+        )];
+        let config = negotiate_config(&ranges, &AudioConfig::default()).unwrap();
+        assert_eq!(config.buffer_size, BufferSize::Default);
+    }
+
+    #[test]
+    fn test_negotiate_no_configs() {
+        assert!(negotiate_config(&[], &AudioConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_sample_conversion_to_f32() {
+        assert_eq!(f32::from_sample(i16::MIN), -1.0);
+        assert_eq!(f32::from_sample(0i16), 0.0);
+        assert_eq!(f32::from_sample(u16::MAX / 2 + 1), 0.0);
+        assert!((f32::from_sample(i32::MAX) - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_ring_buffer_new() {
         let buffer = RingBuffer::new(1024);
-        // capacity is a public field
-        assert_eq!(buffer.capacity, 1024);
+        assert_eq!(buffer.capacity(), 1024);
     }
 
     #[test]
@@ -476,12 +1549,248 @@ mod tests {
         let large_data = vec![1.0; 20];
 
         let written = buffer.write(&large_data);
-        // The ring buffer reserves 1 slot, so only 9 can fit
-        assert_eq!(written, 9);
+        // DropNewest keeps the first 10 samples and counts the rest as overruns
+        assert_eq!(written, 10);
+        assert_eq!(buffer.overrun_count(), 10);
+
+        let read = buffer.read(20).unwrap();
+        assert_eq!(read, vec![1.0; 10]);
+    }
 
-        let read = buffer.read(9).unwrap();
-        assert_eq!(read.len(), 9);
-        assert_eq!(read, vec![1.0; 9]);
+    #[test]
+    fn test_ring_buffer_drop_oldest() {
+        let buffer = RingBuffer::with_policy(10, OverflowPolicy::DropOldest);
+        let data: Vec<f32> = (1..=8).map(|x| x as f32).collect();
+        buffer.write(&data);
+
+        // Only 2 slots free, so the 2 oldest samples are evicted
+        let written = buffer.write(&[9.0, 10.0, 11.0, 12.0]);
+        assert_eq!(written, 4);
+        assert_eq!(buffer.overrun_count(), 2);
+
+        let read = buffer.read(10).unwrap();
+        let expected: Vec<f32> = (3..=12).map(|x| x as f32).collect();
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn test_ring_buffer_drop_oldest_larger_than_capacity() {
+        let buffer = RingBuffer::with_policy(4, OverflowPolicy::DropOldest);
+        buffer.write(&[1.0, 2.0]);
+        let data: Vec<f32> = (3..=9).map(|x| x as f32).collect();
+        assert_eq!(buffer.write(&data), 4);
+        // 3 samples of the write itself plus the 2 buffered ones are dropped
+        assert_eq!(buffer.overrun_count(), 5);
+        assert_eq!(buffer.read(4).unwrap(), vec![6.0, 7.0, 8.0, 9.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_drop_oldest_keeps_channels_aligned() {
+        // 立体声：左声道为正数，右声道为负数
+        let stereo = |frames: std::ops::Range<i32>| -> Vec<f32> {
+            frames.flat_map(|f| [f as f32, -(f as f32)]).collect()
+        };
+        let buffer = RingBuffer::with_frame_size(9, OverflowPolicy::DropOldest, 2);
+        assert_eq!(buffer.capacity(), 8);
+        buffer.write(&stereo(1..4));
+
+        // 消费者只读了半帧，淘汰时仍对齐到帧边界
+        buffer.read_chunk(1).unwrap().consume(1);
+        assert_eq!(buffer.write(&stereo(4..7)), 6);
+        assert_eq!(buffer.overrun_count(), 3);
+        assert_eq!(buffer.read(8).unwrap(), stereo(3..7));
+
+        // 超过容量的写入保留最新的整帧
+        assert_eq!(buffer.write(&stereo(7..13)), 8);
+        assert_eq!(buffer.read(8).unwrap(), stereo(9..13));
+    }
+
+    #[test]
+    fn test_ring_buffer_drop_newest_writes_whole_frames() {
+        let buffer = RingBuffer::with_frame_size(6, OverflowPolicy::DropNewest, 2);
+        buffer.write(&[1.0, -1.0]);
+        buffer.read_chunk(1).unwrap().consume(1);
+        // 剩余 5 个空位只写入 2 帧
+        assert_eq!(buffer.write(&[2.0, -2.0, 3.0, -3.0, 4.0, -4.0]), 4);
+        assert_eq!(buffer.overrun_count(), 2);
+        assert_eq!(buffer.read(6).unwrap(), vec![-1.0, 2.0, -2.0, 3.0, -3.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_drop_oldest_respects_reader() {
+        let buffer = RingBuffer::with_policy(4, OverflowPolicy::DropOldest);
+        buffer.write(&[1.0, 2.0, 3.0, 4.0]);
+
+        let chunk = buffer.read_chunk(2).unwrap();
+        // The reader holds the oldest samples, so new data is dropped instead
+        assert_eq!(buffer.write(&[5.0]), 0);
+        assert_eq!(chunk.to_vec(), vec![1.0, 2.0]);
+        drop(chunk);
+
+        assert_eq!(buffer.write(&[5.0, 6.0]), 2);
+        assert_eq!(buffer.read(4).unwrap(), vec![3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(buffer.overrun_count(), 1);
+    }
+
+    #[test]
+    fn test_ring_buffer_read_chunk_wrap_around() {
+        let buffer = RingBuffer::new(6);
+        buffer.write(&[1.0, 2.0, 3.0, 4.0]);
+        buffer.read(3).unwrap();
+        buffer.write(&[5.0, 6.0, 7.0, 8.0]);
+
+        let chunk = buffer.read_chunk(10).unwrap();
+        let (first, second) = chunk.as_slices();
+        assert_eq!(first, &[4.0, 5.0, 6.0]);
+        assert_eq!(second, &[7.0, 8.0]);
+        assert_eq!(chunk.len(), 5);
+    }
+
+    #[test]
+    fn test_ring_buffer_read_chunk_partial_consume() {
+        let buffer = RingBuffer::new(8);
+        buffer.write(&[1.0, 2.0, 3.0]);
+
+        buffer.read_chunk(3).unwrap().consume(1);
+        assert_eq!(buffer.available(), 2);
+        assert_eq!(buffer.read(3).unwrap(), vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_positions() {
+        let buffer = RingBuffer::new(4);
+        buffer.write(&[1.0, 2.0, 3.0]);
+        buffer.read_chunk(2).unwrap().consume(2);
+        buffer.write(&[4.0, 5.0, 6.0]);
+        assert_eq!(buffer.write_position(), 6);
+        // 位置为累计计数，不受环绕影响
+        assert_eq!(buffer.read_chunk(8).unwrap().position(), 2);
+    }
+
+    fn callback_timestamp(capture_ms: u64, delay_ms: u64) -> InputStreamTimestamp {
+        let instant = |ms: u64| StreamInstant::new((ms / 1000) as i64, (ms % 1000) as u32 * 1_000_000);
+        InputStreamTimestamp { callback: instant(capture_ms + delay_ms), capture: instant(capture_ms) }
+    }
+
+    #[test]
+    fn test_capture_clock_timestamps() {
+        let epoch = Instant::now();
+        let clock = CaptureClock::new(epoch);
+        assert_eq!(clock.timestamp_ns(0, 16000, 1), None);
+
+        // 第一次回调：设备时间 5s，采集后 10ms 才回调
+        clock.record(callback_timestamp(5000, 10), 0);
+        let origin = clock.timestamp_ns(0, 16000, 2).unwrap();
+        assert!(origin <= epoch.elapsed().as_nanos());
+
+        // 100ms 后的回调写入位置 3200 (16kHz 双通道 = 1600 帧 = 100ms)
+        clock.record(callback_timestamp(5100, 10), 3200);
+        assert_eq!(clock.timestamp_ns(3200, 16000, 2), Some(origin + 100_000_000));
+        // 早于最新锚点的位置按采样率回推
+        assert_eq!(clock.timestamp_ns(1600, 16000, 2), Some(origin + 50_000_000));
+        assert_eq!(clock.timestamp_ns(4800, 16000, 2), Some(origin + 150_000_000));
+    }
+
+    #[test]
+    fn test_capture_clock_tracks_callback_jitter() {
+        let clock = CaptureClock::new(Instant::now());
+        clock.record(callback_timestamp(1000, 0), 0);
+        let origin = clock.timestamp_ns(0, 48000, 1).unwrap();
+
+        // 设备实际采集时刻比样本数推算的晚 2ms (如发生过丢帧)，以锚点为准
+        clock.record(callback_timestamp(1012, 0), 480);
+        assert_eq!(clock.timestamp_ns(480, 48000, 1), Some(origin + 12_000_000));
+        assert_eq!(clock.timestamp_ns(960, 48000, 1), Some(origin + 22_000_000));
+    }
+
+    #[test]
+    fn test_audio_frame_duration() {
+        let frame = AudioFrame::new(vec![0.0; 960], 48000, 2, 1_000);
+        assert_eq!(frame.frames(), 480);
+        assert_eq!(frame.duration_ns(), 10_000_000);
+        assert_eq!(frame.end_timestamp_ns(), 10_001_000);
+    }
+
+    #[test]
+    fn test_ring_buffer_single_reader() {
+        let buffer = RingBuffer::new(8);
+        buffer.write(&[1.0, 2.0]);
+
+        let chunk = buffer.read_chunk(1).unwrap();
+        // A second consumer gives up instead of blocking
+        assert!(buffer.read_chunk(1).is_none());
+        drop(chunk);
+        assert_eq!(buffer.read(1).unwrap(), vec![2.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_spsc_stress() {
+        use std::thread;
+
+        const TOTAL: usize = 100_000;
+        let buffer = Arc::new(RingBuffer::new(257));
+        let producer = buffer.clone();
+
+        let handle = thread::spawn(move || {
+            let data: Vec<f32> = (0..TOTAL).map(|x| x as f32).collect();
+            let mut offset = 0;
+            let mut step = 1;
+            while offset < TOTAL {
+                let end = (offset + step).min(TOTAL);
+                offset += producer.write(&data[offset..end]);
+                step = step % 97 + 1;
+            }
+        });
+
+        // Every sample must arrive exactly once and in order
+        let mut expected = 0;
+        while expected < TOTAL {
+            if let Some(chunk) = buffer.read_chunk(expected % 61 + 1) {
+                let (first, second) = chunk.as_slices();
+                for &value in first.iter().chain(second) {
+                    assert_eq!(value, expected as f32);
+                    expected += 1;
+                }
+            }
+        }
+        handle.join().unwrap();
+        assert_eq!(buffer.available(), 0);
+    }
+
+    #[test]
+    fn test_ring_buffer_spsc_stress_drop_oldest() {
+        use std::thread;
+
+        const TOTAL: usize = 100_000;
+        let buffer = Arc::new(RingBuffer::with_policy(128, OverflowPolicy::DropOldest));
+        let producer = buffer.clone();
+
+        let handle = thread::spawn(move || {
+            let data: Vec<f32> = (0..TOTAL).map(|x| x as f32).collect();
+            for chunk in data.chunks(37) {
+                producer.write(chunk);
+            }
+        });
+
+        // Samples may be dropped but never duplicated or reordered
+        let mut received = 0u64;
+        let mut last = -1.0f32;
+        loop {
+            let finished = handle.is_finished();
+            while let Some(chunk) = buffer.read_chunk(50) {
+                for value in chunk.to_vec() {
+                    assert!(value > last, "{} after {}", value, last);
+                    last = value;
+                    received += 1;
+                }
+            }
+            if finished {
+                break;
+            }
+        }
+        handle.join().unwrap();
+
+        assert_eq!(received + buffer.overrun_count(), TOTAL as u64);
     }
 
     #[test]
@@ -518,8 +1827,15 @@ mod tests {
         let info = AudioDeviceInfo {
             name: "Test Device".to_string(),
             id: "test-id".to_string(),
+            host: "ALSA".to_string(),
+            default_sample_rate: 48000,
             sample_rates: vec![44100, 48000],
+            sample_rate_ranges: vec![SampleRateRange { min: 44100, max: 48000 }],
+            sample_formats: vec![SampleFormat::F32],
+            channel_counts: vec![1, 2],
             channels: 2,
+            buffer_size_ranges: Vec::new(),
+            is_loopback: false,
         };
         assert_eq!(info.name, "Test Device");
         assert_eq!(info.id, "test-id");
@@ -527,6 +1843,93 @@ mod tests {
         assert_eq!(info.channels, 2);
     }
 
+    #[test]
+    fn test_device_info_from_configs() {
+        let ranges = vec![
+            SupportedStreamConfigRange::new(
+                2, 8000, 48000,
+                SupportedBufferSize::Range { min: 64, max: 4096 },
+                SampleFormat::I16,
+            ),
+            SupportedStreamConfigRange::new(
+                1, 8000, 48000,
+                SupportedBufferSize::Range { min: 64, max: 4096 },
+                SampleFormat::I16,
+            ),
+            SupportedStreamConfigRange::new(
+                2, 96000, 96000,
+                SupportedBufferSize::Unknown,
+                SampleFormat::F32,
+            ),
+        ];
+        let info = AudioDeviceInfo::from_configs(
+            "USB Mic".to_string(),
+            "alsa:hw:1,0".to_string(),
+            "ALSA".to_string(),
+            44100,
+            &ranges,
+        );
+
+        assert_eq!(info.sample_rate_ranges, vec![
+            SampleRateRange { min: 8000, max: 48000 },
+            SampleRateRange { min: 96000, max: 96000 },
+        ]);
+        assert_eq!(info.sample_rates, vec![8000, 11025, 16000, 22050, 32000, 44100, 48000, 96000]);
+        assert_eq!(info.sample_formats, vec![SampleFormat::I16, SampleFormat::F32]);
+        assert_eq!(info.channel_counts, vec![1, 2]);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.buffer_size_ranges, vec![BufferSizeRange { min: 64, max: 4096 }]);
+    }
+
+    #[test]
+    fn test_device_info_keeps_nonstandard_default_rate() {
+        let ranges = vec![SupportedStreamConfigRange::new(
+            1, 12000, 12000, SupportedBufferSize::Unknown, SampleFormat::F32,
+        )];
+        let info = AudioDeviceInfo::from_configs(
+            "Odd".to_string(), "id".to_string(), "ALSA".to_string(), 12000, &ranges,
+        );
+        assert_eq!(info.sample_rates, vec![12000]);
+        assert_eq!(info.channels, 1);
+        assert!(info.buffer_size_ranges.is_empty());
+    }
+
+    #[test]
+    fn test_unique_device_id() {
+        let existing = ["alsa:Mic", "alsa:Mic#2"];
+        assert_eq!(unique_device_id("alsa:Other", existing.iter().copied()), "alsa:Other");
+        assert_eq!(unique_device_id("alsa:Mic", existing.iter().copied()), "alsa:Mic#3");
+    }
+
+    #[test]
+    fn test_assign_device_ids_prefers_hardware_attribute() {
+        let key = |base: &str, hardware: Option<&str>| DeviceKey {
+            base: base.to_string(),
+            hardware: hardware.map(str::to_string),
+        };
+        let keys = [
+            key("wasapi:USB Mic", Some("usb-1.2")),
+            key("wasapi:Headset", None),
+            key("wasapi:USB Mic", Some("usb-1.1")),
+        ];
+        let ids = assign_device_ids(&keys);
+        assert_eq!(ids, ["wasapi:USB Mic@usb-1.2", "wasapi:Headset", "wasapi:USB Mic@usb-1.1"]);
+
+        // 交换枚举顺序后 ID 保持不变
+        let swapped = [keys[2].clone(), keys[1].clone(), keys[0].clone()];
+        let swapped_ids = assign_device_ids(&swapped);
+        assert_eq!(swapped_ids, ["wasapi:USB Mic@usb-1.1", "wasapi:Headset", "wasapi:USB Mic@usb-1.2"]);
+    }
+
+    #[test]
+    fn test_assign_device_ids_falls_back_to_ordinal() {
+        let keys = [
+            DeviceKey { base: "wasapi:Mic".to_string(), hardware: None },
+            DeviceKey { base: "wasapi:Mic".to_string(), hardware: None },
+        ];
+        assert_eq!(assign_device_ids(&keys), ["wasapi:Mic", "wasapi:Mic#2"]);
+    }
+
     #[test]
     fn test_audio_capturer_default() {
         let capturer = AudioCapturer::new();
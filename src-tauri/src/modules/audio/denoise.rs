@@ -0,0 +1,319 @@
+//! 降噪模块
+//!
+//! 基于短时傅里叶变换的维纳滤波降噪：在 VAD 判定的静音段估计噪声功率谱，
+//! 按判决引导 (decision-directed) 的先验信噪比计算各频点增益，
+//! 并以增益下限抑制"音乐噪声"
+
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// 分析窗时长 (毫秒)，实际窗长取不小于该时长的 2 的幂
+const WINDOW_MS: usize = 32;
+/// 噪声谱平滑系数
+const NOISE_SMOOTHING: f32 = 0.9;
+/// 判决引导先验信噪比的平滑系数
+const PRIORI_SMOOTHING: f32 = 0.98;
+/// 默认增益下限 (约 -20dB)
+const DEFAULT_GAIN_FLOOR: f32 = 0.1;
+
+/// 谱降噪器
+///
+/// 输入输出均为单声道，输出长度与输入相同，整体延迟 [`NoiseSuppressor::latency`] 个样本。
+/// 尚未估计到噪声时原样透传 (仅延迟)
+pub struct NoiseSuppressor {
+    forward: Arc<dyn RealToComplex<f32>>,
+    inverse: Arc<dyn ComplexToReal<f32>>,
+    /// 窗长
+    fft_size: usize,
+    /// 帧移 (窗长的一半)
+    hop: usize,
+    /// 平方根汉宁窗，分析与合成各用一次
+    window: Vec<f32>,
+    /// 当前分析帧的输入样本
+    analysis: Vec<f32>,
+    /// 尚未凑满一个帧移的输入样本数
+    pending: usize,
+    /// 重叠相加累加器
+    overlap: Vec<f32>,
+    /// 已合成、待输出的样本
+    output: VecDeque<f32>,
+    /// 噪声功率谱估计
+    noise_psd: Vec<f32>,
+    /// 参与噪声估计的帧数
+    noise_frames: u32,
+    /// 上一帧的增益
+    prev_gain: Vec<f32>,
+    /// 上一帧的功率谱
+    prev_power: Vec<f32>,
+    /// 增益下限
+    gain_floor: f32,
+    time: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl NoiseSuppressor {
+    /// 创建降噪器
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 输入采样率
+    pub fn new(sample_rate: u32) -> Self {
+        let fft_size = (sample_rate as usize * WINDOW_MS / 1000).next_power_of_two().max(16);
+        let hop = fft_size / 2;
+        let bins = fft_size / 2 + 1;
+
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(fft_size);
+        let inverse = planner.plan_fft_inverse(fft_size);
+        let scratch_len = forward.get_scratch_len().max(inverse.get_scratch_len());
+
+        // 周期汉宁窗在 50% 重叠下满足 w²(n) + w²(n + hop) = 1，可完美重建
+        let window = (0..fft_size)
+            .map(|n| {
+                let hann = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * n as f32 / fft_size as f32).cos();
+                hann.sqrt()
+            })
+            .collect();
+
+        Self {
+            forward,
+            inverse,
+            fft_size,
+            hop,
+            window,
+            analysis: vec![0.0; fft_size],
+            pending: 0,
+            overlap: vec![0.0; fft_size],
+            // 预填一个帧移，保证每次都能输出与输入等长的数据
+            output: std::iter::repeat_n(0.0, hop).collect(),
+            noise_psd: vec![0.0; bins],
+            noise_frames: 0,
+            prev_gain: vec![1.0; bins],
+            prev_power: vec![0.0; bins],
+            gain_floor: DEFAULT_GAIN_FLOOR,
+            time: vec![0.0; fft_size],
+            spectrum: vec![Complex::new(0.0, 0.0); bins],
+            scratch: vec![Complex::new(0.0, 0.0); scratch_len],
+        }
+    }
+
+    /// 设置增益下限 (0.0 - 1.0)，越小降噪越强，残留噪声越不自然
+    pub fn set_gain_floor(&mut self, gain_floor: f32) {
+        self.gain_floor = gain_floor.clamp(0.0, 1.0);
+    }
+
+    /// 输出相对输入的延迟 (样本数)
+    pub fn latency(&self) -> usize {
+        self.fft_size
+    }
+
+    /// 是否已有噪声估计
+    pub fn has_noise_estimate(&self) -> bool {
+        self.noise_frames > 0
+    }
+
+    /// 处理一段音频
+    ///
+    /// # Arguments
+    /// * `input` - 单声道样本
+    /// * `is_speech` - VAD 判定结果，静音时更新噪声估计
+    ///
+    /// # Returns
+    /// 与输入等长的降噪后样本 (延迟 [`Self::latency`] 个样本)
+    pub fn process(&mut self, input: &[f32], is_speech: bool) -> Vec<f32> {
+        for &sample in input {
+            let start = self.fft_size - self.hop;
+            self.analysis[start + self.pending] = sample;
+            self.pending += 1;
+            if self.pending == self.hop {
+                self.process_hop(is_speech);
+                self.pending = 0;
+            }
+        }
+        self.output.drain(..input.len()).collect()
+    }
+
+    /// 取出滤波器中剩余的延迟样本，结束处理时调用
+    pub fn flush(&mut self) -> Vec<f32> {
+        // 补零推动剩余输入通过滤波器，补零部分不参与噪声估计
+        let padding = vec![0.0; self.latency()];
+        self.process(&padding, true)
+    }
+
+    /// 处理一个帧移：加窗 → FFT → 增益 → IFFT → 重叠相加
+    fn process_hop(&mut self, is_speech: bool) {
+        for ((t, &x), &w) in self.time.iter_mut().zip(&self.analysis).zip(&self.window) {
+            *t = x * w;
+        }
+        self.forward
+            .process_with_scratch(&mut self.time, &mut self.spectrum, &mut self.scratch)
+            .expect("FFT buffer sizes are fixed");
+
+        if !is_speech {
+            self.update_noise();
+        }
+        if self.has_noise_estimate() {
+            self.apply_gain();
+        }
+
+        self.inverse
+            .process_with_scratch(&mut self.spectrum, &mut self.time, &mut self.scratch)
+            .expect("FFT buffer sizes are fixed");
+
+        // 逆变换未归一化
+        let scale = 1.0 / self.fft_size as f32;
+        for ((acc, &y), &w) in self.overlap.iter_mut().zip(&self.time).zip(&self.window) {
+            *acc += y * w * scale;
+        }
+        self.output.extend(self.overlap.drain(..self.hop));
+        self.overlap.resize(self.fft_size, 0.0);
+        self.analysis.copy_within(self.hop.., 0);
+    }
+
+    /// 静音帧平滑更新噪声功率谱
+    fn update_noise(&mut self) {
+        for (noise, bin) in self.noise_psd.iter_mut().zip(&self.spectrum) {
+            let power = bin.norm_sqr();
+            *noise = if self.noise_frames == 0 {
+                power
+            } else {
+                NOISE_SMOOTHING * *noise + (1.0 - NOISE_SMOOTHING) * power
+            };
+        }
+        self.noise_frames = self.noise_frames.saturating_add(1);
+    }
+
+    /// 按判决引导的先验信噪比计算维纳增益
+    fn apply_gain(&mut self) {
+        let bins = self.spectrum.iter_mut().zip(&self.noise_psd);
+        for (k, (bin, &noise)) in bins.enumerate() {
+            let power = bin.norm_sqr();
+            let noise = noise.max(f32::EPSILON);
+            let posteriori = power / noise;
+            let priori = PRIORI_SMOOTHING * self.prev_gain[k].powi(2) * self.prev_power[k] / noise
+                + (1.0 - PRIORI_SMOOTHING) * (posteriori - 1.0).max(0.0);
+            let gain = (priori / (1.0 + priori)).max(self.gain_floor);
+
+            *bin *= gain;
+            self.prev_gain[k] = gain;
+            self.prev_power[k] = power;
+        }
+    }
+}
+
+impl std::fmt::Debug for NoiseSuppressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NoiseSuppressor")
+            .field("fft_size", &self.fft_size)
+            .field("noise_frames", &self.noise_frames)
+            .field("gain_floor", &self.gain_floor)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::{PlaybackSpeed, Signal, SyntheticSource};
+    use std::f32::consts::PI;
+
+    const RATE: u32 = 16000;
+    const FRAME: usize = 320;
+
+    /// 合成"语音"：200Hz 基频的谐波，4Hz 音节包络
+    fn synthetic_speech(samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|n| {
+                let t = n as f32 / RATE as f32;
+                let envelope = 0.5 + 0.5 * (2.0 * PI * 4.0 * t).sin();
+                let voiced: f32 = (1..=6)
+                    .map(|h| (2.0 * PI * 200.0 * h as f32 * t).sin() / h as f32)
+                    .sum();
+                0.2 * envelope * voiced
+            })
+            .collect()
+    }
+
+    fn white_noise(samples: usize, amplitude: f32) -> Vec<f32> {
+        let signal = Signal::WhiteNoise { amplitude, seed: 7 };
+        SyntheticSource::new(signal, RATE, 1, None, PlaybackSpeed::AsFastAsPossible).generate(samples)
+    }
+
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s * s).sum()
+    }
+
+    fn snr_db(clean: &[f32], processed: &[f32]) -> f32 {
+        let error: Vec<f32> = clean.iter().zip(processed).map(|(c, p)| p - c).collect();
+        10.0 * (energy(clean) / energy(&error)).log10()
+    }
+
+    /// 按 VAD 帧长分块处理，返回与输入对齐 (已补偿延迟) 的输出
+    fn run(suppressor: &mut NoiseSuppressor, input: &[f32], speech_from: usize) -> Vec<f32> {
+        let mut output = Vec::new();
+        for (i, frame) in input.chunks(FRAME).enumerate() {
+            output.extend(suppressor.process(frame, i * FRAME >= speech_from));
+        }
+        output.extend(suppressor.flush());
+        output.split_off(suppressor.latency())
+    }
+
+    #[test]
+    fn test_passthrough_without_noise_estimate() {
+        let mut suppressor = NoiseSuppressor::new(RATE);
+        let input = synthetic_speech(8000);
+        let output = run(&mut suppressor, &input, 0);
+
+        assert!(!suppressor.has_noise_estimate());
+        assert_eq!(output.len(), input.len());
+        // 窗函数满足完美重建
+        assert!(input.iter().zip(&output).all(|(a, b)| (a - b).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_output_length_matches_input() {
+        let mut suppressor = NoiseSuppressor::new(RATE);
+        for len in [1, 100, 320, 555] {
+            assert_eq!(suppressor.process(&vec![0.1; len], false).len(), len);
+        }
+    }
+
+    #[test]
+    fn test_snr_improves_on_noisy_speech() {
+        // 500ms 纯噪声 (VAD 静音) + 1.5s 带噪语音
+        let lead_in = 8000;
+        let clean: Vec<f32> = std::iter::repeat_n(0.0, lead_in)
+            .chain(synthetic_speech(24000))
+            .collect();
+        let noise = white_noise(clean.len(), 0.08);
+        let noisy: Vec<f32> = clean.iter().zip(&noise).map(|(c, n)| c + n).collect();
+
+        let mut suppressor = NoiseSuppressor::new(RATE);
+        let output = run(&mut suppressor, &noisy, lead_in);
+
+        // 跳过语音开头的收敛段
+        let range = lead_in + 1600..clean.len();
+        let before = snr_db(&clean[range.clone()], &noisy[range.clone()]);
+        let after = snr_db(&clean[range.clone()], &output[range]);
+        assert!(after - before > 5.0, "SNR before {:.1}dB, after {:.1}dB", before, after);
+    }
+
+    #[test]
+    fn test_noise_only_is_attenuated() {
+        let noise = white_noise(16000, 0.05);
+        let mut suppressor = NoiseSuppressor::new(RATE);
+        let output = run(&mut suppressor, &noise, usize::MAX);
+
+        // 噪声谱收敛后的后半段
+        let attenuation_db = 10.0 * (energy(&noise[8000..]) / energy(&output[8000..])).log10();
+        assert!(attenuation_db > 10.0, "attenuation {:.1}dB", attenuation_db);
+    }
+
+    #[test]
+    fn test_window_size_scales_with_rate() {
+        assert_eq!(NoiseSuppressor::new(16000).latency(), 512);
+        assert_eq!(NoiseSuppressor::new(48000).latency(), 2048);
+    }
+}
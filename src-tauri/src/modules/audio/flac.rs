@@ -0,0 +1,440 @@
+//! FLAC 编码器
+//!
+//! 精简的 16 位 FLAC 编码实现：固定块长、独立声道、固定预测器 (0-4 阶)
+//! 配合单分区 Rice 编码，无法压缩时退化为 VERBATIM 子帧
+//!
+//! 不使用现有 crate 的原因：libFLAC 绑定需要系统 C 库，增加各平台的打包依赖；
+//! 纯 Rust 的 flacenc 按整段音频编码，录音需在内存中保留整个会话 (48kHz 立体声
+//! 两分钟约 46MB)。这里每凑满一块即写出，内存占用固定，输出由测试用 claxon 解码校验
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// 每块的帧数 (每帧包含所有声道)
+const BLOCK_SIZE: usize = 4096;
+/// 位深
+const BITS_PER_SAMPLE: u32 = 16;
+/// STREAMINFO 在文件中的偏移 ("fLaC" + 元数据块头)
+const STREAMINFO_OFFSET: u64 = 8;
+/// STREAMINFO 长度
+const STREAMINFO_LEN: usize = 34;
+/// 4 位 Rice 参数的最大值 (15 为转义码)
+const MAX_RICE_PARAM: u32 = 14;
+
+/// FLAC 写入器
+pub struct FlacWriter<W: Write + Seek> {
+    writer: W,
+    sample_rate: u32,
+    channels: u16,
+    /// 尚未凑满一块的交错样本
+    pending: Vec<i32>,
+    frame_number: u32,
+    total_frames: u64,
+    min_frame_size: u32,
+    max_frame_size: u32,
+}
+
+impl FlacWriter<BufWriter<File>> {
+    /// 创建 FLAC 文件
+    pub fn create(path: impl AsRef<Path>, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), sample_rate, channels)
+    }
+}
+
+impl<W: Write + Seek> FlacWriter<W> {
+    /// 创建写入器并写入文件头
+    pub fn new(mut writer: W, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        if !(1..=8).contains(&channels) || sample_rate == 0 || sample_rate >= 1 << 20 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported FLAC format: {}Hz, {} channels", sample_rate, channels),
+            ));
+        }
+
+        writer.write_all(b"fLaC")?;
+        // 最后一个元数据块，类型 0 (STREAMINFO)
+        writer.write_all(&[0x80, 0, 0, STREAMINFO_LEN as u8])?;
+
+        let mut flac = Self {
+            writer,
+            sample_rate,
+            channels,
+            pending: Vec::with_capacity(BLOCK_SIZE * channels as usize),
+            frame_number: 0,
+            total_frames: 0,
+            min_frame_size: 0,
+            max_frame_size: 0,
+        };
+        let info = flac.stream_info();
+        flac.writer.write_all(&info)?;
+        Ok(flac)
+    }
+
+    /// 写入交错 f32 样本，按 16 位量化
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        let block_len = BLOCK_SIZE * self.channels as usize;
+        for &sample in samples {
+            self.pending.push((sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i32);
+            if self.pending.len() == block_len {
+                self.write_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 写出剩余数据并回填 STREAMINFO
+    pub fn finalize(mut self) -> io::Result<W> {
+        // 丢弃不完整的尾帧
+        let channels = self.channels as usize;
+        self.pending.truncate(self.pending.len() - self.pending.len() % channels);
+        if !self.pending.is_empty() {
+            self.write_block()?;
+        }
+
+        let info = self.stream_info();
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(STREAMINFO_OFFSET))?;
+        self.writer.write_all(&info)?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// 编码并写出一块
+    fn write_block(&mut self) -> io::Result<()> {
+        let channels = self.channels as usize;
+        let block_size = self.pending.len() / channels;
+
+        let mut bits = BitWriter::default();
+        // 帧头
+        bits.write(0b11_1111_1111_1110, 14); // 同步码
+        bits.write(0, 1); // 保留
+        bits.write(0, 1); // 固定块长
+        bits.write(0b0111, 4); // 块长在帧头末尾以 16 位给出
+        bits.write(0b0000, 4); // 采样率取自 STREAMINFO
+        bits.write(channels as u64 - 1, 4); // 独立声道
+        bits.write(0b100, 3); // 16 位
+        bits.write(0, 1); // 保留
+        for byte in utf8_number(self.frame_number) {
+            bits.write(byte as u64, 8);
+        }
+        bits.write(block_size as u64 - 1, 16);
+        let crc = crc8(bits.bytes());
+        bits.write(crc as u64, 8);
+
+        // 子帧
+        let mut channel = Vec::with_capacity(block_size);
+        for ch in 0..channels {
+            channel.clear();
+            channel.extend(self.pending.iter().skip(ch).step_by(channels));
+            write_subframe(&mut bits, &channel);
+        }
+
+        // 帧尾
+        bits.align();
+        let crc = crc16(bits.bytes());
+        bits.write(crc as u64, 16);
+
+        let frame = bits.into_bytes();
+        self.writer.write_all(&frame)?;
+
+        let size = frame.len() as u32;
+        self.min_frame_size = if self.frame_number == 0 { size } else { self.min_frame_size.min(size) };
+        self.max_frame_size = self.max_frame_size.max(size);
+        self.frame_number += 1;
+        self.total_frames += block_size as u64;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// 构建 STREAMINFO 块 (MD5 置零表示未计算)
+    fn stream_info(&self) -> Vec<u8> {
+        let mut bits = BitWriter::default();
+        bits.write(BLOCK_SIZE as u64, 16); // 最小块长
+        bits.write(BLOCK_SIZE as u64, 16); // 最大块长
+        bits.write(self.min_frame_size as u64, 24);
+        bits.write(self.max_frame_size as u64, 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(self.channels as u64 - 1, 3);
+        bits.write(BITS_PER_SAMPLE as u64 - 1, 5);
+        bits.write(self.total_frames, 36);
+        bits.write(0, 64);
+        bits.write(0, 64);
+        bits.into_bytes()
+    }
+}
+
+/// 编码单个声道的子帧，选择开销最小的编码方式
+fn write_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&s| s == samples[0]) {
+        bits.write(0, 1);
+        bits.write(0b000000, 6); // CONSTANT
+        bits.write(0, 1);
+        bits.write_signed(samples[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+    let best = (0..=4usize)
+        .filter(|&order| order < samples.len())
+        .filter_map(|order| {
+            let residuals = fixed_residuals(samples, order);
+            let (param, cost) = best_rice_param(&residuals)?;
+            let total = order as u64 * BITS_PER_SAMPLE as u64 + 6 + 4 + cost;
+            Some((total, order, param, residuals))
+        })
+        .min_by_key(|(total, ..)| *total);
+
+    match best {
+        Some((total, order, param, residuals)) if total < verbatim_bits => {
+            bits.write(0, 1);
+            bits.write(0b001000 | order as u64, 6); // FIXED
+            bits.write(0, 1);
+            for &warmup in &samples[..order] {
+                bits.write_signed(warmup, BITS_PER_SAMPLE);
+            }
+            bits.write(0b00, 2); // 4 位 Rice 参数
+            bits.write(0, 4); // 分区阶数 0
+            bits.write(param as u64, 4);
+            for &residual in &residuals {
+                bits.write_rice(residual, param);
+            }
+        }
+        _ => {
+            bits.write(0, 1);
+            bits.write(0b000001, 6); // VERBATIM
+            bits.write(0, 1);
+            for &sample in samples {
+                bits.write_signed(sample, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// 固定预测器残差
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |k: usize| samples[i - k];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// 选择编码开销最小的 Rice 参数，返回 (参数, 残差总位数)
+fn best_rice_param(residuals: &[i32]) -> Option<(u32, u64)> {
+    let folded: Vec<u64> = residuals.iter().map(|&r| zigzag(r) as u64).collect();
+    (0..=MAX_RICE_PARAM)
+        .map(|k| {
+            let cost: u64 = folded.iter().map(|&u| (u >> k) + 1 + k as u64).sum();
+            (k, cost)
+        })
+        .min_by_key(|&(_, cost)| cost)
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// 帧号的 UTF-8 风格变长编码
+fn utf8_number(value: u32) -> Vec<u8> {
+    if value < 0x80 {
+        return vec![value as u8];
+    }
+    let mut continuation = Vec::new();
+    let mut v = value;
+    // 每个后续字节携带 6 位，首字节剩余容量随字节数递减
+    loop {
+        continuation.push(0x80 | (v & 0x3F) as u8);
+        v >>= 6;
+        let len = continuation.len() + 1;
+        let first_capacity = 7 - len as u32;
+        if v < (1 << first_capacity) {
+            let prefix = !(0xFFu8 >> len);
+            let mut out = vec![prefix | v as u8];
+            out.extend(continuation.iter().rev());
+            return out;
+        }
+    }
+}
+
+/// CRC-8，多项式 x^8 + x^2 + x + 1
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// CRC-16，多项式 x^16 + x^15 + x^2 + 1
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// 按位写入缓冲 (高位在前)
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// 当前字节已使用的位数
+    used: u32,
+}
+
+impl BitWriter {
+    /// 写入 `value` 的低 `count` 位
+    fn write(&mut self, value: u64, count: u32) {
+        for i in (0..count).rev() {
+            self.push_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn write_signed(&mut self, value: i32, count: u32) {
+        self.write(value as u64 & ((1u64 << count) - 1), count);
+    }
+
+    fn write_rice(&mut self, value: i32, param: u32) {
+        let folded = zigzag(value);
+        for _ in 0..(folded >> param) {
+            self.push_bit(false);
+        }
+        self.push_bit(true);
+        self.write(folded as u64, param);
+    }
+
+    fn push_bit(&mut self, bit: bool) {
+        if self.used == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> self.used;
+        }
+        self.used = (self.used + 1) % 8;
+    }
+
+    /// 补零至字节边界
+    fn align(&mut self) {
+        self.used = 0;
+    }
+
+    /// 已写入的完整字节 (调用前需对齐)
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn encode(samples: &[f32], sample_rate: u32, channels: u16) -> Vec<u8> {
+        let mut writer = FlacWriter::new(Cursor::new(Vec::new()), sample_rate, channels).unwrap();
+        writer.write_samples(samples).unwrap();
+        writer.finalize().unwrap().into_inner()
+    }
+
+    fn decode(bytes: Vec<u8>) -> (claxon::metadata::StreamInfo, Vec<i32>) {
+        let mut reader = claxon::FlacReader::new(Cursor::new(bytes)).unwrap();
+        let info = reader.streaminfo();
+        let samples = reader.samples().collect::<Result<Vec<_>, _>>().unwrap();
+        (info, samples)
+    }
+
+    fn quantize(samples: &[f32]) -> Vec<i32> {
+        samples.iter().map(|&s| (s.clamp(-1.0, 1.0) * 32767.0).round() as i32).collect()
+    }
+
+    #[test]
+    fn test_flac_roundtrip_tone() {
+        // 立体声，两声道内容不同，长度不是块长整数倍
+        let frames = BLOCK_SIZE * 2 + 1234;
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|i| {
+                let t = i as f32 / 16000.0;
+                [0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin(), 0.25 * (2.0 * std::f32::consts::PI * 1000.0 * t).cos()]
+            })
+            .collect();
+
+        let bytes = encode(&samples, 16000, 2);
+        // 平滑信号应当被压缩
+        assert!(bytes.len() < samples.len() * 2);
+
+        let (info, decoded) = decode(bytes);
+        assert_eq!(info.sample_rate, 16000);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, 16);
+        assert_eq!(info.samples, Some(frames as u64));
+        assert_eq!(decoded, quantize(&samples));
+    }
+
+    #[test]
+    fn test_flac_roundtrip_noise_and_silence() {
+        // 伪随机噪声 (走 VERBATIM 或低阶预测) + 静音 (CONSTANT) + 削波
+        let mut state = 12345u32;
+        let mut samples: Vec<f32> = (0..5000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0
+            })
+            .collect();
+        samples.extend(std::iter::repeat_n(0.0, BLOCK_SIZE));
+        samples.extend([1.5, -1.5, 1.0, -1.0]);
+
+        let (info, decoded) = decode(encode(&samples, 48000, 1));
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.samples, Some(samples.len() as u64));
+        assert_eq!(decoded, quantize(&samples));
+    }
+
+    #[test]
+    fn test_flac_many_frames() {
+        // 超过 128 帧时帧号使用多字节编码
+        let samples = vec![0.1f32; BLOCK_SIZE * 130];
+        let (info, decoded) = decode(encode(&samples, 8000, 1));
+        assert_eq!(info.samples, Some(samples.len() as u64));
+        assert_eq!(decoded.len(), samples.len());
+    }
+
+    #[test]
+    fn test_flac_empty() {
+        let (info, decoded) = decode(encode(&[], 16000, 1));
+        // 总样本数 0 在 STREAMINFO 中表示未知
+        assert_eq!(info.samples, None);
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn test_utf8_number() {
+        assert_eq!(utf8_number(0x7F), vec![0x7F]);
+        assert_eq!(utf8_number(0x80), vec![0xC2, 0x80]);
+        assert_eq!(utf8_number(0x7FF), vec![0xDF, 0xBF]);
+        assert_eq!(utf8_number(0x800), vec![0xE0, 0xA0, 0x80]);
+    }
+
+    #[test]
+    fn test_invalid_format() {
+        assert!(FlacWriter::new(Cursor::new(Vec::new()), 16000, 0).is_err());
+        assert!(FlacWriter::new(Cursor::new(Vec::new()), 0, 1).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! 系统音频 (回环) 采集模块
+//!
+//! Linux 上 PulseAudio/PipeWire 为每个输出设备提供监听源 (monitor source)。
+//! ALSA 设备枚举中看不到这些监听源，因此通过 `pactl` 列出，
+//! 并由 `parec` 子进程录制 (PipeWire 通过 pipewire-pulse 兼容)。
+//! 设备 ID 为 `monitor:<源名称>`，与普通输入设备一样可在配置中选择
+
+use super::capture::DeviceSummary;
+use crate::error::AudioError;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// 监听源设备 ID 前缀
+pub const MONITOR_ID_PREFIX: &str = "monitor:";
+/// 回环类设备名称中的关键字 (小写)
+const LOOPBACK_KEYWORDS: [&str; 4] = ["monitor", "loopback", "stereo mix", "blackhole"];
+/// `parec` 请求的缓冲延迟 (毫秒)
+const PAREC_LATENCY_MS: u32 = 20;
+
+/// 监听源缓存：刷新时的普通输入设备 ID 集合及对应的监听源列表
+static MONITOR_CACHE: Mutex<Option<(Vec<String>, Vec<MonitorSource>)>> = Mutex::new(None);
+
+/// 监听源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorSource {
+    /// PulseAudio 源名称 (如 `alsa_output.pci-0000_00_1f.3.analog-stereo.monitor`)
+    pub name: String,
+    /// 显示名称
+    pub description: String,
+}
+
+impl MonitorSource {
+    /// 设备 ID
+    pub fn device_id(&self) -> String {
+        format!("{}{}", MONITOR_ID_PREFIX, self.name)
+    }
+
+    /// 设备摘要
+    pub fn summary(&self) -> DeviceSummary {
+        DeviceSummary { id: self.device_id(), name: self.description.clone() }
+    }
+}
+
+/// 从设备 ID 中取出监听源名称，非监听源返回 None
+pub fn monitor_source_name(device_id: &str) -> Option<&str> {
+    device_id
+        .strip_prefix(MONITOR_ID_PREFIX)
+        .filter(|name| !name.is_empty())
+}
+
+/// 根据设备名称判断是否为回环类设备 (监听源、snd-aloop、立体声混音等)
+pub fn is_loopback_device_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_KEYWORDS.iter().any(|keyword| name.contains(keyword))
+}
+
+/// 列出系统中的监听源
+///
+/// 仅 Linux 支持，`pactl` 不可用时返回空列表
+pub fn monitor_sources() -> Vec<MonitorSource> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("pactl")
+            .env("LC_ALL", "C")
+            .args(["list", "sources"])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                parse_pactl_sources(&String::from_utf8_lossy(&output.stdout))
+            }
+            Ok(output) => {
+                tracing::debug!("pactl exited with {}", output.status);
+                Vec::new()
+            }
+            Err(e) => {
+                tracing::debug!("Failed to run pactl: {}", e);
+                Vec::new()
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+/// 获取缓存的监听源列表
+///
+/// `pactl` 需要启动子进程，不适合在热插拔轮询中反复调用。
+/// 仅在普通输入设备集合 (`device_ids`) 与上次刷新时不同时重新查询
+pub fn cached_monitor_sources(device_ids: &[String]) -> Vec<MonitorSource> {
+    let mut cache = MONITOR_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    match cache.as_ref() {
+        Some((ids, sources)) if ids.as_slice() == device_ids => sources.clone(),
+        _ => {
+            let sources = monitor_sources();
+            *cache = Some((device_ids.to_vec(), sources.clone()));
+            sources
+        }
+    }
+}
+
+/// 重新查询监听源并更新缓存 (打开设备选择界面时调用)
+pub fn refresh_monitor_sources(device_ids: &[String]) -> Vec<MonitorSource> {
+    let sources = monitor_sources();
+    *MONITOR_CACHE.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some((device_ids.to_vec(), sources.clone()));
+    sources
+}
+
+/// 解析 `pactl list sources` 的输出 (C 语言环境)，只保留监听源
+pub fn parse_pactl_sources(output: &str) -> Vec<MonitorSource> {
+    let mut sources = Vec::new();
+    // (名称, 描述, 是否为监听源)
+    let mut current: Option<(String, String, bool)> = None;
+
+    for line in output.lines() {
+        if line.starts_with("Source #") {
+            sources.extend(current.take().and_then(into_monitor));
+            current = Some(Default::default());
+            continue;
+        }
+        let Some((name, description, is_monitor)) = current.as_mut() else {
+            continue;
+        };
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "Name" => *name = value.to_string(),
+            "Description" => *description = value.to_string(),
+            "Monitor of Sink" => *is_monitor = value != "n/a",
+            _ => {}
+        }
+    }
+    sources.extend(current.and_then(into_monitor));
+    sources
+}
+
+fn into_monitor((name, description, is_monitor): (String, String, bool)) -> Option<MonitorSource> {
+    if name.is_empty() || !(is_monitor || name.ends_with(".monitor")) {
+        return None;
+    }
+    let description = if description.is_empty() { name.clone() } else { description };
+    Some(MonitorSource { name, description })
+}
+
+/// 监听源采集流
+///
+/// 运行 `parec` 录制监听源，从标准输出读取 f32 小端交错样本。
+/// 丢弃时结束子进程并等待读取线程退出
+pub struct MonitorStream {
+    child: Child,
+    /// 主动停止，读取线程据此区分正常结束和意外退出
+    stopping: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl MonitorStream {
+    /// 启动监听源采集
+    ///
+    /// # Arguments
+    /// * `source` - 监听源名称
+    /// * `sample_rate` - 采样率，由 PulseAudio 负责重采样
+    /// * `channels` - 通道数
+    /// * `on_data` - 读取线程收到整帧样本时调用
+    /// * `on_lost` - 子进程意外退出时调用
+    pub fn spawn(
+        source: &str,
+        sample_rate: u32,
+        channels: u16,
+        mut on_data: impl FnMut(&[f32]) + Send + 'static,
+        on_lost: impl FnOnce() + Send + 'static,
+    ) -> Result<Self, AudioError> {
+        let mut child = Command::new("parec")
+            .arg(format!("--device={}", source))
+            .args(["--raw", "--format=float32le"])
+            .arg(format!("--rate={}", sample_rate))
+            .arg(format!("--channels={}", channels))
+            .arg(format!("--latency-msec={}", PAREC_LATENCY_MS))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| AudioError::StreamCreationFailed(format!("Failed to run parec: {}", e)))?;
+        let Some(mut stdout) = child.stdout.take() else {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AudioError::StreamCreationFailed("parec stdout unavailable".to_string()));
+        };
+
+        let stopping = Arc::new(AtomicBool::new(false));
+        let reader_stopping = stopping.clone();
+        let frame_bytes = 4 * channels.max(1) as usize;
+        let reader = std::thread::Builder::new()
+            .name("monitor-capture".to_string())
+            .spawn(move || {
+                let mut bytes = vec![0u8; frame_bytes * 1024];
+                let mut samples = Vec::with_capacity(bytes.len() / 4);
+                // 上次读取剩余的不完整帧字节数
+                let mut pending = 0;
+                loop {
+                    match stdout.read(&mut bytes[pending..]) {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            let available = pending + n;
+                            let whole = available / frame_bytes * frame_bytes;
+                            decode_f32le(&bytes[..whole], &mut samples);
+                            if !samples.is_empty() {
+                                on_data(&samples);
+                            }
+                            bytes.copy_within(whole..available, 0);
+                            pending = available - whole;
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                        Err(e) => {
+                            tracing::debug!("Failed to read parec output: {}", e);
+                            break;
+                        }
+                    }
+                }
+                if !reader_stopping.load(Ordering::SeqCst) {
+                    tracing::warn!("parec exited unexpectedly");
+                    on_lost();
+                }
+            });
+        let reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(AudioError::StreamCreationFailed(e.to_string()));
+            }
+        };
+
+        Ok(Self { child, stopping, reader: Some(reader) })
+    }
+}
+
+impl Drop for MonitorStream {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// 把 f32 小端字节解码为样本，覆盖 `out` 原有内容
+fn decode_f32le(bytes: &[u8], out: &mut Vec<f32>) {
+    out.clear();
+    out.extend(
+        bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACTL_OUTPUT: &str = "\
+Source #0
+\tState: SUSPENDED
+\tName: alsa_output.pci-0000_00_1f.3.analog-stereo.monitor
+\tDescription: Monitor of Built-in Audio Analog Stereo
+\tDriver: module-alsa-card.c
+\tMonitor of Sink: alsa_output.pci-0000_00_1f.3.analog-stereo
+\tProperties:
+\t\tdevice.class = \"monitor\"
+
+Source #1
+\tState: RUNNING
+\tName: alsa_input.pci-0000_00_1f.3.analog-stereo
+\tDescription: Built-in Audio Analog Stereo
+\tMonitor of Sink: n/a
+
+Source #2
+\tName: bluez_output.00_11_22.1.monitor
+\tMonitor of Sink: bluez_output.00_11_22.1
+";
+
+    #[test]
+    fn test_parse_pactl_sources() {
+        let sources = parse_pactl_sources(PACTL_OUTPUT);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].name, "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor");
+        assert_eq!(sources[0].description, "Monitor of Built-in Audio Analog Stereo");
+        // 没有描述时使用名称
+        assert_eq!(sources[1].description, sources[1].name);
+        assert!(parse_pactl_sources("").is_empty());
+    }
+
+    #[test]
+    fn test_monitor_device_id_round_trip() {
+        let source = MonitorSource { name: "sink.monitor".to_string(), description: "Sink".to_string() };
+        let id = source.device_id();
+        assert_eq!(id, "monitor:sink.monitor");
+        assert_eq!(monitor_source_name(&id), Some("sink.monitor"));
+        assert_eq!(monitor_source_name("alsa:default"), None);
+        assert_eq!(monitor_source_name("monitor:"), None);
+        assert_eq!(source.summary().name, "Sink");
+    }
+
+    #[test]
+    fn test_is_loopback_device_name() {
+        assert!(is_loopback_device_name("Monitor of Built-in Audio"));
+        assert!(is_loopback_device_name("hw:CARD=Loopback,DEV=1"));
+        assert!(is_loopback_device_name("Stereo Mix (Realtek)"));
+        assert!(!is_loopback_device_name("USB Microphone"));
+    }
+
+    #[test]
+    fn test_decode_f32le() {
+        let bytes: Vec<u8> = [0.5f32, -1.0, 0.25]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let mut samples = vec![9.0];
+        decode_f32le(&bytes, &mut samples);
+        assert_eq!(samples, vec![0.5, -1.0, 0.25]);
+        decode_f32le(&[], &mut samples);
+        assert!(samples.is_empty());
+    }
+}
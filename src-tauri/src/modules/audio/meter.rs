@@ -0,0 +1,279 @@
+//! 电平表模块
+//!
+//! 按采集流计算输入电平：RMS 与峰值 (dBFS)、带保持与衰减的峰值指示、削波计数。
+//! 读数按音频时间节流输出，供悬浮窗显示音量
+
+use super::capture::AudioFrame;
+use std::time::Duration;
+
+/// 电平下限 (dBFS)，静音读数不低于此值
+pub const METER_FLOOR_DB: f32 = -100.0;
+
+/// 电平表配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelMeterConfig {
+    /// 读数输出间隔 (默认约 30Hz)
+    pub interval: Duration,
+    /// 峰值保持时长
+    pub peak_hold: Duration,
+    /// 保持结束后峰值的衰减速度 (dB/秒)
+    pub decay_db_per_sec: f32,
+    /// 削波判定阈值 (线性幅度)
+    pub clip_threshold: f32,
+}
+
+impl Default for LevelMeterConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(33),
+            peak_hold: Duration::from_millis(1000),
+            decay_db_per_sec: 20.0,
+            clip_threshold: 0.999,
+        }
+    }
+}
+
+/// 电平读数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelReading {
+    /// 本次间隔内的 RMS (线性, 0.0 - 1.0)
+    pub rms: f32,
+    /// 本次间隔内的峰值 (线性)
+    pub peak: f32,
+    /// RMS (dBFS)
+    pub rms_db: f32,
+    /// 峰值 (dBFS)
+    pub peak_db: f32,
+    /// 峰值保持指示 (dBFS)
+    pub peak_hold_db: f32,
+    /// 会话开始以来的削波样本数
+    pub clipped_samples: u64,
+    /// 本次间隔内是否出现削波
+    pub clipping: bool,
+    /// 读数对应的采集时间 (纳秒)
+    pub timestamp_ns: u128,
+}
+
+/// 电平表
+///
+/// 所有通道合并统计，时间按帧的采样率与时间戳推算，与采集端使用同一时间轴
+#[derive(Debug, Clone)]
+pub struct LevelMeter {
+    config: LevelMeterConfig,
+    /// 当前间隔的平方和
+    sum_squares: f64,
+    /// 当前间隔的样本数
+    samples: u64,
+    /// 当前间隔的峰值
+    window_peak: f32,
+    /// 当前间隔是否削波
+    window_clipped: bool,
+    /// 当前间隔的开始时间
+    window_start: Option<u128>,
+    /// 峰值保持指示 (dBFS)
+    peak_hold_db: f32,
+    /// 峰值保持开始时间
+    peak_hold_since: u128,
+    /// 上次更新峰值保持的时间
+    last_update: u128,
+    clipped_samples: u64,
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new(LevelMeterConfig::default())
+    }
+}
+
+impl LevelMeter {
+    pub fn new(config: LevelMeterConfig) -> Self {
+        Self {
+            config,
+            sum_squares: 0.0,
+            samples: 0,
+            window_peak: 0.0,
+            window_clipped: false,
+            window_start: None,
+            peak_hold_db: METER_FLOOR_DB,
+            peak_hold_since: 0,
+            last_update: 0,
+            clipped_samples: 0,
+        }
+    }
+
+    /// 送入一帧采集数据，到达输出间隔时返回读数
+    pub fn process(&mut self, frame: &AudioFrame) -> Option<LevelReading> {
+        if frame.samples.is_empty() {
+            return None;
+        }
+        let window_start = *self.window_start.get_or_insert(frame.timestamp_ns);
+
+        for &sample in &frame.samples {
+            let magnitude = sample.abs();
+            self.sum_squares += (sample as f64) * (sample as f64);
+            self.window_peak = self.window_peak.max(magnitude);
+            if magnitude >= self.config.clip_threshold {
+                self.clipped_samples += 1;
+                self.window_clipped = true;
+            }
+        }
+        self.samples += frame.samples.len() as u64;
+
+        let now = frame.end_timestamp_ns();
+        if now.saturating_sub(window_start) < self.config.interval.as_nanos() {
+            return None;
+        }
+        Some(self.take_reading(now))
+    }
+
+    /// 重置所有状态 (含削波计数)
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// 会话开始以来的削波样本数
+    pub fn clipped_samples(&self) -> u64 {
+        self.clipped_samples
+    }
+
+    /// 结束当前间隔并生成读数
+    fn take_reading(&mut self, now: u128) -> LevelReading {
+        let rms = (self.sum_squares / self.samples.max(1) as f64).sqrt() as f32;
+        let peak = self.window_peak;
+        let peak_db = to_db(peak);
+        self.update_peak_hold(peak_db, now);
+
+        let reading = LevelReading {
+            rms,
+            peak,
+            rms_db: to_db(rms),
+            peak_db,
+            peak_hold_db: self.peak_hold_db,
+            clipped_samples: self.clipped_samples,
+            clipping: self.window_clipped,
+            timestamp_ns: now,
+        };
+
+        self.sum_squares = 0.0;
+        self.samples = 0;
+        self.window_peak = 0.0;
+        self.window_clipped = false;
+        self.window_start = Some(now);
+        reading
+    }
+
+    /// 新峰值不低于保持值时重新开始保持，否则保持期满后按速率衰减
+    fn update_peak_hold(&mut self, peak_db: f32, now: u128) {
+        if peak_db >= self.peak_hold_db {
+            self.peak_hold_db = peak_db;
+            self.peak_hold_since = now;
+        } else {
+            let hold_end = self.peak_hold_since + self.config.peak_hold.as_nanos();
+            let decay_from = self.last_update.max(hold_end);
+            if now > decay_from {
+                let elapsed_s = (now - decay_from) as f32 / 1e9;
+                self.peak_hold_db = (self.peak_hold_db - self.config.decay_db_per_sec * elapsed_s)
+                    .max(peak_db);
+            }
+        }
+        self.last_update = now;
+    }
+}
+
+/// 线性幅度转换为 dBFS，不低于 [`METER_FLOOR_DB`]
+fn to_db(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return METER_FLOOR_DB;
+    }
+    (20.0 * amplitude.log10()).max(METER_FLOOR_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::capture::frames_to_ns;
+
+    const RATE: u32 = 16000;
+    /// 10ms
+    const CHUNK: usize = 160;
+
+    fn samples_for(duration: Duration, sample_rate: u32) -> usize {
+        (duration.as_nanos() * sample_rate as u128 / 1_000_000_000) as usize
+    }
+
+    /// 连续送入恒定幅度的方波，返回所有读数
+    fn feed(meter: &mut LevelMeter, amplitude: f32, chunks: usize, start_ns: u128) -> Vec<LevelReading> {
+        (0..chunks)
+            .filter_map(|i| {
+                let samples = (0..CHUNK)
+                    .map(|n| if n % 2 == 0 { amplitude } else { -amplitude })
+                    .collect();
+                let ts = start_ns + frames_to_ns((i * CHUNK) as u64, RATE);
+                meter.process(&AudioFrame::new(samples, RATE, 1, ts))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_readings_are_throttled() {
+        let mut meter = LevelMeter::default();
+        // 1 秒音频按 10ms 送入，约 30 个读数
+        let readings = feed(&mut meter, 0.1, 100, 0);
+        assert!((25..=34).contains(&readings.len()), "{} readings", readings.len());
+        assert!(readings.windows(2).all(|w| w[1].timestamp_ns > w[0].timestamp_ns));
+    }
+
+    #[test]
+    fn test_rms_and_peak_dbfs() {
+        let mut meter = LevelMeter::default();
+        let reading = feed(&mut meter, 0.5, 10, 0)[0];
+        assert!((reading.rms - 0.5).abs() < 1e-4);
+        assert!((reading.peak_db + 6.02).abs() < 0.01);
+        assert!((reading.rms_db - reading.peak_db).abs() < 0.01);
+        assert!(!reading.clipping);
+    }
+
+    #[test]
+    fn test_silence_reads_floor() {
+        let mut meter = LevelMeter::default();
+        let reading = feed(&mut meter, 0.0, 10, 0)[0];
+        assert_eq!(reading.rms_db, METER_FLOOR_DB);
+        assert_eq!(reading.peak_hold_db, METER_FLOOR_DB);
+    }
+
+    #[test]
+    fn test_peak_hold_then_decay() {
+        let config = LevelMeterConfig::default();
+        let mut meter = LevelMeter::new(config);
+        feed(&mut meter, 0.5, 10, 0);
+
+        // 保持期内峰值指示不变
+        let hold = samples_for(config.peak_hold, RATE) / CHUNK;
+        let during = feed(&mut meter, 0.01, hold - 10, 100_000_000);
+        assert!(during.iter().all(|r| (r.peak_hold_db + 6.02).abs() < 0.01));
+
+        // 保持期后每秒衰减 20dB，直到当前峰值
+        let after = feed(&mut meter, 0.01, 100, 1_000_000_000);
+        let last = after.last().unwrap();
+        assert!(last.peak_hold_db < -20.0, "hold {}", last.peak_hold_db);
+        assert!(last.peak_hold_db >= last.peak_db);
+        assert!(after.windows(2).all(|w| w[1].peak_hold_db <= w[0].peak_hold_db));
+    }
+
+    #[test]
+    fn test_clip_detection() {
+        let mut meter = LevelMeter::default();
+        let readings = feed(&mut meter, 1.0, 10, 0);
+        assert!(readings[0].clipping);
+        assert!(readings[0].clipped_samples > 0);
+
+        // 削波计数累计，之后的安静间隔不再标记削波
+        let clipped = meter.clipped_samples();
+        let readings = feed(&mut meter, 0.1, 10, 100_000_000);
+        assert!(!readings.last().unwrap().clipping);
+        assert_eq!(readings.last().unwrap().clipped_samples, clipped);
+
+        meter.reset();
+        assert_eq!(meter.clipped_samples(), 0);
+    }
+}
@@ -0,0 +1,657 @@
+//! 多输入混合模块
+//!
+//! 同时读取多个音频源 (如本地麦克风与会议的远端音频)，各自混为单声道、
+//! 重采样到主输入的采样率并施加各自的增益，按采集时间戳对齐后
+//! 叠加为单声道，或按输入顺序交错为多通道帧 (每个通道带有标签)
+
+use super::capture::{AudioConfig, AudioFrame, frames_to_ns};
+use super::resampler::BatchResampler;
+use super::source::AudioSource;
+use crate::error::AudioError;
+use crate::modules::config::MultiInputMode;
+use std::collections::VecDeque;
+
+/// 麦克风通道标签
+pub const MICROPHONE_LABEL: &str = "mic";
+/// 系统音频通道标签
+pub const SYSTEM_AUDIO_LABEL: &str = "system";
+/// 单次从各输入读取的最大样本数
+const INPUT_READ_SAMPLES: usize = 4800;
+/// 输入之间允许的最大偏差 (毫秒)
+///
+/// 次输入落后超过该值时补静音 (如监听源在无播放时不产出数据)，
+/// 领先超过该值时丢弃最旧的数据
+const MAX_SKEW_MS: u32 = 200;
+/// 按时间戳对齐的容差 (毫秒)，更小的偏差不做校正
+const ALIGN_TOLERANCE_MS: u32 = 10;
+
+/// 混合源中的一路输入
+struct MixedInput {
+    label: String,
+    source: Box<dyn AudioSource>,
+    /// 使用的设备 ID，None 表示使用会话配置的设备
+    device_id: Option<String>,
+    /// 次输入的设备已失效或无法打开，之后以静音代替
+    lost: bool,
+    /// 线性增益
+    gain: f32,
+    /// 重采样到输出采样率的单声道样本
+    buffer: VecDeque<f32>,
+    /// 缓冲首个样本的采集时间 (纳秒)
+    start_ns: u128,
+    /// (输入采样率, 重采样器)
+    resampler: Option<(u32, BatchResampler)>,
+}
+
+impl MixedInput {
+    /// 读取输入中已有的数据，有新数据时以最新数据的时间戳回推缓冲起点，
+    /// 丢帧造成的时间跳变不会累积
+    fn pull(&mut self, output_rate: u32) -> Result<(), AudioError> {
+        let channels = self.source.channels().max(1) as usize;
+        let read_size = INPUT_READ_SAMPLES - INPUT_READ_SAMPLES % channels;
+        let mut end_ns = None;
+        while let Some(frame) = self.source.read_frame(read_size) {
+            let mono = frame.to_mono();
+            let resampled = if mono.sample_rate == output_rate {
+                mono
+            } else {
+                let rate = mono.sample_rate;
+                if !matches!(self.resampler, Some((r, _)) if r == rate) {
+                    self.resampler = Some((rate, BatchResampler::new(rate, output_rate)?));
+                }
+                match self.resampler.as_mut() {
+                    Some((_, resampler)) => resampler.process_frame(&mono)?,
+                    None => continue,
+                }
+            };
+            if !resampled.samples.is_empty() {
+                end_ns = Some(resampled.end_timestamp_ns());
+                let gain = self.gain;
+                self.buffer.extend(resampled.samples.iter().map(|s| s * gain));
+            }
+        }
+        if let Some(end_ns) = end_ns {
+            let buffered = frames_to_ns(self.buffer.len() as u64, output_rate);
+            self.start_ns = end_ns.saturating_sub(buffered);
+        }
+        Ok(())
+    }
+
+    /// 按会话配置打开输入，指定了设备时替换设备 ID
+    fn configure(&mut self, config: &AudioConfig) -> Result<(), AudioError> {
+        let mut config = config.clone();
+        if self.device_id.is_some() {
+            config.device_id = self.device_id.clone();
+        }
+        self.buffer.clear();
+        self.start_ns = 0;
+        self.resampler = None;
+        self.source.configure(config)
+    }
+
+    /// 停止失效的次输入，已缓冲的数据读完后以静音代替
+    fn drop_input(&mut self, reason: &dyn std::fmt::Display) {
+        tracing::warn!(
+            "Input {} ({}) unavailable, continuing without it: {}",
+            self.label,
+            self.device_id.as_deref().unwrap_or("default"),
+            reason
+        );
+        let _ = self.source.stop();
+        self.lost = true;
+        self.resampler = None;
+    }
+
+    /// 在缓冲前端补静音
+    fn pad_front(&mut self, frames: usize, sample_rate: u32) {
+        for _ in 0..frames {
+            self.buffer.push_front(0.0);
+        }
+        self.start_ns = self.start_ns.saturating_sub(frames_to_ns(frames as u64, sample_rate));
+    }
+
+    /// 丢弃缓冲前端的样本
+    fn drop_front(&mut self, frames: usize, sample_rate: u32) {
+        let frames = frames.min(self.buffer.len());
+        self.buffer.drain(..frames);
+        self.start_ns += frames_to_ns(frames as u64, sample_rate);
+    }
+}
+
+/// 多输入混合源
+///
+/// 第一路为主输入，决定输出采样率、时间戳和设备 ID；只有主输入失效时才报告流中断，
+/// 由会话切换到备用设备。次输入失效或无法打开时单独停用，对应通道以静音代替。
+/// [`MultiInputMode::Separate`] 时每路输入占一个输出通道，通道标签见
+/// [`AudioSource::channel_labels`]；[`MultiInputMode::Mix`] 时输出单声道。
+/// 各输入的时间戳使用同一零点 (见 [`AudioCapturer::with_epoch`]) 时按采集时间对齐，
+/// 偏差超出 [`MAX_SKEW_MS`] 视为时钟不可比，只按缓冲长度对齐
+///
+/// [`AudioCapturer::with_epoch`]: super::capture::AudioCapturer::with_epoch
+#[derive(Default)]
+pub struct MixedSource {
+    inputs: Vec<MixedInput>,
+    mode: MultiInputMode,
+    sample_rate: u32,
+}
+
+impl MixedSource {
+    pub fn new(mode: MultiInputMode) -> Self {
+        Self { mode, ..Self::default() }
+    }
+
+    /// 添加一路输入
+    ///
+    /// # Arguments
+    /// * `label` - 通道标签
+    /// * `source` - 音频源
+    /// * `device_id` - 使用的设备 ID，None 表示使用会话配置的设备
+    /// * `gain_db` - 输入增益 (dB)
+    pub fn add_input(
+        &mut self,
+        label: impl Into<String>,
+        source: Box<dyn AudioSource>,
+        device_id: Option<String>,
+        gain_db: f32,
+    ) {
+        self.inputs.push(MixedInput {
+            label: label.into(),
+            source,
+            device_id,
+            lost: false,
+            gain: 10f32.powf(gain_db / 20.0),
+            buffer: VecDeque::new(),
+            start_ns: 0,
+            resampler: None,
+        });
+    }
+
+    /// 合并方式
+    pub fn mode(&self) -> MultiInputMode {
+        self.mode
+    }
+
+    /// 拉取各输入的数据，并把次输入对齐到主输入
+    fn pull_inputs(&mut self) -> Result<(), AudioError> {
+        let sample_rate = self.sample_rate;
+        let max_skew = (sample_rate * MAX_SKEW_MS / 1000) as usize;
+        let tolerance = (sample_rate * ALIGN_TOLERANCE_MS / 1000) as i128;
+        let Some((primary, others)) = self.inputs.split_first_mut() else {
+            return Ok(());
+        };
+
+        primary.pull(sample_rate)?;
+        let primary_len = primary.buffer.len();
+        let primary_start = primary.start_ns;
+
+        for input in others {
+            if !input.lost && input.source.is_stream_lost() {
+                input.drop_input(&"stream lost");
+            }
+            if input.lost {
+                let len = input.buffer.len();
+                if len == 0 {
+                    input.start_ns = primary_start;
+                }
+                input.buffer.extend(std::iter::repeat_n(0.0, primary_len.saturating_sub(len)));
+                continue;
+            }
+            input.pull(sample_rate)?;
+
+            // 按采集时间对齐缓冲起点
+            if !input.buffer.is_empty() {
+                let offset_ns = input.start_ns as i128 - primary_start as i128;
+                let offset = offset_ns * sample_rate as i128 / 1_000_000_000;
+                if offset.unsigned_abs() <= max_skew as u128 && offset.abs() > tolerance {
+                    if offset > 0 {
+                        input.pad_front(offset as usize, sample_rate);
+                    } else {
+                        input.drop_front(offset.unsigned_abs() as usize, sample_rate);
+                    }
+                }
+            }
+
+            // 按缓冲长度兜底
+            let len = input.buffer.len();
+            if len + max_skew < primary_len {
+                if len == 0 {
+                    input.start_ns = primary_start;
+                }
+                input.buffer.extend(std::iter::repeat_n(0.0, primary_len - len));
+            } else if len > primary_len + max_skew {
+                input.drop_front(len - primary_len - max_skew, sample_rate);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AudioSource for MixedSource {
+    /// 各输入使用会话配置，指定了设备的输入只替换设备 ID
+    ///
+    /// 主输入指定的设备无法打开时改用会话配置的设备 (如切换后的备用设备)；
+    /// 次输入无法打开时停用，已停用的次输入不再重新打开
+    fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError> {
+        let Some((primary, others)) = self.inputs.split_first_mut() else {
+            return Err(AudioError::ConfigurationFailed("Mixed source has no inputs".to_string()));
+        };
+        if let Err(e) = primary.configure(&config) {
+            if primary.device_id.is_none() {
+                return Err(e);
+            }
+            tracing::warn!("Primary input {} unavailable, using session device: {}", primary.label, e);
+            primary.device_id = None;
+            primary.configure(&config)?;
+        }
+        for input in others.iter_mut().filter(|input| !input.lost) {
+            if let Err(e) = input.configure(&config) {
+                input.drop_input(&e);
+            }
+        }
+        self.sample_rate = primary.source.sample_rate();
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), AudioError> {
+        for (i, input) in self.inputs.iter_mut().enumerate() {
+            if input.lost {
+                continue;
+            }
+            if let Err(e) = input.source.start() {
+                // 已启动的输入随之停止
+                for started in &mut self.inputs[..i] {
+                    let _ = started.source.stop();
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), AudioError> {
+        let mut result = Ok(());
+        for input in self.inputs.iter_mut().filter(|input| !input.lost) {
+            if let Err(e) = input.source.stop() {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    fn is_running(&self) -> bool {
+        self.inputs.first().is_some_and(|input| input.source.is_running())
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        match self.mode {
+            MultiInputMode::Separate => self.inputs.len().max(1) as u16,
+            MultiInputMode::Mix => 1,
+        }
+    }
+
+    fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame> {
+        if let Err(e) = self.pull_inputs() {
+            tracing::warn!("Failed to read mixed input: {}", e);
+        }
+
+        let inputs = self.inputs.len();
+        let channels = self.channels() as usize;
+        let frames = self.inputs
+            .iter()
+            .map(|input| input.buffer.len())
+            .min()
+            .unwrap_or(0)
+            .min(max_samples / channels);
+        if frames == 0 {
+            return None;
+        }
+
+        let mut samples = vec![0.0; frames * channels];
+        let timestamp_ns = self.inputs[0].start_ns;
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            let drained = input.buffer.drain(..frames);
+            match self.mode {
+                MultiInputMode::Separate => {
+                    for (frame, sample) in drained.enumerate() {
+                        samples[frame * inputs + index] = sample;
+                    }
+                }
+                MultiInputMode::Mix => {
+                    for (mixed, sample) in samples.iter_mut().zip(drained) {
+                        *mixed += sample;
+                    }
+                }
+            }
+            input.start_ns += frames_to_ns(frames as u64, self.sample_rate);
+        }
+        if self.mode == MultiInputMode::Mix {
+            samples.iter_mut().for_each(|s| *s = s.clamp(-1.0, 1.0));
+        }
+        Some(AudioFrame::new(samples, self.sample_rate, channels as u16, timestamp_ns))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.inputs
+            .first()
+            .is_some_and(|input| input.source.is_finished() && input.buffer.is_empty())
+    }
+
+    fn device_id(&self) -> Option<&str> {
+        self.inputs.first().and_then(|input| input.source.device_id())
+    }
+
+    fn is_stream_lost(&self) -> bool {
+        self.inputs.first().is_some_and(|input| input.source.is_stream_lost())
+    }
+
+    /// 叠加为单声道时没有通道标签
+    fn channel_labels(&self) -> Vec<String> {
+        match self.mode {
+            MultiInputMode::Separate => self.inputs.iter().map(|input| input.label.clone()).collect(),
+            MultiInputMode::Mix => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::{FileSource, PlaybackSpeed, Signal, SyntheticSource};
+    use std::time::Duration;
+
+    fn tone(frequency: f32, amplitude: f32, seconds: u64) -> Box<dyn AudioSource> {
+        Box::new(SyntheticSource::new(
+            Signal::Tone { frequency, amplitude },
+            48000,
+            1,
+            Some(Duration::from_secs(seconds)),
+            PlaybackSpeed::AsFastAsPossible,
+        ))
+    }
+
+    fn config(sample_rate: u32, channels: u16) -> AudioConfig {
+        AudioConfig { sample_rate, channels, ..AudioConfig::default() }
+    }
+
+    fn read_all(source: &mut MixedSource) -> Vec<AudioFrame> {
+        source.start().unwrap();
+        let mut frames = Vec::new();
+        while !source.is_finished() {
+            match source.read_frame(960) {
+                Some(frame) => frames.push(frame),
+                None => break,
+            }
+        }
+        frames
+    }
+
+    fn channel_rms(frames: &[AudioFrame], channel: usize) -> f32 {
+        let samples: Vec<f32> = frames
+            .iter()
+            .flat_map(|f| f.samples.iter().skip(channel).step_by(f.channels as usize).copied())
+            .collect();
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+    }
+
+    #[test]
+    fn test_mixed_source_labeled_channels() {
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        source.add_input(MICROPHONE_LABEL, tone(440.0, 0.5, 1), None, 0.0);
+        let system = Some("monitor:sink.monitor".to_string());
+        source.add_input(SYSTEM_AUDIO_LABEL, tone(1000.0, 0.1, 1), system, 0.0);
+        source.configure(config(16000, 2)).unwrap();
+
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.sample_rate(), 16000);
+        assert_eq!(source.channel_labels(), vec!["mic", "system"]);
+
+        let frames = read_all(&mut source);
+        let total: usize = frames.iter().map(|f| f.frames()).sum();
+        assert_eq!(total, 16000);
+        assert!((channel_rms(&frames, 0) - 0.354).abs() < 0.01);
+        assert!((channel_rms(&frames, 1) - 0.0707).abs() < 0.01);
+        // 时间戳连续
+        assert!(frames.windows(2).all(|w| w[1].timestamp_ns == w[0].end_timestamp_ns()));
+    }
+
+    #[test]
+    fn test_mixed_source_resamples_secondary_input() {
+        // 次输入为固定 48kHz 的文件源，主输入按配置为 16kHz
+        let secondary: Vec<f32> = (0..48000).map(|n| if (n / 24) % 2 == 0 { 0.2 } else { -0.2 }).collect();
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        source.add_input(MICROPHONE_LABEL, tone(440.0, 0.5, 1), None, 0.0);
+        source.add_input(
+            SYSTEM_AUDIO_LABEL,
+            Box::new(FileSource::from_samples(secondary, 48000, 1, PlaybackSpeed::AsFastAsPossible)),
+            None,
+            0.0,
+        );
+        source.configure(config(16000, 1)).unwrap();
+
+        let frames = read_all(&mut source);
+        let total: usize = frames.iter().map(|f| f.frames()).sum();
+        // 重采样器的延迟使次输入略短，其余部分补静音
+        assert!(total >= 15500, "{} frames", total);
+        assert!(channel_rms(&frames, 1) > 0.1);
+    }
+
+    #[test]
+    fn test_mixed_source_pads_stalled_input() {
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        source.add_input(MICROPHONE_LABEL, tone(440.0, 0.5, 1), None, 0.0);
+        // 没有数据的次输入
+        source.add_input(
+            SYSTEM_AUDIO_LABEL,
+            Box::new(SyntheticSource::new(
+                Signal::Silence,
+                16000,
+                1,
+                Some(Duration::ZERO),
+                PlaybackSpeed::AsFastAsPossible,
+            )),
+            None,
+            0.0,
+        );
+        source.configure(config(16000, 1)).unwrap();
+
+        let frames = read_all(&mut source);
+        let total: usize = frames.iter().map(|f| f.frames()).sum();
+        assert_eq!(total, 16000);
+        assert_eq!(channel_rms(&frames, 1), 0.0);
+    }
+
+    /// 时间戳整体偏移的音频源
+    struct DelayedSource {
+        inner: FileSource,
+        delay_ns: u128,
+    }
+
+    impl AudioSource for DelayedSource {
+        fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError> {
+            self.inner.configure(config)
+        }
+        fn start(&mut self) -> Result<(), AudioError> {
+            self.inner.start()
+        }
+        fn stop(&mut self) -> Result<(), AudioError> {
+            self.inner.stop()
+        }
+        fn is_running(&self) -> bool {
+            self.inner.is_running()
+        }
+        fn sample_rate(&self) -> u32 {
+            self.inner.sample_rate()
+        }
+        fn channels(&self) -> u16 {
+            self.inner.channels()
+        }
+        fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame> {
+            let mut frame = self.inner.read_frame(max_samples)?;
+            frame.timestamp_ns += self.delay_ns;
+            Some(frame)
+        }
+        fn is_finished(&self) -> bool {
+            self.inner.is_finished()
+        }
+    }
+
+    fn constant(value: f32, frames: usize) -> FileSource {
+        FileSource::from_samples(vec![value; frames], 16000, 1, PlaybackSpeed::AsFastAsPossible)
+    }
+
+    #[test]
+    fn test_mixed_source_aligns_by_timestamp() {
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        source.add_input("me", Box::new(constant(0.5, 16000)), None, 0.0);
+        // 远端音频晚 50ms 开始
+        let remote = DelayedSource { inner: constant(0.25, 8000), delay_ns: 50_000_000 };
+        source.add_input("remote", Box::new(remote), None, 0.0);
+        source.configure(config(16000, 1)).unwrap();
+
+        let frames = read_all(&mut source);
+        let remote: Vec<f32> = frames
+            .iter()
+            .flat_map(|f| f.samples.iter().skip(1).step_by(2).copied())
+            .collect();
+        let first = remote.iter().position(|&s| s != 0.0).unwrap();
+        assert_eq!(first, 800);
+        assert!(remote[800..8800].iter().all(|&s| s == 0.25));
+        assert_eq!(frames[0].timestamp_ns, 0);
+    }
+
+    #[test]
+    fn test_mixed_source_mix_mode_applies_gain() {
+        let mut source = MixedSource::new(MultiInputMode::Mix);
+        source.add_input("me", Box::new(constant(0.25, 16000)), None, 0.0);
+        source.add_input("remote", Box::new(constant(0.5, 16000)), None, -6.0206);
+        source.configure(config(16000, 1)).unwrap();
+
+        assert_eq!(source.channels(), 1);
+        assert!(source.channel_labels().is_empty());
+        let frames = read_all(&mut source);
+        let total: usize = frames.iter().map(|f| f.frames()).sum();
+        assert_eq!(total, 16000);
+        assert!(frames.iter().flat_map(|f| &f.samples).all(|s| (s - 0.5).abs() < 1e-3));
+    }
+
+    /// 指定设备缺失或流中断的音频源
+    struct FlakySource {
+        inner: FileSource,
+        /// 无法打开的设备 ID
+        missing: Option<String>,
+        device_id: Option<String>,
+        /// 读取这么多帧后流中断
+        lost_after: Option<usize>,
+        read: usize,
+    }
+
+    impl FlakySource {
+        fn new(inner: FileSource) -> Self {
+            Self { inner, missing: None, device_id: None, lost_after: None, read: 0 }
+        }
+    }
+
+    impl AudioSource for FlakySource {
+        fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError> {
+            if config.device_id.is_some() && config.device_id == self.missing {
+                return Err(AudioError::NoDevice);
+            }
+            self.device_id = config.device_id.clone();
+            self.inner.configure(config)
+        }
+        fn start(&mut self) -> Result<(), AudioError> {
+            self.inner.start()
+        }
+        fn stop(&mut self) -> Result<(), AudioError> {
+            self.inner.stop()
+        }
+        fn is_running(&self) -> bool {
+            self.inner.is_running()
+        }
+        fn sample_rate(&self) -> u32 {
+            self.inner.sample_rate()
+        }
+        fn channels(&self) -> u16 {
+            self.inner.channels()
+        }
+        fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame> {
+            if self.is_stream_lost() {
+                return None;
+            }
+            let frame = self.inner.read_frame(max_samples.min(1600))?;
+            self.read += frame.frames();
+            Some(frame)
+        }
+        fn is_finished(&self) -> bool {
+            self.inner.is_finished()
+        }
+        fn device_id(&self) -> Option<&str> {
+            self.device_id.as_deref()
+        }
+        fn is_stream_lost(&self) -> bool {
+            self.lost_after.is_some_and(|after| self.read >= after)
+        }
+    }
+
+    #[test]
+    fn test_mixed_source_drops_lost_secondary_input() {
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        source.add_input("me", Box::new(constant(0.5, 16000)), None, 0.0);
+        // 远端设备在 0.1s 后被拔出
+        let remote = FlakySource { lost_after: Some(1600), ..FlakySource::new(constant(0.25, 16000)) };
+        source.add_input("remote", Box::new(remote), Some("usb-headset".to_string()), 0.0);
+        source.configure(config(16000, 1)).unwrap();
+
+        // 主输入继续采集，不触发会话的设备切换，远端通道之后为静音
+        let frames = read_all(&mut source);
+        assert!(!source.is_stream_lost());
+        let total: usize = frames.iter().map(|f| f.frames()).sum();
+        assert_eq!(total, 16000);
+        assert!((channel_rms(&frames, 0) - 0.5).abs() < 1e-6);
+        let remote: Vec<f32> = frames.iter().flat_map(|f| f.samples.iter().skip(1).step_by(2).copied()).collect();
+        assert!(remote[..1600].iter().all(|&s| s == 0.25));
+        assert!(remote[3200..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_mixed_source_reports_only_primary_loss() {
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        let primary = FlakySource { lost_after: Some(1600), ..FlakySource::new(constant(0.5, 16000)) };
+        source.add_input("me", Box::new(primary), None, 0.0);
+        source.add_input("remote", Box::new(constant(0.25, 16000)), None, 0.0);
+        source.configure(config(16000, 1)).unwrap();
+        source.start().unwrap();
+        while source.read_frame(960).is_some() {}
+        assert!(source.is_stream_lost());
+    }
+
+    #[test]
+    fn test_mixed_source_skips_missing_devices() {
+        let mut source = MixedSource::new(MultiInputMode::Separate);
+        let primary = FlakySource { missing: Some("usb-mic".to_string()), ..FlakySource::new(constant(0.5, 16000)) };
+        source.add_input("me", Box::new(primary), Some("usb-mic".to_string()), 0.0);
+        let remote = FlakySource { missing: Some("usb-headset".to_string()), ..FlakySource::new(constant(0.25, 16000)) };
+        source.add_input("remote", Box::new(remote), Some("usb-headset".to_string()), 0.0);
+
+        // 主输入改用会话配置的设备 (如切换后的备用设备)，次输入停用
+        let audio = AudioConfig { device_id: Some("fallback".to_string()), ..config(16000, 1) };
+        source.configure(audio).unwrap();
+        assert_eq!(source.device_id(), Some("fallback"));
+        assert_eq!(source.channels(), 2);
+
+        let frames = read_all(&mut source);
+        let total: usize = frames.iter().map(|f| f.frames()).sum();
+        assert_eq!(total, 16000);
+        assert!((channel_rms(&frames, 0) - 0.5).abs() < 1e-6);
+        assert_eq!(channel_rms(&frames, 1), 0.0);
+    }
+
+    #[test]
+    fn test_mixed_source_requires_inputs() {
+        assert!(MixedSource::new(MultiInputMode::Separate).configure(AudioConfig::default()).is_err());
+    }
+}
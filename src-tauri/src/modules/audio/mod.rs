@@ -1,11 +1,62 @@
 //! 音频处理模块
 //!
-//! 提供音频采集、重采样和语音活动检测功能
+//! 提供音频采集、系统音频回环采集、多输入混合、音频源抽象、重采样、降噪、自动增益、
+//! 可组合处理链、电平表、麦克风校准、语音活动检测 (能量与频谱特征)、语音段切分、免手动停止、会话录音和设备热插拔监听功能
 
+pub mod agc;
+pub mod autostop;
+pub mod calibration;
 pub mod capture;
+pub mod denoise;
+pub mod flac;
+pub mod loopback;
+pub mod meter;
+pub mod mix;
+pub mod noise_floor;
+pub mod preroll;
+pub mod processing;
+pub mod recorder;
 pub mod resampler;
+pub mod segmenter;
+pub mod source;
+pub mod spectral_vad;
+#[cfg(test)]
+mod test_signals;
 pub mod vad;
+#[cfg(test)]
+mod vad_eval;
+pub mod watcher;
 
-pub use capture::{AudioCapturer, AudioConfig, AudioDeviceInfo, AudioFrame, RingBuffer};
-pub use resampler::{AudioResampler, BatchResampler};
-pub use vad::{VadConfig, VadLevel, VadState, VoiceActivityDetector};
+pub use agc::{AgcConfig, AutoGainControl};
+pub use autostop::{AutoStop, AutoStopConfig, AutoStopEvent, AutoStopReason};
+pub use calibration::{
+    CalibrationPhase, CalibrationResult, Calibrator, LevelCollector, LevelDistribution, record_phase,
+};
+pub use capture::{
+    AudioCapturer, AudioConfig, AudioDeviceInfo, AudioFrame, BufferSizeRange, CaptureClock,
+    DeviceSummary, NegotiatedConfig, OverflowPolicy, ReadChunk, RingBuffer, SampleRateRange,
+    frames_to_ns, negotiate_config,
+};
+pub use denoise::NoiseSuppressor;
+pub use flac::FlacWriter;
+pub use loopback::{
+    MONITOR_ID_PREFIX, MonitorSource, is_loopback_device_name, monitor_source_name, monitor_sources,
+};
+pub use meter::{LevelMeter, LevelMeterConfig, LevelReading, METER_FLOOR_DB};
+pub use mix::{MICROPHONE_LABEL, MixedSource, SYSTEM_AUDIO_LABEL};
+pub use noise_floor::NoiseFloorTracker;
+pub use preroll::{DEFAULT_PREROLL_MS, FrameHistory, MAX_PREROLL_MS, PreRollBuffer};
+pub use processing::{
+    AudioProcessor, AutoGain, DEFAULT_HIGH_PASS_HZ, DcRemover, Downmix, Gain, HighPassFilter,
+    NoiseSuppression, ProcessingChain, Resample, StageStats,
+};
+pub use recorder::{RecorderConfig, SessionRecorder};
+pub use resampler::{AudioResampler, BatchResampler, ResamplerConfig, ResamplerQuality};
+pub use segmenter::{Segmenter, SegmenterConfig, Utterance, split_utterances};
+pub use source::{
+    AudioSource, FileSource, PcmEncoding, PcmFormat, PlaybackSpeed, Signal, SourceKind,
+    SyntheticSource,
+};
+pub use spectral_vad::{SpectralDetector, SpectralFeatures};
+pub use vad::{AdaptiveThreshold, VadConfig, VadLevel, VadMode, VadState, VoiceActivityDetector};
+pub use watcher::{DeviceListChange, DeviceWatcher, diff_devices, select_failover_device};
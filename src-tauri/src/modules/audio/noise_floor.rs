@@ -0,0 +1,142 @@
+//! 底噪跟踪模块
+//!
+//! 最小统计 (minimum statistics) 底噪估计：对逐帧能量做平滑，
+//! 取最近一个窗口内平滑能量的最小值并补偿偏差作为底噪。
+//! 语音中的停顿使窗口内的最小值落在静音段，因此无需事先知道哪些帧是静音
+
+use std::collections::VecDeque;
+
+/// 窗口划分的子窗口数，每个子窗口结束时滚动一次
+const SUBWINDOWS: usize = 4;
+/// 逐帧能量的平滑系数
+const SMOOTHING: f32 = 0.5;
+/// 最小值相对平均底噪的偏差补偿 (线性均方)
+const BIAS: f32 = 1.5;
+/// 能量下限，避免数字静音得到负无穷
+const MIN_ENERGY: f32 = 1e-12;
+
+/// 最小统计底噪跟踪器
+///
+/// 能量为线性均方值，`floor_db` 使用 VAD 能量刻度 (均方值的 20·log10)
+#[derive(Debug, Clone)]
+pub struct NoiseFloorTracker {
+    /// 窗口长度 (帧)
+    window_frames: usize,
+    /// 子窗口长度 (帧)
+    subwindow_frames: usize,
+    /// 平滑后的能量
+    smoothed: Option<f32>,
+    /// 当前子窗口内的最小值
+    current_min: f32,
+    /// 当前子窗口已累计的帧数
+    current_frames: usize,
+    /// 之前各子窗口的最小值
+    minima: VecDeque<f32>,
+    /// 已处理的帧数
+    frames: usize,
+}
+
+impl NoiseFloorTracker {
+    /// 创建跟踪器
+    ///
+    /// # Arguments
+    /// * `window_frames` - 窗口长度 (帧)，应长于一般的连续发音时长
+    pub fn new(window_frames: usize) -> Self {
+        let window_frames = window_frames.max(SUBWINDOWS);
+        Self {
+            window_frames,
+            subwindow_frames: window_frames / SUBWINDOWS,
+            smoothed: None,
+            current_min: f32::INFINITY,
+            current_frames: 0,
+            minima: VecDeque::with_capacity(SUBWINDOWS),
+            frames: 0,
+        }
+    }
+
+    /// 输入一帧的能量 (线性均方)
+    pub fn update(&mut self, energy: f32) {
+        let energy = energy.max(MIN_ENERGY);
+        let smoothed = match self.smoothed {
+            Some(previous) => SMOOTHING * previous + (1.0 - SMOOTHING) * energy,
+            None => energy,
+        };
+        self.smoothed = Some(smoothed);
+        self.current_min = self.current_min.min(smoothed);
+        self.current_frames += 1;
+        self.frames = self.frames.saturating_add(1);
+
+        if self.current_frames >= self.subwindow_frames {
+            if self.minima.len() == SUBWINDOWS - 1 {
+                self.minima.pop_front();
+            }
+            self.minima.push_back(self.current_min);
+            self.current_min = f32::INFINITY;
+            self.current_frames = 0;
+        }
+    }
+
+    /// 当前底噪 (线性均方)，尚无数据时返回 None
+    pub fn floor(&self) -> Option<f32> {
+        let min = self.minima.iter().copied().fold(self.current_min, f32::min);
+        min.is_finite().then_some(min * BIAS)
+    }
+
+    /// 当前底噪 (VAD 能量刻度)
+    pub fn floor_db(&self) -> Option<f32> {
+        self.floor().map(|floor| 20.0 * floor.log10())
+    }
+
+    /// 是否已处理满一个窗口，此前的估计可能偏高 (如开头即为语音)
+    pub fn is_settled(&self) -> bool {
+        self.frames >= self.window_frames
+    }
+
+    /// 重置估计
+    pub fn reset(&mut self) {
+        *self = Self::new(self.window_frames);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_floor_ignores_speech_bursts() {
+        let mut tracker = NoiseFloorTracker::new(60);
+        assert!(tracker.floor().is_none());
+
+        // 底噪 1e-6 上间歇出现高能量语音
+        for i in 0..300 {
+            let energy = if (i / 10) % 3 == 0 { 1e-2 } else { 1e-6 };
+            tracker.update(energy);
+        }
+        let floor = tracker.floor().unwrap();
+        assert!(floor > 1e-6 && floor < 1e-5, "{}", floor);
+        assert!(tracker.is_settled());
+    }
+
+    #[test]
+    fn test_noise_floor_follows_level_changes() {
+        let mut tracker = NoiseFloorTracker::new(40);
+        for _ in 0..50 {
+            tracker.update(1e-6);
+        }
+        // 背景变吵后一个窗口内跟上
+        for _ in 0..50 {
+            tracker.update(1e-3);
+        }
+        let floor_db = tracker.floor_db().unwrap();
+        assert!((floor_db - (-60.0 + 20.0 * BIAS.log10())).abs() < 1.0, "{}", floor_db);
+
+        // 变安静时立即跟上
+        tracker.update(1e-6);
+        tracker.update(1e-6);
+        assert!(tracker.floor().unwrap() < 1e-3);
+
+        tracker.reset();
+        assert!(tracker.floor().is_none());
+        assert!(!tracker.is_settled());
+    }
+}
@@ -0,0 +1,186 @@
+//! 预录缓冲
+//!
+//! 保留最近一段时间的音频，在语音段或会话开始时一并发送，
+//! 避免 VAD 确认语音前的首个音节丢失。
+//! [`PreRollBuffer`] 位于 VAD 之后，缓存会话中的静音帧；
+//! [`FrameHistory`] 位于采集端，待机时缓存热键按下之前的原始帧
+
+use super::capture::AudioFrame;
+use std::collections::VecDeque;
+
+/// 默认预录时长 (毫秒)
+pub const DEFAULT_PREROLL_MS: u32 = 400;
+/// 最大预录时长 (毫秒)
+pub const MAX_PREROLL_MS: u32 = 1000;
+
+/// 预录缓冲区
+///
+/// 固定容量，写满后丢弃最旧的样本
+#[derive(Debug, Clone)]
+pub struct PreRollBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl PreRollBuffer {
+    /// 创建指定容量 (样本数) 的缓冲区
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// 按时长创建缓冲区，时长上限为 [`MAX_PREROLL_MS`]
+    pub fn with_duration(duration_ms: u32, sample_rate: u32) -> Self {
+        let duration_ms = duration_ms.min(MAX_PREROLL_MS);
+        Self::new((sample_rate as u64 * duration_ms as u64 / 1000) as usize)
+    }
+
+    /// 写入样本，超出容量时丢弃最旧的样本
+    pub fn push(&mut self, samples: &[f32]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let overflow = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(samples);
+    }
+
+    /// 取出全部样本 (按时间顺序) 并清空
+    pub fn drain(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+
+    /// 清空缓冲区
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// 当前样本数
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// 容量 (样本数)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// 原始音频帧历史
+///
+/// 按时长保留最近的采集帧，超出时整帧丢弃最旧的数据
+#[derive(Debug, Clone, Default)]
+pub struct FrameHistory {
+    frames: VecDeque<AudioFrame>,
+    /// 保留时长 (纳秒)
+    max_ns: u128,
+    /// 当前总时长 (纳秒)
+    total_ns: u128,
+}
+
+impl FrameHistory {
+    /// 创建保留 `duration_ms` 的历史，时长上限为 [`MAX_PREROLL_MS`]
+    pub fn with_duration(duration_ms: u32) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            max_ns: duration_ms.min(MAX_PREROLL_MS) as u128 * 1_000_000,
+            total_ns: 0,
+        }
+    }
+
+    /// 追加一帧，丢弃不再需要的最旧帧
+    pub fn push(&mut self, frame: AudioFrame) {
+        if self.max_ns == 0 {
+            return;
+        }
+        self.total_ns += frame.duration_ns();
+        self.frames.push_back(frame);
+        // 去掉最旧的帧后仍能覆盖保留时长时才丢弃
+        while let Some(oldest) = self.frames.front() {
+            let oldest_ns = oldest.duration_ns();
+            if self.total_ns - oldest_ns < self.max_ns {
+                break;
+            }
+            self.total_ns -= oldest_ns;
+            self.frames.pop_front();
+        }
+    }
+
+    /// 取出全部帧 (按时间顺序) 并清空
+    pub fn drain(&mut self) -> Vec<AudioFrame> {
+        self.total_ns = 0;
+        self.frames.drain(..).collect()
+    }
+
+    /// 当前总时长 (纳秒)
+    pub fn duration_ns(&self) -> u128 {
+        self.total_ns
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preroll_keeps_latest_samples() {
+        let mut buffer = PreRollBuffer::new(4);
+        buffer.push(&[1.0, 2.0, 3.0]);
+        buffer.push(&[4.0, 5.0]);
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.drain(), vec![2.0, 3.0, 4.0, 5.0]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_preroll_push_larger_than_capacity() {
+        let mut buffer = PreRollBuffer::new(3);
+        buffer.push(&[0.0]);
+        buffer.push(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(buffer.drain(), vec![3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_preroll_with_duration() {
+        assert_eq!(PreRollBuffer::with_duration(400, 16000).capacity(), 6400);
+        // 超出上限时截断
+        assert_eq!(PreRollBuffer::with_duration(5000, 16000).capacity(), 16000);
+
+        let mut disabled = PreRollBuffer::with_duration(0, 16000);
+        disabled.push(&[1.0; 10]);
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn test_frame_history_keeps_latest_frames() {
+        // 保留 100ms，每帧 40ms
+        let mut history = FrameHistory::with_duration(100);
+        for i in 0..5u128 {
+            history.push(AudioFrame::new(vec![i as f32; 640], 16000, 1, i * 40_000_000));
+        }
+        assert_eq!(history.duration_ns(), 120_000_000);
+
+        let frames = history.drain();
+        let timestamps: Vec<u128> = frames.iter().map(|f| f.timestamp_ns / 1_000_000).collect();
+        assert_eq!(timestamps, vec![80, 120, 160]);
+        assert!(history.is_empty());
+        assert_eq!(history.duration_ns(), 0);
+
+        let mut disabled = FrameHistory::with_duration(0);
+        disabled.push(AudioFrame::new(vec![0.0; 640], 16000, 1, 0));
+        assert!(disabled.is_empty());
+    }
+}
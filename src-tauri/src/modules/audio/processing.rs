@@ -0,0 +1,1008 @@
+//! 音频处理链模块
+//!
+//! 以 [`AudioProcessor`] 为统一接口串联各处理阶段 (去直流、高通滤波、固定增益、降噪、
+//! 自动增益、单声道混合、重采样)。阶段可按配置重排、单独旁路，并分别统计处理耗时，
+//! 新的滤波器实现该 trait 即可加入处理链
+
+use super::agc::{AgcConfig, AutoGainControl};
+use super::capture::{AudioFrame, frames_to_ns};
+use super::denoise::NoiseSuppressor;
+use super::resampler::{BatchResampler, ResamplerConfig};
+use super::vad::{VadState, VoiceActivityDetector};
+use crate::error::AudioError;
+use crate::modules::config::{ChannelRouting, ProcessingStageKind, ProcessingStageSettings};
+use std::time::{Duration, Instant};
+
+/// 默认高通截止频率 (Hz)，滤除空调、桌面震动等低频噪声
+pub const DEFAULT_HIGH_PASS_HZ: f32 = 80.0;
+/// 去直流滤波器极点，越接近 1 截止频率越低 (0.995 @ 16kHz 约 13Hz)
+const DC_POLE: f32 = 0.995;
+/// 自适应选择通道时的能量平滑时间常数 (毫秒)
+const LOUDEST_SMOOTHING_MS: f32 = 200.0;
+/// 其他通道能量超过当前通道的倍数 (约 3dB) 时才切换，避免来回跳变
+const LOUDEST_SWITCH_RATIO: f32 = 2.0;
+/// 参与切换比较的最低能量 (约 -80dBFS)，静音时不因底噪切换
+const LOUDEST_MIN_ENERGY: f32 = 1e-8;
+
+/// 音频处理阶段
+///
+/// 输入输出均为交错排列的 [`AudioFrame`]，阶段可以改变通道数与采样率，
+/// 但需维护输出帧的时间戳
+pub trait AudioProcessor: Send {
+    /// 阶段名称，用于定位阶段与统计耗时
+    fn name(&self) -> &'static str;
+
+    /// 处理一帧音频
+    fn process(&mut self, frame: AudioFrame) -> Result<AudioFrame, AudioError>;
+
+    /// 取出内部缓冲的剩余数据，结束处理或输入格式变化时调用
+    fn flush(&mut self) -> Result<Option<AudioFrame>, AudioError> {
+        Ok(None)
+    }
+
+    /// 清空内部状态
+    fn reset(&mut self) {}
+
+    /// 是否只接受单声道输入
+    fn requires_mono(&self) -> bool {
+        false
+    }
+}
+
+/// 去直流
+///
+/// 一阶 IIR 直流阻断器 `y[n] = x[n] - x[n-1] + R·y[n-1]`，各通道独立
+#[derive(Debug, Default)]
+pub struct DcRemover {
+    /// 各通道的 (上一输入, 上一输出)
+    state: Vec<(f32, f32)>,
+}
+
+impl DcRemover {
+    pub const NAME: &'static str = "dc_removal";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AudioProcessor for DcRemover {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, mut frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        let channels = frame.channels.max(1) as usize;
+        if self.state.len() != channels {
+            self.state = vec![(0.0, 0.0); channels];
+        }
+        for (i, sample) in frame.samples.iter_mut().enumerate() {
+            let (x1, y1) = &mut self.state[i % channels];
+            let y = *sample - *x1 + DC_POLE * *y1;
+            *x1 = *sample;
+            *y1 = y;
+            *sample = y;
+        }
+        Ok(frame)
+    }
+
+    fn reset(&mut self) {
+        self.state.clear();
+    }
+}
+
+/// 二阶节系数 (已按 a0 归一化)
+#[derive(Debug, Clone, Copy)]
+struct BiquadCoefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// 高通滤波
+///
+/// 二阶 Butterworth 高通 (RBJ 双二阶)，采样率变化时重新计算系数，各通道独立
+#[derive(Debug)]
+pub struct HighPassFilter {
+    cutoff_hz: f32,
+    /// 系数对应的采样率
+    sample_rate: u32,
+    coefficients: Option<BiquadCoefficients>,
+    /// 各通道的 [x1, x2, y1, y2]
+    state: Vec<[f32; 4]>,
+}
+
+impl HighPassFilter {
+    pub const NAME: &'static str = "high_pass";
+
+    /// 创建高通滤波器
+    ///
+    /// # Arguments
+    /// * `cutoff_hz` - 截止频率 (-3dB 点)
+    pub fn new(cutoff_hz: f32) -> Self {
+        Self { cutoff_hz, sample_rate: 0, coefficients: None, state: Vec::new() }
+    }
+
+    /// 截止频率 (Hz)
+    pub fn cutoff_hz(&self) -> f32 {
+        self.cutoff_hz
+    }
+
+    fn design(cutoff_hz: f32, sample_rate: u32) -> BiquadCoefficients {
+        // 截止频率限制在奈奎斯特频率以内
+        let cutoff = cutoff_hz.clamp(1.0, sample_rate as f32 * 0.49);
+        let w0 = 2.0 * std::f32::consts::PI * cutoff / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / std::f32::consts::SQRT_2;
+        let a0 = 1.0 + alpha;
+        BiquadCoefficients {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+}
+
+impl AudioProcessor for HighPassFilter {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, mut frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        let channels = frame.channels.max(1) as usize;
+        if self.coefficients.is_none() || self.sample_rate != frame.sample_rate {
+            self.coefficients = Some(Self::design(self.cutoff_hz, frame.sample_rate.max(1)));
+            self.sample_rate = frame.sample_rate;
+            self.state.clear();
+        }
+        if self.state.len() != channels {
+            self.state = vec![[0.0; 4]; channels];
+        }
+        let c = self.coefficients.unwrap();
+        for (i, sample) in frame.samples.iter_mut().enumerate() {
+            let [x1, x2, y1, y2] = &mut self.state[i % channels];
+            let x = *sample;
+            let y = c.b0 * x + c.b1 * *x1 + c.b2 * *x2 - c.a1 * *y1 - c.a2 * *y2;
+            *x2 = *x1;
+            *x1 = x;
+            *y2 = *y1;
+            *y1 = y;
+            *sample = y;
+        }
+        Ok(frame)
+    }
+
+    fn reset(&mut self) {
+        self.state.clear();
+    }
+}
+
+/// 固定增益
+#[derive(Debug, Clone, Copy)]
+pub struct Gain {
+    gain_db: f32,
+    linear: f32,
+}
+
+impl Gain {
+    pub const NAME: &'static str = "gain";
+
+    pub fn new(gain_db: f32) -> Self {
+        Self { gain_db, linear: 10.0f32.powf(gain_db / 20.0) }
+    }
+
+    /// 增益 (dB)
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+}
+
+impl AudioProcessor for Gain {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, mut frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        frame.samples.iter_mut().for_each(|s| *s *= self.linear);
+        Ok(frame)
+    }
+}
+
+/// 降噪阶段
+///
+/// 包装 [`NoiseSuppressor`]，自带一个能量 VAD 判定静音段以更新噪声估计。
+/// 输出时间戳按降噪器延迟前移
+pub struct NoiseSuppression {
+    /// (采样率, 降噪器)，首帧或采样率变化时创建
+    suppressor: Option<(u32, NoiseSuppressor)>,
+    vad: VoiceActivityDetector,
+    /// 已输出样本之后下一个样本的采集时间
+    next_ts: u128,
+}
+
+impl NoiseSuppression {
+    pub const NAME: &'static str = "noise_suppression";
+
+    pub fn new() -> Self {
+        Self { suppressor: None, vad: VoiceActivityDetector::default(), next_ts: 0 }
+    }
+}
+
+impl Default for NoiseSuppression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioProcessor for NoiseSuppression {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        require_mono(Self::NAME, &frame)?;
+        let rate = frame.sample_rate;
+        let mut suppressor = match self.suppressor.take() {
+            Some((r, suppressor)) if r == rate => suppressor,
+            _ => NoiseSuppressor::new(rate),
+        };
+
+        let is_speech = self.vad.detect(&frame.samples) != VadState::Silence;
+        let latency_ns = frames_to_ns(suppressor.latency() as u64, rate);
+        let samples = suppressor.process(&frame.samples, is_speech);
+        self.suppressor = Some((rate, suppressor));
+        let output = AudioFrame::new(samples, rate, 1, frame.timestamp_ns.saturating_sub(latency_ns));
+        self.next_ts = output.end_timestamp_ns();
+        Ok(output)
+    }
+
+    fn flush(&mut self) -> Result<Option<AudioFrame>, AudioError> {
+        let Some((rate, mut suppressor)) = self.suppressor.take() else {
+            return Ok(None);
+        };
+        let samples = suppressor.flush();
+        Ok((!samples.is_empty()).then(|| AudioFrame::new(samples, rate, 1, self.next_ts)))
+    }
+
+    fn reset(&mut self) {
+        self.suppressor = None;
+        self.vad.reset();
+    }
+
+    fn requires_mono(&self) -> bool {
+        true
+    }
+}
+
+/// 自动增益阶段
+pub struct AutoGain {
+    config: AgcConfig,
+    /// (每秒样本数, 控制器)，首帧或格式变化时创建
+    agc: Option<(u32, AutoGainControl)>,
+}
+
+impl AutoGain {
+    pub const NAME: &'static str = "auto_gain";
+
+    pub fn new(config: AgcConfig) -> Self {
+        Self { config, agc: None }
+    }
+
+    /// 当前增益 (dB)，尚未处理音频时为 0
+    pub fn gain_db(&self) -> f32 {
+        self.agc.as_ref().map_or(0.0, |(_, agc)| agc.gain_db())
+    }
+}
+
+impl AudioProcessor for AutoGain {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, mut frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        // 交错多通道按总样本率换算帧时长，所有通道使用同一增益
+        let rate = frame.sample_rate * frame.channels.max(1) as u32;
+        if !matches!(self.agc, Some((r, _)) if r == rate) {
+            self.agc = Some((rate, AutoGainControl::new(self.config, rate)));
+        }
+        if let Some((_, agc)) = self.agc.as_mut() {
+            frame.samples = agc.process(&frame.samples);
+        }
+        Ok(frame)
+    }
+
+    fn reset(&mut self) {
+        self.agc = None;
+    }
+}
+
+/// 混合为单声道
+///
+/// 按 [`ChannelRouting`] 选择参与混合的通道，默认平均所有通道
+#[derive(Debug, Default, Clone)]
+pub struct Downmix {
+    routing: ChannelRouting,
+    /// 自适应模式下各通道的平滑能量
+    energy: Vec<f32>,
+    /// 自适应模式下当前使用的通道
+    selected: Option<u16>,
+}
+
+impl Downmix {
+    pub const NAME: &'static str = "downmix";
+
+    pub fn new(routing: ChannelRouting) -> Self {
+        Self { routing, energy: Vec::new(), selected: None }
+    }
+
+    /// 当前的通道路由
+    pub fn routing(&self) -> &ChannelRouting {
+        &self.routing
+    }
+
+    /// 自适应模式下当前使用的通道
+    pub fn selected_channel(&self) -> Option<u16> {
+        self.selected
+    }
+
+    /// 更新各通道能量并返回最响的通道，当前通道仍足够响时保持不变
+    fn select_loudest(&mut self, frame: &AudioFrame) -> u16 {
+        let energy = frame.channel_energy();
+        if self.energy.len() != energy.len() {
+            self.energy = energy;
+            self.selected = None;
+        } else {
+            let elapsed_ms = frame.duration_ns() as f32 / 1e6;
+            let alpha = 1.0 - (-elapsed_ms / LOUDEST_SMOOTHING_MS).exp();
+            for (smoothed, e) in self.energy.iter_mut().zip(energy) {
+                *smoothed += alpha * (e - *smoothed);
+            }
+        }
+
+        let loudest = self.energy
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(i, _)| i);
+        let selected = match self.selected {
+            Some(current) => {
+                let floor = self.energy[current as usize].max(LOUDEST_MIN_ENERGY);
+                if self.energy[loudest] > floor * LOUDEST_SWITCH_RATIO {
+                    loudest as u16
+                } else {
+                    current
+                }
+            }
+            None => loudest as u16,
+        };
+        self.selected = Some(selected);
+        selected
+    }
+}
+
+impl AudioProcessor for Downmix {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        if frame.channels == 1 {
+            return Ok(frame);
+        }
+        Ok(match &self.routing {
+            ChannelRouting::Average => frame.to_mono(),
+            ChannelRouting::Single { channel } => frame.channel(*channel),
+            ChannelRouting::Subset { channels } => frame.mix_channels(channels),
+            ChannelRouting::Loudest => {
+                let channel = self.select_loudest(&frame);
+                frame.channel(channel)
+            }
+        })
+    }
+
+    fn reset(&mut self) {
+        self.energy.clear();
+        self.selected = None;
+    }
+}
+
+/// 重采样阶段
+///
+/// 首帧或输入采样率变化时创建 [`BatchResampler`]；
+/// 采样率变化时旧重采样器的剩余数据与新数据合并为一帧输出
+pub struct Resample {
+    output_rate: u32,
+    /// 重采样质量与块大小
+    config: ResamplerConfig,
+    /// (输入采样率, 重采样器)
+    resampler: Option<(u32, BatchResampler)>,
+}
+
+impl Resample {
+    pub const NAME: &'static str = "resample";
+
+    pub fn new(output_rate: u32) -> Self {
+        Self::with_config(output_rate, ResamplerConfig::default())
+    }
+
+    /// 使用指定质量档位与块大小的重采样阶段
+    pub fn with_config(output_rate: u32, config: ResamplerConfig) -> Self {
+        Self { output_rate, config, resampler: None }
+    }
+
+    /// 输出采样率
+    pub fn output_rate(&self) -> u32 {
+        self.output_rate
+    }
+}
+
+impl AudioProcessor for Resample {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn process(&mut self, frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        require_mono(Self::NAME, &frame)?;
+        let tail = match self.resampler {
+            Some((rate, _)) if rate == frame.sample_rate => None,
+            _ => self.flush()?,
+        };
+        let mut resampler = match self.resampler.take() {
+            Some((_, resampler)) => resampler,
+            None => BatchResampler::with_config(frame.sample_rate, self.output_rate, self.config)?,
+        };
+        let output = resampler.process_frame(&frame);
+        self.resampler = Some((frame.sample_rate, resampler));
+        let mut output = output?;
+        if let Some(mut tail) = tail {
+            tail.samples.append(&mut output.samples);
+            output = tail;
+        }
+        Ok(output)
+    }
+
+    fn flush(&mut self) -> Result<Option<AudioFrame>, AudioError> {
+        // 刷新后丢弃重采样器，下一帧按其采样率重新创建
+        let Some((_, mut resampler)) = self.resampler.take() else {
+            return Ok(None);
+        };
+        let tail = resampler.flush_frame()?;
+        Ok((!tail.samples.is_empty()).then_some(tail))
+    }
+
+    fn reset(&mut self) {
+        self.resampler = None;
+    }
+
+    fn requires_mono(&self) -> bool {
+        true
+    }
+}
+
+fn require_mono(stage: &str, frame: &AudioFrame) -> Result<(), AudioError> {
+    if frame.channels != 1 {
+        return Err(AudioError::ConfigurationFailed(format!(
+            "{} stage requires mono input, got {} channels",
+            stage, frame.channels
+        )));
+    }
+    Ok(())
+}
+
+/// 阶段统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StageStats {
+    /// 阶段名称
+    pub name: &'static str,
+    /// 是否旁路
+    pub bypass: bool,
+    /// 处理次数 (旁路时不计)
+    pub calls: u64,
+    /// 累计处理耗时
+    pub total: Duration,
+    /// 单次最大耗时
+    pub max: Duration,
+    /// 累计处理的音频时长 (纳秒)
+    pub audio_ns: u128,
+}
+
+impl StageStats {
+    fn new(name: &'static str, bypass: bool) -> Self {
+        Self { name, bypass, calls: 0, total: Duration::ZERO, max: Duration::ZERO, audio_ns: 0 }
+    }
+
+    /// 平均单次耗时
+    pub fn average(&self) -> Duration {
+        if self.calls == 0 {
+            return Duration::ZERO;
+        }
+        self.total / self.calls as u32
+    }
+
+    /// CPU 负载：处理耗时占音频时长的比例，大于 1 表示跟不上实时
+    pub fn load(&self) -> f64 {
+        if self.audio_ns == 0 {
+            return 0.0;
+        }
+        self.total.as_nanos() as f64 / self.audio_ns as f64
+    }
+
+    fn record(&mut self, elapsed: Duration, audio_ns: u128) {
+        self.calls += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+        self.audio_ns += audio_ns;
+    }
+}
+
+/// 处理链中的一个阶段
+struct Stage {
+    processor: Box<dyn AudioProcessor>,
+    stats: StageStats,
+}
+
+/// 音频处理链
+///
+/// 按顺序执行各阶段，旁路的阶段保留位置与状态但不处理音频
+#[derive(Default)]
+pub struct ProcessingChain {
+    stages: Vec<Stage>,
+}
+
+impl ProcessingChain {
+    /// 创建空的处理链 (直接透传)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据配置构建处理链
+    ///
+    /// # Arguments
+    /// * `settings` - 按顺序排列的阶段配置
+    /// * `output_rate` - 重采样阶段的目标采样率
+    /// * `resampler` - 重采样阶段的质量档位与块大小
+    ///
+    /// 只接受单声道的阶段 (降噪、重采样) 之前必须有未旁路的单声道混合阶段
+    pub fn from_settings(
+        settings: &[ProcessingStageSettings],
+        output_rate: u32,
+        resampler: ResamplerConfig,
+    ) -> Result<Self, AudioError> {
+        let mut chain = Self::new();
+        let mut mono = false;
+        for stage in settings {
+            let processor: Box<dyn AudioProcessor> = match stage.kind {
+                ProcessingStageKind::DcRemoval => Box::new(DcRemover::new()),
+                ProcessingStageKind::HighPass => {
+                    Box::new(HighPassFilter::new(stage.cutoff_hz.unwrap_or(DEFAULT_HIGH_PASS_HZ)))
+                }
+                ProcessingStageKind::Gain => Box::new(Gain::new(stage.gain_db.unwrap_or(0.0))),
+                ProcessingStageKind::NoiseSuppression => Box::new(NoiseSuppression::new()),
+                ProcessingStageKind::AutoGain => Box::new(AutoGain::new(AgcConfig::default())),
+                ProcessingStageKind::Downmix => Box::new(Downmix::default()),
+                ProcessingStageKind::Resample => Box::new(Resample::with_config(output_rate, resampler)),
+            };
+            if !stage.bypass {
+                if processor.requires_mono() && !mono {
+                    return Err(AudioError::ConfigurationFailed(format!(
+                        "{} stage must come after {}",
+                        processor.name(),
+                        Downmix::NAME
+                    )));
+                }
+                mono |= stage.kind == ProcessingStageKind::Downmix;
+            }
+            chain.push(processor);
+            chain.set_bypass(chain.len() - 1, stage.bypass);
+        }
+        Ok(chain)
+    }
+
+    /// 默认阶段：单声道混合 → 重采样 → 自动增益
+    ///
+    /// # Arguments
+    /// * `auto_gain` - 是否启用自动增益 (关闭时旁路)
+    pub fn default_settings(auto_gain: bool) -> Vec<ProcessingStageSettings> {
+        vec![
+            ProcessingStageSettings::new(ProcessingStageKind::Downmix),
+            ProcessingStageSettings::new(ProcessingStageKind::Resample),
+            ProcessingStageSettings {
+                bypass: !auto_gain,
+                ..ProcessingStageSettings::new(ProcessingStageKind::AutoGain)
+            },
+        ]
+    }
+
+    /// 在末尾追加阶段
+    pub fn push(&mut self, processor: Box<dyn AudioProcessor>) {
+        let len = self.stages.len();
+        self.insert(len, processor);
+    }
+
+    /// 在指定位置插入阶段
+    pub fn insert(&mut self, index: usize, processor: Box<dyn AudioProcessor>) {
+        let stats = StageStats::new(processor.name(), false);
+        self.stages.insert(index.min(self.stages.len()), Stage { processor, stats });
+    }
+
+    /// 替换阶段，保留旁路状态，索引越界时返回 None
+    pub fn replace(
+        &mut self,
+        index: usize,
+        processor: Box<dyn AudioProcessor>,
+    ) -> Option<Box<dyn AudioProcessor>> {
+        let stage = self.stages.get_mut(index)?;
+        stage.stats = StageStats::new(processor.name(), stage.stats.bypass);
+        Some(std::mem::replace(&mut stage.processor, processor))
+    }
+
+    /// 移除阶段
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn AudioProcessor>> {
+        (index < self.stages.len()).then(|| self.stages.remove(index).processor)
+    }
+
+    /// 将阶段移动到新位置，索引越界时返回 false
+    pub fn move_stage(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.stages.len() || to >= self.stages.len() {
+            return false;
+        }
+        let stage = self.stages.remove(from);
+        self.stages.insert(to, stage);
+        true
+    }
+
+    /// 设置阶段旁路，索引越界时返回 false
+    pub fn set_bypass(&mut self, index: usize, bypass: bool) -> bool {
+        let Some(stage) = self.stages.get_mut(index) else {
+            return false;
+        };
+        stage.stats.bypass = bypass;
+        true
+    }
+
+    /// 第一个指定名称的阶段的位置
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|s| s.processor.name() == name)
+    }
+
+    /// 是否存在未旁路的指定阶段
+    pub fn is_active(&self, name: &str) -> bool {
+        self.stages.iter().any(|s| !s.stats.bypass && s.processor.name() == name)
+    }
+
+    /// 阶段名称 (按执行顺序)
+    pub fn names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.processor.name()).collect()
+    }
+
+    /// 阶段数
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// 是否没有任何阶段
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// 依次执行各阶段
+    pub fn process(&mut self, frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        self.stages.iter_mut().try_fold(frame, |frame, stage| stage.run(frame))
+    }
+
+    /// 刷新各阶段的缓冲数据
+    ///
+    /// 前序阶段刷新出的数据会经过后续阶段处理，返回按时间顺序排列的尾部帧
+    pub fn flush(&mut self) -> Result<Vec<AudioFrame>, AudioError> {
+        let mut tails = Vec::new();
+        for stage in &mut self.stages {
+            let mut next = tails
+                .into_iter()
+                .map(|frame| stage.run(frame))
+                .collect::<Result<Vec<_>, _>>()?;
+            if !stage.stats.bypass {
+                next.extend(stage.processor.flush()?);
+            }
+            tails = next;
+        }
+        Ok(tails)
+    }
+
+    /// 清空各阶段的内部状态
+    pub fn reset(&mut self) {
+        self.stages.iter_mut().for_each(|s| s.processor.reset());
+    }
+
+    /// 各阶段统计 (按执行顺序)
+    pub fn stats(&self) -> Vec<StageStats> {
+        self.stages.iter().map(|s| s.stats).collect()
+    }
+}
+
+impl Stage {
+    fn run(&mut self, frame: AudioFrame) -> Result<AudioFrame, AudioError> {
+        if self.stats.bypass {
+            return Ok(frame);
+        }
+        let audio_ns = frame.duration_ns();
+        let started = Instant::now();
+        let output = self.processor.process(frame)?;
+        self.stats.record(started.elapsed(), audio_ns);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::test_signals::tone;
+    use crate::modules::config::ResamplerQuality;
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn stage(kind: ProcessingStageKind) -> ProcessingStageSettings {
+        ProcessingStageSettings::new(kind)
+    }
+
+    #[test]
+    fn test_empty_chain_passthrough() {
+        let mut chain = ProcessingChain::new();
+        let output = chain.process(AudioFrame::new(vec![0.1, -0.2, 0.3], 16000, 1, 42)).unwrap();
+        assert_eq!(output.samples, vec![0.1, -0.2, 0.3]);
+        assert_eq!(output.timestamp_ns, 42);
+        assert!(chain.flush().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dc_removal() {
+        let mut chain = ProcessingChain::new();
+        chain.push(Box::new(DcRemover::new()));
+        let mut samples = tone(440.0, 0.3, 16000, 16000);
+        samples.iter_mut().for_each(|s| *s += 0.2);
+
+        let output = chain.process(AudioFrame::new(samples, 16000, 1, 0)).unwrap();
+        let tail = &output.samples[8000..];
+        let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+        assert!(mean.abs() < 0.005, "residual dc {}", mean);
+        assert!((rms(tail) - 0.3 / 2.0f32.sqrt()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_high_pass_attenuates_rumble() {
+        let mut filter = HighPassFilter::new(DEFAULT_HIGH_PASS_HZ);
+        let rumble = filter
+            .process(AudioFrame::new(tone(20.0, 0.5, 16000, 16000), 16000, 1, 0))
+            .unwrap();
+        filter.reset();
+        let voice = filter
+            .process(AudioFrame::new(tone(1000.0, 0.5, 16000, 16000), 16000, 1, 0))
+            .unwrap();
+
+        // 20Hz 衰减约 24dB，1kHz 基本不变
+        let input_rms = 0.5 / 2.0f32.sqrt();
+        assert!(rms(&rumble.samples[8000..]) < input_rms * 0.1);
+        assert!((rms(&voice.samples[8000..]) / input_rms - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_filters_keep_channels_independent() {
+        let mut filter = HighPassFilter::new(DEFAULT_HIGH_PASS_HZ);
+        // 左声道 1kHz，右声道静音
+        let samples: Vec<f32> = tone(1000.0, 0.5, 16000, 1600)
+            .into_iter()
+            .flat_map(|s| [s, 0.0])
+            .collect();
+        let output = filter.process(AudioFrame::new(samples, 16000, 2, 0)).unwrap();
+        assert!(output.samples.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_gain() {
+        let mut gain = Gain::new(6.0);
+        let output = gain.process(AudioFrame::new(vec![0.1, -0.1], 16000, 1, 0)).unwrap();
+        assert!((output.samples[0] - 0.1995).abs() < 1e-3);
+        assert!((output.samples[1] + 0.1995).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_chain_from_default_settings() {
+        let mut chain = ProcessingChain::from_settings(
+            &ProcessingChain::default_settings(false),
+            16000,
+            ResamplerConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(chain.names(), vec![Downmix::NAME, Resample::NAME, AutoGain::NAME]);
+        assert!(!chain.is_active(AutoGain::NAME));
+
+        // 100ms 立体声 48kHz → 单声道 16kHz
+        let mut total = 0;
+        let frame = chain.process(AudioFrame::new(vec![0.5; 9600], 48000, 2, 1_000)).unwrap();
+        assert_eq!((frame.channels, frame.sample_rate), (1, 16000));
+        total += frame.samples.len();
+        total += chain.flush().unwrap().iter().map(|f| f.samples.len()).sum::<usize>();
+        assert!((1280..=1760).contains(&total), "unexpected output length {}", total);
+    }
+
+    #[test]
+    fn test_chain_rejects_mono_stage_before_downmix() {
+        let settings = [stage(ProcessingStageKind::Resample), stage(ProcessingStageKind::Downmix)];
+        assert!(ProcessingChain::from_settings(&settings, 16000, ResamplerConfig::default()).is_err());
+
+        // 旁路的阶段不参与检查
+        let settings = [
+            ProcessingStageSettings { bypass: true, ..stage(ProcessingStageKind::NoiseSuppression) },
+            stage(ProcessingStageKind::Downmix),
+            stage(ProcessingStageKind::Resample),
+        ];
+        assert!(ProcessingChain::from_settings(&settings, 16000, ResamplerConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_chain_settings_parameters() {
+        let settings = [
+            ProcessingStageSettings { gain_db: Some(-6.0), ..stage(ProcessingStageKind::Gain) },
+            ProcessingStageSettings { cutoff_hz: Some(120.0), ..stage(ProcessingStageKind::HighPass) },
+            stage(ProcessingStageKind::DcRemoval),
+        ];
+        let mut chain = ProcessingChain::from_settings(&settings, 16000, ResamplerConfig::default()).unwrap();
+        assert_eq!(chain.names(), vec![Gain::NAME, HighPassFilter::NAME, DcRemover::NAME]);
+
+        let output = chain.process(AudioFrame::new(tone(1000.0, 0.5, 16000, 16000), 16000, 1, 0)).unwrap();
+        let expected = 0.5 / 2.0 / 2.0f32.sqrt();
+        assert!((rms(&output.samples[8000..]) / expected - 1.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn test_chain_reorder_and_bypass() {
+        let mut chain = ProcessingChain::new();
+        chain.push(Box::new(Gain::new(20.0)));
+        chain.push(Box::new(Downmix::default()));
+        assert!(chain.move_stage(0, 1));
+        assert_eq!(chain.names(), vec![Downmix::NAME, Gain::NAME]);
+        assert!(!chain.move_stage(0, 2));
+
+        let frame = AudioFrame::new(vec![0.01, 0.03], 16000, 2, 0);
+        let output = chain.process(frame.clone()).unwrap();
+        assert_eq!(output.channels, 1);
+        assert!((output.samples[0] - 0.2).abs() < 1e-5);
+
+        assert!(chain.set_bypass(chain.position(Gain::NAME).unwrap(), true));
+        let output = chain.process(frame).unwrap();
+        assert!((output.samples[0] - 0.02).abs() < 1e-6);
+
+        let stats = chain.stats();
+        assert_eq!(stats[1].calls, 1);
+        assert!(stats[1].bypass);
+        assert_eq!(stats[0].calls, 2);
+    }
+
+    /// 两通道 10ms 帧，左右声道分别为给定幅度的方波
+    fn stereo(left: f32, right: f32, index: usize) -> AudioFrame {
+        let samples = (0..160)
+            .flat_map(|n| if n % 2 == 0 { [left, right] } else { [-left, -right] })
+            .collect();
+        AudioFrame::new(samples, 16000, 2, frames_to_ns(index as u64 * 160, 16000))
+    }
+
+    #[test]
+    fn test_downmix_channel_routing() {
+        let frame = stereo(0.4, 0.0, 0);
+        let average = Downmix::default().process(frame.clone()).unwrap();
+        assert!((average.samples[0] - 0.2).abs() < 1e-6);
+
+        // 麦克风只接在第一路时单通道路由不损失电平
+        let single = Downmix::new(ChannelRouting::Single { channel: 0 }).process(frame.clone()).unwrap();
+        assert_eq!(single.channels, 1);
+        assert!((single.samples[0] - 0.4).abs() < 1e-6);
+
+        let subset = Downmix::new(ChannelRouting::Subset { channels: vec![1] }).process(frame).unwrap();
+        assert!(subset.samples.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_downmix_loudest_channel_with_hysteresis() {
+        let mut downmix = Downmix::new(ChannelRouting::Loudest);
+        let output = downmix.process(stereo(0.05, 0.3, 0)).unwrap();
+        assert_eq!(downmix.selected_channel(), Some(1));
+        assert!((output.samples[0] - 0.3).abs() < 1e-6);
+
+        // 略响 (小于 3dB) 的通道不触发切换
+        for i in 1..100 {
+            downmix.process(stereo(0.35, 0.3, i)).unwrap();
+        }
+        assert_eq!(downmix.selected_channel(), Some(1));
+
+        // 持续明显更响后切换
+        for i in 100..200 {
+            downmix.process(stereo(0.6, 0.1, i)).unwrap();
+        }
+        assert_eq!(downmix.selected_channel(), Some(0));
+
+        downmix.reset();
+        assert_eq!(downmix.selected_channel(), None);
+    }
+
+    #[test]
+    fn test_chain_replace_keeps_bypass() {
+        let mut chain = ProcessingChain::new();
+        chain.push(Box::new(Downmix::default()));
+        chain.set_bypass(0, true);
+        let old = chain.replace(0, Box::new(Downmix::new(ChannelRouting::Loudest)));
+        assert_eq!(old.map(|p| p.name()), Some(Downmix::NAME));
+        assert!(chain.stats()[0].bypass);
+        assert!(chain.replace(1, Box::new(Downmix::default())).is_none());
+    }
+
+    #[test]
+    fn test_chain_stats_track_audio_duration() {
+        let mut chain = ProcessingChain::from_settings(
+            &[stage(ProcessingStageKind::HighPass)],
+            16000,
+            ResamplerConfig::default(),
+        )
+        .unwrap();
+        for _ in 0..10 {
+            chain.process(AudioFrame::new(vec![0.1; 1600], 16000, 1, 0)).unwrap();
+        }
+        let stats = chain.stats()[0];
+        assert_eq!(stats.name, HighPassFilter::NAME);
+        assert_eq!(stats.calls, 10);
+        assert_eq!(stats.audio_ns, 1_000_000_000);
+        assert!(stats.max <= stats.total);
+        assert!(stats.load() < 1.0);
+    }
+
+    #[test]
+    fn test_resample_stage_switches_input_rate() {
+        let mut resample = Resample::new(16000);
+        let first = resample.process(AudioFrame::new(vec![0.5; 4800], 48000, 1, 0)).unwrap();
+
+        // 切换到 16kHz 时旧重采样器的剩余数据随新数据一起输出
+        let second = resample
+            .process(AudioFrame::new(vec![0.5; 1600], 16000, 1, 100_000_000))
+            .unwrap();
+        assert_eq!(second.timestamp_ns, first.end_timestamp_ns());
+        // 旧重采样器刷新后输出长度与输入时长一致
+        assert_eq!(first.samples.len() + second.samples.len(), 3200);
+        assert!(resample.process(AudioFrame::new(vec![0.5; 2], 48000, 2, 0)).is_err());
+    }
+
+    #[test]
+    fn test_chain_resample_stage_uses_quality() {
+        let settings = [stage(ProcessingStageKind::Downmix), stage(ProcessingStageKind::Resample)];
+        let config = ResamplerConfig { quality: ResamplerQuality::High, chunk_size: 480 };
+        let mut chain = ProcessingChain::from_settings(&settings, 16000, config).unwrap();
+
+        // 高于 8kHz 的成分被抗混叠低通滤除，不会折叠到语音频带
+        let output = chain.process(AudioFrame::new(tone(11000.0, 0.5, 48000, 4800), 48000, 1, 0)).unwrap();
+        assert_eq!(output.sample_rate, 16000);
+        assert!(rms(&output.samples[200..]) < 1e-3);
+    }
+
+    #[test]
+    fn test_noise_suppression_stage_flushes_latency() {
+        let mut chain = ProcessingChain::from_settings(
+            &[stage(ProcessingStageKind::Downmix), stage(ProcessingStageKind::NoiseSuppression)],
+            16000,
+            ResamplerConfig::default(),
+        )
+        .unwrap();
+        let output = chain.process(AudioFrame::new(vec![0.0; 3200], 16000, 1, 1_000_000_000)).unwrap();
+        assert_eq!(output.samples.len(), 3200);
+        assert!(output.timestamp_ns < 1_000_000_000);
+
+        let tail = chain.flush().unwrap();
+        assert_eq!(tail.len(), 1);
+        assert_eq!(tail[0].timestamp_ns, output.end_timestamp_ns());
+        // 尾部补齐延迟，结束时间与输入对齐
+        let end_ns = tail[0].end_timestamp_ns();
+        assert!(end_ns.abs_diff(1_200_000_000) < 1_000, "unexpected end {}ns", end_ns);
+    }
+}
@@ -0,0 +1,411 @@
+//! 会话录音
+//!
+//! 将采集到的原始音频和/或重采样后的音频写入 WAV 或 FLAC 文件，
+//! 录音期间使用临时文件名，会话结束后按 Scribe 会话 ID 重命名
+
+use super::capture::AudioFrame;
+use super::flac::FlacWriter;
+use crate::error::AudioError;
+use crate::modules::config::{RecordingFormat, RecordingSettings};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 录音配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecorderConfig {
+    /// 录音目录
+    pub directory: PathBuf,
+    /// 文件格式
+    pub format: RecordingFormat,
+    /// 录制原始音频
+    pub record_raw: bool,
+    /// 录制重采样后的音频
+    pub record_resampled: bool,
+}
+
+impl RecorderConfig {
+    /// 根据用户设置构建，未启用或未选择任何音轨时返回 None
+    pub fn from_settings(settings: &RecordingSettings, directory: PathBuf) -> Option<Self> {
+        if !settings.enabled || !(settings.raw || settings.resampled) {
+            return None;
+        }
+        Some(Self {
+            directory,
+            format: settings.format,
+            record_raw: settings.raw,
+            record_resampled: settings.resampled,
+        })
+    }
+}
+
+/// 底层写入器
+enum TrackWriter {
+    Wav(hound::WavWriter<BufWriter<File>>),
+    Flac(FlacWriter<BufWriter<File>>),
+}
+
+/// 单条音轨
+struct Track {
+    writer: TrackWriter,
+    path: PathBuf,
+    /// 最终文件名中的音轨标签 (如 `raw`、`16k`)
+    label: String,
+    sample_rate: u32,
+    channels: u16,
+}
+
+impl Track {
+    fn create(
+        format: RecordingFormat,
+        path: PathBuf,
+        label: String,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Result<Self, AudioError> {
+        let failed = |e: String| AudioError::RecordingFailed(format!("{}: {}", path.display(), e));
+        let writer = match format {
+            RecordingFormat::Wav => {
+                let spec = hound::WavSpec {
+                    channels,
+                    sample_rate,
+                    bits_per_sample: 16,
+                    sample_format: hound::SampleFormat::Int,
+                };
+                TrackWriter::Wav(hound::WavWriter::create(&path, spec).map_err(|e| failed(e.to_string()))?)
+            }
+            RecordingFormat::Flac => TrackWriter::Flac(
+                FlacWriter::create(&path, sample_rate, channels).map_err(|e| failed(e.to_string()))?,
+            ),
+        };
+        Ok(Self { writer, path, label, sample_rate, channels })
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        match &mut self.writer {
+            TrackWriter::Wav(writer) => {
+                for &sample in samples {
+                    let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+                    writer.write_sample(value).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            TrackWriter::Flac(writer) => writer.write_samples(samples).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn finalize(self) -> Result<(PathBuf, String), String> {
+        match self.writer {
+            TrackWriter::Wav(writer) => writer.finalize().map_err(|e| e.to_string())?,
+            TrackWriter::Flac(writer) => {
+                writer.finalize().map_err(|e| e.to_string())?;
+            }
+        }
+        Ok((self.path, self.label))
+    }
+}
+
+/// 会话录音器
+///
+/// 写入失败时记录日志并停止对应音轨，不影响听写会话
+pub struct SessionRecorder {
+    config: RecorderConfig,
+    /// 临时文件名前缀
+    stem: String,
+    raw: Option<Track>,
+    resampled: Option<Track>,
+    /// 原始音轨因格式变化 (如设备切换) 而分段的序号
+    raw_segment: u32,
+    /// 已完成的音轨 (临时路径, 标签)
+    finished: Vec<(PathBuf, String)>,
+}
+
+impl SessionRecorder {
+    /// 创建录音器，确保录音目录存在
+    pub fn new(config: RecorderConfig) -> Result<Self, AudioError> {
+        std::fs::create_dir_all(&config.directory).map_err(|e| {
+            AudioError::RecordingFailed(format!("{}: {}", config.directory.display(), e))
+        })?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        Ok(Self {
+            config,
+            stem: format!("session-{}", millis),
+            raw: None,
+            resampled: None,
+            raw_segment: 0,
+            finished: Vec::new(),
+        })
+    }
+
+    /// 写入采集到的原始音频
+    pub fn write_raw(&mut self, frame: &AudioFrame) {
+        if !self.config.record_raw || frame.samples.is_empty() {
+            return;
+        }
+
+        // 格式变化时结束当前分段并开始新分段
+        let format_changed = self
+            .raw
+            .as_ref()
+            .is_some_and(|t| t.sample_rate != frame.sample_rate || t.channels != frame.channels);
+        if format_changed {
+            let track = self.raw.take();
+            self.finish_track(track);
+        }
+
+        if self.raw.is_none() {
+            self.raw_segment += 1;
+            let label = match self.raw_segment {
+                1 => "raw".to_string(),
+                n => format!("raw-{}", n),
+            };
+            match self.open_track(&label, frame.sample_rate, frame.channels) {
+                Ok(track) => self.raw = Some(track),
+                Err(e) => {
+                    tracing::error!("Failed to start raw recording: {}", e);
+                    self.config.record_raw = false;
+                    return;
+                }
+            }
+        }
+
+        if let Some(track) = self.raw.as_mut()
+            && let Err(e) = track.write(&frame.samples)
+        {
+            tracing::error!("Failed to write raw recording: {}", e);
+            let track = self.raw.take();
+            self.finish_track(track);
+            self.config.record_raw = false;
+        }
+    }
+
+    /// 写入重采样后的单声道音频
+    pub fn write_resampled(&mut self, samples: &[f32], sample_rate: u32) {
+        if !self.config.record_resampled || samples.is_empty() {
+            return;
+        }
+
+        if self.resampled.is_none() {
+            let label = format!("{}k", sample_rate / 1000);
+            match self.open_track(&label, sample_rate, 1) {
+                Ok(track) => self.resampled = Some(track),
+                Err(e) => {
+                    tracing::error!("Failed to start resampled recording: {}", e);
+                    self.config.record_resampled = false;
+                    return;
+                }
+            }
+        }
+
+        if let Some(track) = self.resampled.as_mut()
+            && let Err(e) = track.write(samples)
+        {
+            tracing::error!("Failed to write resampled recording: {}", e);
+            let track = self.resampled.take();
+            self.finish_track(track);
+            self.config.record_resampled = false;
+        }
+    }
+
+    /// 结束录音，按会话 ID 重命名文件并返回最终路径
+    ///
+    /// 没有会话 ID 时保留带时间戳的名称
+    pub fn finish(mut self, session_id: Option<&str>) -> Vec<PathBuf> {
+        let raw = self.raw.take();
+        self.finish_track(raw);
+        let resampled = self.resampled.take();
+        self.finish_track(resampled);
+
+        let base = session_id
+            .map(sanitize_file_name)
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| self.stem.clone());
+        let extension = extension(self.config.format);
+
+        self.finished
+            .drain(..)
+            .map(|(temp, label)| {
+                let target = unique_path(&self.config.directory, &format!("{}-{}", base, label), extension);
+                match std::fs::rename(&temp, &target) {
+                    Ok(()) => {
+                        tracing::info!("Session recording saved: {}", target.display());
+                        target
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to rename recording {}: {}", temp.display(), e);
+                        temp
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn open_track(&self, label: &str, sample_rate: u32, channels: u16) -> Result<Track, AudioError> {
+        let path = self.config.directory.join(format!(
+            ".{}-{}.{}.part",
+            self.stem,
+            label,
+            extension(self.config.format)
+        ));
+        Track::create(self.config.format, path, label.to_string(), sample_rate, channels)
+    }
+
+    fn finish_track(&mut self, track: Option<Track>) {
+        let Some(track) = track else {
+            return;
+        };
+        match track.finalize() {
+            Ok(finished) => self.finished.push(finished),
+            Err(e) => tracing::error!("Failed to finalize recording: {}", e),
+        }
+    }
+}
+
+fn extension(format: RecordingFormat) -> &'static str {
+    match format {
+        RecordingFormat::Wav => "wav",
+        RecordingFormat::Flac => "flac",
+    }
+}
+
+/// 替换文件名中不安全的字符
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// 在目录中生成不与已有文件冲突的路径
+fn unique_path(directory: &Path, base: &str, extension: &str) -> PathBuf {
+    let mut path = directory.join(format!("{}.{}", base, extension));
+    let mut n = 2;
+    while path.exists() {
+        path = directory.join(format!("{}-{}.{}", base, n, extension));
+        n += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn config(dir: &Path, format: RecordingFormat) -> RecorderConfig {
+        RecorderConfig {
+            directory: dir.to_path_buf(),
+            format,
+            record_raw: true,
+            record_resampled: true,
+        }
+    }
+
+    fn file_names(paths: &[PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_recorder_config_from_settings() {
+        let dir = PathBuf::from("/tmp/recordings");
+        assert!(RecorderConfig::from_settings(&RecordingSettings::default(), dir.clone()).is_none());
+
+        let settings = RecordingSettings { enabled: true, ..Default::default() };
+        let config = RecorderConfig::from_settings(&settings, dir.clone()).unwrap();
+        assert!(!config.record_raw);
+        assert!(config.record_resampled);
+
+        let settings = RecordingSettings { enabled: true, raw: false, resampled: false, ..Default::default() };
+        assert!(RecorderConfig::from_settings(&settings, dir).is_none());
+    }
+
+    #[test]
+    fn test_records_wav_named_by_session_id() {
+        let dir = tempdir().unwrap();
+        let mut recorder = SessionRecorder::new(config(dir.path(), RecordingFormat::Wav)).unwrap();
+
+        recorder.write_raw(&AudioFrame::new(vec![0.5, -0.5, 0.25, -0.25], 48000, 2, 0));
+        recorder.write_resampled(&[0.5, 0.25, 0.0], 16000);
+        let paths = recorder.finish(Some("sess_abc"));
+
+        assert_eq!(file_names(&paths), vec!["sess_abc-raw.wav", "sess_abc-16k.wav"]);
+
+        let mut raw = hound::WavReader::open(&paths[0]).unwrap();
+        assert_eq!(raw.spec().sample_rate, 48000);
+        assert_eq!(raw.spec().channels, 2);
+        let samples: Vec<i16> = raw.samples::<i16>().map(Result::unwrap).collect();
+        assert_eq!(samples, vec![16384, -16384, 8192, -8192]);
+
+        let resampled = hound::WavReader::open(&paths[1]).unwrap();
+        assert_eq!(resampled.spec().sample_rate, 16000);
+        assert_eq!(resampled.spec().channels, 1);
+        assert_eq!(resampled.len(), 3);
+
+        // 临时文件已清理
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_records_flac() {
+        let dir = tempdir().unwrap();
+        let mut config = config(dir.path(), RecordingFormat::Flac);
+        config.record_raw = false;
+        let mut recorder = SessionRecorder::new(config).unwrap();
+
+        let samples: Vec<f32> = (0..5000).map(|i| (i as f32 * 0.01).sin() * 0.5).collect();
+        recorder.write_raw(&AudioFrame::new(vec![0.1; 100], 48000, 1, 0));
+        recorder.write_resampled(&samples, 16000);
+        let paths = recorder.finish(Some("sess_flac"));
+        assert_eq!(file_names(&paths), vec!["sess_flac-16k.flac"]);
+
+        let mut reader = claxon::FlacReader::open(&paths[0]).unwrap();
+        assert_eq!(reader.streaminfo().sample_rate, 16000);
+        assert_eq!(reader.samples().count(), 5000);
+    }
+
+    #[test]
+    fn test_raw_track_splits_on_format_change() {
+        let dir = tempdir().unwrap();
+        let mut config = config(dir.path(), RecordingFormat::Wav);
+        config.record_resampled = false;
+        let mut recorder = SessionRecorder::new(config).unwrap();
+
+        recorder.write_raw(&AudioFrame::new(vec![0.1; 480], 48000, 1, 0));
+        recorder.write_raw(&AudioFrame::new(vec![0.1; 441], 44100, 1, 0));
+        let paths = recorder.finish(Some("sess"));
+        assert_eq!(file_names(&paths), vec!["sess-raw.wav", "sess-raw-2.wav"]);
+        assert_eq!(hound::WavReader::open(&paths[1]).unwrap().spec().sample_rate, 44100);
+    }
+
+    #[test]
+    fn test_names_without_session_id_and_collisions() {
+        let dir = tempdir().unwrap();
+        let mut recorder = SessionRecorder::new(config(dir.path(), RecordingFormat::Wav)).unwrap();
+        recorder.write_resampled(&[0.0; 10], 16000);
+        let paths = recorder.finish(None);
+        let name = &file_names(&paths)[0];
+        assert!(name.starts_with("session-") && name.ends_with("-16k.wav"), "{}", name);
+
+        // 同一会话 ID 多次听写不会覆盖
+        for expected in ["a_b-16k.wav", "a_b-16k-2.wav"] {
+            let mut recorder = SessionRecorder::new(config(dir.path(), RecordingFormat::Wav)).unwrap();
+            recorder.write_resampled(&[0.0; 10], 16000);
+            let paths = recorder.finish(Some("a/b"));
+            assert_eq!(file_names(&paths), vec![expected]);
+        }
+    }
+
+    #[test]
+    fn test_empty_recording_creates_no_files() {
+        let dir = tempdir().unwrap();
+        let recorder = SessionRecorder::new(config(dir.path(), RecordingFormat::Wav)).unwrap();
+        assert!(recorder.finish(Some("sess")).is_empty());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -2,26 +2,144 @@
 //!
 //! 使用 rubato 库实现高质量音频重采样
 
+use super::capture::{AudioFrame, frames_to_ns};
 use crate::error::AudioError;
-use rubato::{Resampler, FastFixedIn, PolynomialDegree};
+use crate::modules::config::ResamplerSettings;
+use rubato::{
+    FastFixedIn, PolynomialDegree, ResampleError, Resampler, SincFixedIn, SincInterpolationParameters,
+    SincInterpolationType, WindowFunction, calculate_cutoff,
+};
 use std::error::Error;
 
+pub use crate::modules::config::ResamplerQuality;
+
+/// 默认单次处理的输入帧数
+pub const DEFAULT_CHUNK_SIZE: usize = 128;
+
+/// 重采样器配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResamplerConfig {
+    /// 质量档位
+    pub quality: ResamplerQuality,
+    /// 单次处理的输入帧数
+    pub chunk_size: usize,
+}
+
+impl Default for ResamplerConfig {
+    fn default() -> Self {
+        Self {
+            quality: ResamplerQuality::Balanced,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl ResamplerConfig {
+    /// 根据用户设置构建，块大小至少为 1
+    pub fn from_settings(settings: &ResamplerSettings) -> Self {
+        Self {
+            quality: settings.quality,
+            chunk_size: (settings.chunk_size as usize).max(1),
+        }
+    }
+}
+
+/// sinc 插值参数
+///
+/// 截止频率按窗函数的过渡带计算，使过渡带止于较低一侧的奈奎斯特频率，
+/// 降采样时高于输出奈奎斯特频率的成分不会混叠到语音频带
+fn sinc_parameters(quality: ResamplerQuality) -> SincInterpolationParameters {
+    let (sinc_len, oversampling_factor, interpolation, window) = match quality {
+        ResamplerQuality::High => {
+            (256, 256, SincInterpolationType::Cubic, WindowFunction::BlackmanHarris2)
+        }
+        _ => (128, 128, SincInterpolationType::Linear, WindowFunction::Blackman2),
+    };
+    SincInterpolationParameters {
+        sinc_len,
+        f_cutoff: calculate_cutoff(sinc_len, window),
+        oversampling_factor,
+        interpolation,
+        window,
+    }
+}
+
+/// 按质量档位选用的 rubato 重采样器
+///
+/// 用枚举而非 trait 对象，以便直接处理借用的输入切片
+enum Interpolator {
+    /// 多项式插值
+    Fast(FastFixedIn<f32>),
+    /// sinc 插值
+    Sinc(SincFixedIn<f32>),
+}
+
+impl Interpolator {
+    /// 处理一块单声道输入
+    fn process(&mut self, input: &[f32]) -> Result<Vec<f32>, ResampleError> {
+        let mut channels = match self {
+            Self::Fast(resampler) => resampler.process(&[input], None)?,
+            Self::Sinc(resampler) => resampler.process(&[input], None)?,
+        };
+        Ok(channels.pop().unwrap_or_default())
+    }
+
+    /// rubato 报告的滤波器延迟 (输出样本数，向下取整)
+    fn output_delay(&self) -> usize {
+        match self {
+            Self::Fast(resampler) => resampler.output_delay(),
+            Self::Sinc(resampler) => resampler.output_delay(),
+        }
+    }
+
+    /// 清空内部缓冲，回到初始状态
+    fn reset(&mut self) {
+        match self {
+            Self::Fast(resampler) => resampler.reset(),
+            Self::Sinc(resampler) => resampler.reset(),
+        }
+    }
+
+    /// 实测输出相对输入的偏移 (输入样本数)
+    ///
+    /// `output_delay()` 按输出样本取整，且 sinc 插值报告的是输出滞后而非样本偏移，
+    /// 不足以逐样本对齐。因此送入斜坡信号，在越过开头零填充影响的
+    /// `output_delay()` 个输出样本之后读出偏移，测完重置
+    fn measure_delay(&mut self, ratio: f64, chunk_size: usize) -> Result<usize, ResampleError> {
+        let probe = self.output_delay() + 2;
+        let mut ramp = (0u32..).map(|i| i as f32);
+        let mut output = Vec::new();
+        while output.len() <= probe {
+            let chunk: Vec<f32> = ramp.by_ref().take(chunk_size).collect();
+            output.extend(self.process(&chunk)?);
+        }
+        self.reset();
+        let delay = (probe + 1) as f64 / ratio - output[probe] as f64;
+        Ok(delay.round().max(0.0) as usize)
+    }
+}
+
 /// 音频重采样器
 ///
-/// 使用多项式插值实现轻量级重采样
+/// 按质量档位使用多项式插值或 sinc 插值，单声道、固定比例
 pub struct AudioResampler {
     /// 内部重采样器
-    resampler: Option<FastFixedIn<f32>>,
+    resampler: Option<Interpolator>,
     /// 输入采样率
     input_rate: u32,
     /// 输出采样率
     output_rate: u32,
     /// 单次处理的输入帧数
     chunk_size: usize,
+    /// 质量档位
+    quality: ResamplerQuality,
+    /// 输出相对输入的偏移 (输入样本数)：第 k 个输出样本对应输入位置
+    /// `(k + 1) / 比例 - delay`
+    delay: usize,
 }
 
 impl AudioResampler {
-    /// 创建新的重采样器
+    /// 创建新的重采样器 (默认质量档位)
     ///
     /// # Arguments
     /// * `input_rate` - 输入采样率
@@ -30,29 +148,54 @@ impl AudioResampler {
     /// # Returns
     /// 新的重采样器实例
     pub fn new(input_rate: u32, output_rate: u32) -> Result<Self, AudioError> {
+        Self::with_config(input_rate, output_rate, ResamplerConfig::default())
+    }
+
+    /// 按指定质量档位与块大小创建重采样器
+    ///
+    /// # Arguments
+    /// * `input_rate` - 输入采样率
+    /// * `output_rate` - 输出采样率
+    /// * `config` - 质量档位与块大小
+    pub fn with_config(
+        input_rate: u32,
+        output_rate: u32,
+        config: ResamplerConfig,
+    ) -> Result<Self, AudioError> {
         if input_rate == output_rate {
             return Ok(Self {
                 resampler: None,
                 input_rate,
                 output_rate,
                 chunk_size: 0,
+                quality: config.quality,
+                delay: 0,
             });
         }
 
-        // 使用三次多项式插值
-        let resampler = FastFixedIn::new(
-            input_rate as f64,
-            output_rate as f64,
-            PolynomialDegree::Cubic,
-            128,  // chunk_size
-            128,  // output_chunk_size
-        ).map_err(|e| AudioError::ResamplingFailed(e.to_string()))?;
+        let ratio = output_rate as f64 / input_rate as f64;
+        let chunk_size = config.chunk_size.max(1);
+        let mut resampler = match config.quality {
+            ResamplerQuality::Fast => Interpolator::Fast(
+                FastFixedIn::new(ratio, 1.0, PolynomialDegree::Cubic, chunk_size, 1)
+                    .map_err(|e| AudioError::ResamplingFailed(e.to_string()))?,
+            ),
+            quality => Interpolator::Sinc(
+                SincFixedIn::new(ratio, 1.0, sinc_parameters(quality), chunk_size, 1)
+                    .map_err(|e| AudioError::ResamplingFailed(e.to_string()))?,
+            ),
+        };
+        let delay = resampler
+            .measure_delay(ratio, chunk_size)
+            .map_err(|e| AudioError::ResamplingFailed(e.to_string()))?;
 
         Ok(Self {
             resampler: Some(resampler),
             input_rate,
             output_rate,
-            chunk_size: 128,
+            chunk_size,
+            quality: config.quality,
+            delay,
         })
     }
 
@@ -69,27 +212,13 @@ impl AudioResampler {
     /// # Returns
     /// 重采样后的音频数据
     pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>, AudioError> {
-        if self.resampler.is_none() {
+        match self.resampler.as_mut() {
             // 无需重采样，直接返回
-            return Ok(input.to_vec());
-        }
-
-        let resampler = self.resampler.as_mut().unwrap();
-        let mut output = Vec::new();
-
-        // rubato expects &[V] where V: AsRef<[T]>, so we wrap input in a vec
-        let input_vec: Vec<&[f32]> = vec![input];
-
-        // process returns Result<Vec<Vec<T>, ResampleError>
-        let result: Result<Vec<Vec<f32>>, _> = resampler.process(&input_vec, None);
-        let chunks = result.map_err(|e| AudioError::ResamplingFailed(e.to_string()))?;
-
-        // Flatten the result (for mono audio, chunks has one channel)
-        for chunk in chunks {
-            output.extend(chunk);
+            None => Ok(input.to_vec()),
+            Some(resampler) => {
+                resampler.process(input).map_err(|e| AudioError::ResamplingFailed(e.to_string()))
+            }
         }
-
-        Ok(output)
     }
 
     /// 获取输入采样率
@@ -106,64 +235,198 @@ impl AudioResampler {
     pub fn needs_resampling(&self) -> bool {
         self.input_rate != self.output_rate
     }
+
+    /// 质量档位
+    pub fn quality(&self) -> ResamplerQuality {
+        self.quality
+    }
+
+    /// 单次处理的输入帧数，无需重采样时为 0
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
 }
 
 /// 批量重采样器
 ///
-/// 用于处理大量音频数据的分块重采样
+/// 用于处理大量音频数据的分块重采样，并把输入帧的时间戳换算到输出样本。
+/// 输出已补偿插值滤波器的延迟：第 k 个输出样本对应输入时间 k / 输出采样率，
+/// 刷新后输出总长度为输入长度 × 采样率比例 (四舍五入)
 pub struct BatchResampler {
     resampler: AudioResampler,
     buffer: Vec<f32>,
+    /// 累计输入样本数
+    input_samples: u64,
+    /// 累计输出样本数
+    output_samples: u64,
+    /// 最近一帧输入的时间锚点 (输入样本位置, 采集时间)
+    anchor: Option<(u64, u128)>,
+    /// 开头补的静音样本数，使输出偏移为整数个输出样本
+    priming: usize,
+    /// 开头需丢弃的输出样本数
+    trim: usize,
+    /// 开头尚未丢弃的输出样本数
+    pending_trim: usize,
 }
 
 impl BatchResampler {
-    /// 创建新的批量重采样器
+    /// 创建新的批量重采样器 (默认质量档位)
     pub fn new(input_rate: u32, output_rate: u32) -> Result<Self, AudioError> {
-        let resampler = AudioResampler::new(input_rate, output_rate)?;
+        Self::with_config(input_rate, output_rate, ResamplerConfig::default())
+    }
+
+    /// 按指定质量档位与块大小创建批量重采样器
+    pub fn with_config(
+        input_rate: u32,
+        output_rate: u32,
+        config: ResamplerConfig,
+    ) -> Result<Self, AudioError> {
+        let resampler = AudioResampler::with_config(input_rate, output_rate, config)?;
+        // 输出相对输入的偏移按输入样本计，通常不是整数个输出样本；开头补静音把偏移
+        // 凑到输入、输出样本的公共时间格点上，丢弃对应的输出样本即可精确对齐
+        let (priming, trim) = if resampler.needs_resampling() {
+            let divisor = gcd(input_rate, output_rate);
+            let step = (input_rate / divisor) as usize;
+            let aligned = resampler.delay.div_ceil(step) * step;
+            (aligned - resampler.delay, aligned / step * (output_rate / divisor) as usize - 1)
+        } else {
+            (0, 0)
+        };
         Ok(Self {
             resampler,
-            buffer: Vec::new(),
+            buffer: vec![0.0; priming],
+            input_samples: 0,
+            output_samples: 0,
+            anchor: None,
+            priming,
+            trim,
+            pending_trim: trim,
         })
     }
 
+    /// 插值滤波器带来的延迟 (输出样本数)
+    ///
+    /// rubato 报告的滤波器延迟加上实测的样本偏移，即输入样本要等其后这么长的输入到达后
+    /// 才能输出，不含凑满一块前留在缓冲区中的数据
+    pub fn latency_frames(&self) -> usize {
+        let resampler = &self.resampler;
+        let Some(interpolator) = &resampler.resampler else {
+            return 0;
+        };
+        let offset = (resampler.delay as u64 * resampler.output_rate as u64)
+            .div_ceil(resampler.input_rate as u64) as usize;
+        interpolator.output_delay() + offset
+    }
+
+    /// 插值滤波器带来的延迟 (纳秒)
+    pub fn latency_ns(&self) -> u128 {
+        frames_to_ns(self.latency_frames() as u64, self.resampler.output_rate)
+    }
+
+    /// 重采样一帧单声道音频
+    ///
+    /// 输出帧的时间戳为其首个样本对应的采集时间
+    pub fn process_frame(&mut self, frame: &AudioFrame) -> Result<AudioFrame, AudioError> {
+        self.anchor = Some((self.input_samples, frame.timestamp_ns));
+        let timestamp_ns = self.output_timestamp_ns();
+        let samples = self.process(&frame.samples)?;
+        Ok(AudioFrame::new(samples, self.resampler.output_rate, 1, timestamp_ns))
+    }
+
+    /// 刷新缓冲区，剩余数据作为一帧返回
+    pub fn flush_frame(&mut self) -> Result<AudioFrame, AudioError> {
+        let timestamp_ns = self.output_timestamp_ns();
+        let samples = self.flush()?;
+        Ok(AudioFrame::new(samples, self.resampler.output_rate, 1, timestamp_ns))
+    }
+
+    /// 下一个输出样本对应的采集时间 (纳秒)，尚无输入帧时为 0
+    ///
+    /// 以最近一帧输入为锚点按采样率换算，输入中的时间跳变 (如丢帧) 会反映到输出
+    pub fn output_timestamp_ns(&self) -> u128 {
+        let Some((anchor_pos, anchor_ns)) = self.anchor else {
+            return 0;
+        };
+        let output_ns = frames_to_ns(self.output_samples, self.resampler.output_rate) as i128;
+        let anchor_offset_ns = frames_to_ns(anchor_pos, self.resampler.input_rate) as i128;
+        (anchor_ns as i128 + output_ns - anchor_offset_ns).max(0) as u128
+    }
+
     /// 添加数据并处理
     ///
     /// 当缓冲区积累到足够数据时进行处理
     pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>, AudioError> {
+        self.input_samples += input.len() as u64;
+
+        // 采样率相同时无需分块，直接透传
+        if !self.resampler.needs_resampling() {
+            self.output_samples += input.len() as u64;
+            return Ok(input.to_vec());
+        }
+
         self.buffer.extend_from_slice(input);
 
         let chunk_size = self.resampler.chunk_size;
         let mut output = Vec::new();
 
         while self.buffer.len() >= chunk_size {
-            let chunk = self.buffer[..chunk_size].to_vec();
-            let processed = self.resampler.process(&chunk)?;
-            output.extend_from_slice(&processed);
+            let processed = self.resampler.process(&self.buffer[..chunk_size])?;
+            self.append_compensated(&mut output, &processed);
 
             self.buffer.drain(..chunk_size);
         }
 
+        self.output_samples += output.len() as u64;
         Ok(output)
     }
 
     /// 刷新缓冲区，获取剩余数据
+    ///
+    /// 以静音推出滤波器中的剩余样本，并截去超出输入长度对应的部分；
+    /// 刷新后重采样器回到初始状态，可继续处理新的数据
     pub fn flush(&mut self) -> Result<Vec<f32>, AudioError> {
-        let mut output = Vec::new();
+        if !self.resampler.needs_resampling() {
+            return Ok(Vec::new());
+        }
 
-        if !self.buffer.is_empty() {
-            // 对最后一块进行零填充
-            let chunk_size = self.resampler.chunk_size;
-            let mut chunk = self.buffer.clone();
-            chunk.resize(chunk_size, 0.0);
+        let input_rate = self.resampler.input_rate as u64;
+        let output_rate = self.resampler.output_rate as u64;
+        let expected = (self.input_samples * output_rate + input_rate / 2) / input_rate;
+        let remaining = expected.saturating_sub(self.output_samples) as usize;
 
+        let chunk_size = self.resampler.chunk_size;
+        let mut output = Vec::new();
+        while output.len() < remaining {
+            let mut chunk = std::mem::take(&mut self.buffer);
+            chunk.resize(chunk_size, 0.0);
             let processed = self.resampler.process(&chunk)?;
-            output.extend_from_slice(&processed);
-
-            self.buffer.clear();
+            if processed.is_empty() {
+                break;
+            }
+            self.append_compensated(&mut output, &processed);
         }
+        output.truncate(remaining);
+        self.output_samples += output.len() as u64;
 
+        if let Some(interpolator) = self.resampler.resampler.as_mut() {
+            interpolator.reset();
+        }
+        self.buffer = vec![0.0; self.priming];
+        self.pending_trim = self.trim;
         Ok(output)
     }
+
+    /// 丢弃开头的偏移样本后追加到输出
+    fn append_compensated(&mut self, output: &mut Vec<f32>, processed: &[f32]) {
+        let skip = self.pending_trim.min(processed.len());
+        self.pending_trim -= skip;
+        output.extend_from_slice(&processed[skip..]);
+    }
+}
+
+/// 最大公约数
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
 }
 
 impl Default for AudioResampler {
@@ -172,7 +435,9 @@ impl Default for AudioResampler {
             resampler: None,
             input_rate: 48000,
             output_rate: 16000,
-            chunk_size: 128,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            quality: ResamplerQuality::Balanced,
+            delay: 0,
         })
     }
 }
@@ -202,4 +467,239 @@ mod tests {
         let resampler = AudioResampler::new(48000, 48000).unwrap();
         assert!(!resampler.needs_resampling());
     }
+
+    #[test]
+    fn test_resample_48k_to_16k_length() {
+        let mut resampler = AudioResampler::create_48k_to_16k().unwrap();
+        let mut total = 0;
+        for _ in 0..30 {
+            total += resampler.process(&vec![0.5; 128]).unwrap().len();
+        }
+        // 3840 帧 @ 48kHz = 1280 帧 @ 16kHz (开头少了 sinc 滤波器延迟的约 21 帧)
+        assert!((1250..=1280).contains(&total), "unexpected length {}", total);
+    }
+
+    #[test]
+    fn test_batch_passthrough_same_rates() {
+        let mut resampler = BatchResampler::new(16000, 16000).unwrap();
+        let input = vec![0.1; 500];
+        assert_eq!(resampler.process(&input).unwrap(), input);
+        assert!(resampler.flush().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_batch_frame_timestamps_passthrough() {
+        let mut resampler = BatchResampler::new(16000, 16000).unwrap();
+        assert_eq!(resampler.output_timestamp_ns(), 0);
+
+        let first = resampler.process_frame(&AudioFrame::new(vec![0.1; 1600], 16000, 1, 5_000)).unwrap();
+        assert_eq!(first.timestamp_ns, 5_000);
+        // 输入出现 50ms 空洞时，输出时间戳随之跳变
+        let second = resampler
+            .process_frame(&AudioFrame::new(vec![0.1; 1600], 16000, 1, 150_005_000))
+            .unwrap();
+        assert_eq!(second.timestamp_ns, 150_005_000);
+    }
+
+    #[test]
+    fn test_batch_frame_timestamps_resampled() {
+        let mut resampler = BatchResampler::new(48000, 16000).unwrap();
+        let start_ns = 1_000_000_000u128;
+        let mut expected = start_ns;
+        for _ in 0..5 {
+            let output = resampler
+                .process_frame(&AudioFrame::new(vec![0.5; 4800], 48000, 1, expected))
+                .unwrap();
+            assert_eq!(output.sample_rate, 16000);
+            // 未凑满一块的输入留在缓冲区，输出最多滞后一块 (128 / 48kHz)
+            let lag = expected as i128 - output.timestamp_ns as i128;
+            assert!((0..3_000_000).contains(&lag.abs()), "unexpected lag {}ns", lag);
+            expected += 100_000_000;
+        }
+        let tail = resampler.flush_frame().unwrap();
+        assert!(tail.timestamp_ns < expected && tail.timestamp_ns > expected - 3_000_000);
+    }
+
+    /// 以 `chunk` 为单位送入 48kHz → 16kHz 重采样器，返回全部输出
+    fn resample_all(config: ResamplerConfig, input: &[f32], chunk: usize) -> Vec<f32> {
+        let mut resampler = BatchResampler::with_config(48000, 16000, config).unwrap();
+        let mut output = Vec::new();
+        for block in input.chunks(chunk) {
+            output.extend(resampler.process(block).unwrap());
+        }
+        output.extend(resampler.flush().unwrap());
+        output
+    }
+
+    /// `freq` 附近以外 (50Hz ~ 0.975 × 奈奎斯特频率) 的能量相对 `freq` 处能量 (dB)
+    fn spurious_db(samples: &[f32], sample_rate: u32, freq: f32) -> f32 {
+        use realfft::RealFftPlanner;
+        use std::f32::consts::PI;
+
+        let n = samples.len();
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(n);
+        let mut input: Vec<f32> = samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s * (0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos()))
+            .collect();
+        let mut spectrum = fft.make_output_vec();
+        fft.process(&mut input, &mut spectrum).unwrap();
+
+        let bin_hz = sample_rate as f32 / n as f32;
+        let (mut signal, mut spurious) = (0.0f64, 0.0f64);
+        for (i, bin) in spectrum.iter().enumerate() {
+            let hz = i as f32 * bin_hz;
+            let power = bin.norm_sqr() as f64;
+            if (hz - freq).abs() <= 30.0 {
+                signal += power;
+            } else if hz >= 50.0 && hz <= sample_rate as f32 * 0.4875 {
+                spurious += power;
+            }
+        }
+        10.0 * (spurious / signal).log10() as f32
+    }
+
+    #[test]
+    fn test_resampler_config_from_settings() {
+        let config = ResamplerConfig::from_settings(&ResamplerSettings::default());
+        assert_eq!(config, ResamplerConfig::default());
+        let config = ResamplerConfig::from_settings(&ResamplerSettings {
+            quality: ResamplerQuality::High,
+            chunk_size: 0,
+        });
+        assert_eq!(config.quality, ResamplerQuality::High);
+        assert_eq!(config.chunk_size, 1);
+    }
+
+    #[test]
+    fn test_sinc_tiers_chunk_size() {
+        for quality in [ResamplerQuality::Balanced, ResamplerQuality::High] {
+            let config = ResamplerConfig { quality, chunk_size: 480 };
+            let resampler = AudioResampler::with_config(48000, 16000, config).unwrap();
+            assert_eq!(resampler.quality(), quality);
+            assert_eq!(resampler.chunk_size(), 480);
+
+            let output = resample_all(config, &vec![0.5; 48000], 1000);
+            assert!((15900..=16200).contains(&output.len()), "unexpected length {}", output.len());
+            // 直流经过 sinc 低通后幅度不变
+            let settled = &output[1000..15000];
+            assert!(settled.iter().all(|s| (s - 0.5).abs() < 1e-3), "{:?} ripple", quality);
+        }
+    }
+
+    #[test]
+    fn test_downsample_aliasing_per_quality() {
+        // 1kHz 语音频带信号叠加 11kHz 干扰，48kHz → 16kHz 后 11kHz 会折叠到 5kHz
+        // 相位用 f64 计算，避免 f32 相位误差本身产生杂散
+        let input: Vec<f32> = (0..48000)
+            .map(|i| {
+                let t = i as f64 / 48000.0;
+                let tone = |freq: f64| 0.5 * (2.0 * std::f64::consts::PI * freq * t).sin();
+                (tone(1000.0) + tone(11000.0)) as f32
+            })
+            .collect();
+        let measure = |quality| {
+            let output = resample_all(ResamplerConfig { quality, chunk_size: 128 }, &input, 480);
+            spurious_db(&output[2048..2048 + 8192], 16000, 1000.0)
+        };
+
+        let fast = measure(ResamplerQuality::Fast);
+        let balanced = measure(ResamplerQuality::Balanced);
+        let high = measure(ResamplerQuality::High);
+        assert!(fast > -30.0, "fast tier spurious {} dB", fast);
+        assert!(balanced < -90.0, "balanced tier spurious {} dB", balanced);
+        assert!(high < -100.0, "high tier spurious {} dB", high);
+    }
+
+    /// 0.5 幅度的线性扫频信号在第 `i` 个样本处的值，频率在 `duration` 秒内从 100Hz 升到 `end_hz`
+    fn sweep(i: usize, sample_rate: u32, end_hz: f64, duration: f64) -> f32 {
+        let t = i as f64 / sample_rate as f64;
+        let phase = 100.0 * t + (end_hz - 100.0) * t * t / (2.0 * duration);
+        (0.5 * (2.0 * std::f64::consts::PI * phase).sin()) as f32
+    }
+
+    #[test]
+    fn test_batch_reports_latency() {
+        let config = ResamplerConfig { quality: ResamplerQuality::Fast, ..Default::default() };
+        let fast = BatchResampler::with_config(48000, 16000, config).unwrap();
+        assert_eq!(fast.latency_frames(), 3);
+        assert_eq!(fast.latency_ns(), 187_500);
+        assert_eq!(BatchResampler::new(16000, 16000).unwrap().latency_frames(), 0);
+
+        for quality in [ResamplerQuality::Fast, ResamplerQuality::Balanced, ResamplerQuality::High] {
+            for (input_rate, output_rate) in [(48000, 16000), (44100, 16000), (96000, 24000)] {
+                let config = ResamplerConfig { quality, chunk_size: 256 };
+                let mut resampler = BatchResampler::with_config(input_rate, output_rate, config).unwrap();
+                let latency = resampler.latency_frames() as f64;
+                // 每次送入后缓冲区恰好为空，输出落后于输入的长度即为滤波器延迟
+                let mut input_len = 256 - resampler.priming % 256;
+                let mut output_len = resampler.process(&vec![0.1; input_len]).unwrap().len();
+                for _ in 0..50 {
+                    input_len += 256;
+                    output_len += resampler.process(&[0.1; 256]).unwrap().len();
+                    assert!(resampler.buffer.is_empty());
+                    let behind = input_len as f64 * output_rate as f64 / input_rate as f64 - output_len as f64;
+                    assert!(
+                        (behind - latency).abs() <= 1.0,
+                        "{}->{} {:?} latency {} behind {}",
+                        input_rate,
+                        output_rate,
+                        quality,
+                        latency,
+                        behind
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_sweep_sample_accurate() {
+        const DURATION: f64 = 0.5;
+        let qualities = [
+            (ResamplerQuality::Fast, 0.03),
+            (ResamplerQuality::Balanced, 0.02),
+            (ResamplerQuality::High, 5e-3),
+        ];
+        for input_rate in [44100, 48000, 96000] {
+            for output_rate in [16000, 24000] {
+                // 扫频上限留在抗混叠滤波器通带内
+                let end_hz = output_rate as f64 * 0.35;
+                let input: Vec<f32> = (0..(input_rate as f64 * DURATION) as usize)
+                    .map(|i| sweep(i, input_rate, end_hz, DURATION))
+                    .collect();
+                let expected_len = input.len() * output_rate as usize / input_rate as usize;
+
+                for (quality, tolerance) in qualities {
+                    let config = ResamplerConfig { quality, chunk_size: 256 };
+                    let mut resampler = BatchResampler::with_config(input_rate, output_rate, config).unwrap();
+                    // 刷新后重采样器可重复使用，两轮结果一致
+                    for _ in 0..2 {
+                        let mut output = Vec::new();
+                        for (i, block) in input.chunks(997).enumerate() {
+                            let (head, tail) = block.split_at(block.len().min(i % 5 * 61));
+                            output.extend(resampler.process(head).unwrap());
+                            output.extend(resampler.process(tail).unwrap());
+                        }
+                        output.extend(resampler.flush().unwrap());
+                        assert_eq!(output.len(), expected_len, "{}->{} {:?}", input_rate, output_rate, quality);
+
+                        // 两端的滤波器过渡区之外逐样本与理想扫频比较
+                        let max_error = (100..output.len() - 100)
+                            .map(|k| (output[k] - sweep(k, output_rate, end_hz, DURATION)).abs())
+                            .fold(0.0f32, f32::max);
+                        assert!(
+                            max_error < tolerance,
+                            "{}->{} {:?} max error {}",
+                            input_rate,
+                            output_rate,
+                            quality,
+                            max_error
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
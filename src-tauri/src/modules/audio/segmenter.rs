@@ -0,0 +1,452 @@
+//! 语音段切分模块
+//!
+//! 根据 VAD 状态把连续的音频帧切分为完整的语音段 (utterance)，
+//! 附带首尾填充与起止时间，供批量转写与自动提交使用
+
+use super::capture::{AudioFrame, frames_to_ns};
+use super::preroll::PreRollBuffer;
+use super::vad::{VadConfig, VadState, VoiceActivityDetector};
+
+/// 批量切分时的 VAD 帧长 (毫秒)
+const BATCH_FRAME_MS: u32 = 20;
+
+/// 语音段切分配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmenterConfig {
+    /// 语音段开始前保留的音频 (毫秒)
+    pub leading_padding_ms: u32,
+    /// VAD 判定语音结束 (`Ending`) 后追加的音频 (毫秒)
+    pub trailing_padding_ms: u32,
+    /// 语音段最大时长 (毫秒)，超出时在最安静处强制切分，0 表示不限制
+    pub max_segment_ms: u32,
+    /// 语音结束后在此时长内再次出现语音时合并为同一语音段 (毫秒)
+    pub min_gap_ms: u32,
+}
+
+impl Default for SegmenterConfig {
+    fn default() -> Self {
+        Self {
+            leading_padding_ms: 200,
+            trailing_padding_ms: 200,
+            max_segment_ms: 30_000,
+            min_gap_ms: 300,
+        }
+    }
+}
+
+/// 完整的语音段
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    /// 单声道样本 (含首尾填充)
+    pub samples: Vec<f32>,
+    /// 采样率 (Hz)
+    pub sample_rate: u32,
+    /// 首个样本的采集时间 (纳秒)
+    pub start_ns: u128,
+    /// 最后一个样本之后的时间 (纳秒)
+    pub end_ns: u128,
+    /// 是否因超过最大时长被强制切分 (语音在下一段中继续)
+    pub forced_split: bool,
+}
+
+impl Utterance {
+    /// 时长 (毫秒)
+    pub fn duration_ms(&self) -> u64 {
+        ((self.end_ns - self.start_ns) / 1_000_000) as u64
+    }
+}
+
+/// 切分阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// 无语音，音频进入首部填充缓冲
+    Idle,
+    /// 语音段进行中
+    Active,
+    /// 已收到 `Ending`，等待尾部填充与合并间隔
+    Closing,
+}
+
+/// 语音段切分器
+///
+/// 逐帧输入单声道音频及其 VAD 状态，语音段结束 (含尾部填充与合并间隔) 时输出。
+/// VAD 因语音过短直接回到 `Silence` 的片段会被丢弃。
+/// 相邻语音段的填充不重叠，尾部填充优先
+#[derive(Debug, Clone)]
+pub struct Segmenter {
+    config: SegmenterConfig,
+    sample_rate: u32,
+    phase: Phase,
+    /// 首部填充
+    leading: PreRollBuffer,
+    /// 当前语音段的样本
+    current: Vec<f32>,
+    /// 当前语音段首个样本的时间 (纳秒)
+    current_start_ns: u128,
+    /// 当前语音段各帧的 (起始偏移, 样本数, 均方能量)，用于寻找强制切分点
+    frame_energy: Vec<(usize, usize, f32)>,
+    /// `Ending` 之后的静音样本
+    tail: Vec<f32>,
+}
+
+impl Segmenter {
+    /// 创建切分器
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 输入音频的采样率
+    /// * `config` - 切分配置
+    pub fn new(sample_rate: u32, config: SegmenterConfig) -> Self {
+        let sample_rate = sample_rate.max(1);
+        Self {
+            config,
+            sample_rate,
+            phase: Phase::Idle,
+            leading: PreRollBuffer::new(ms_to_samples(config.leading_padding_ms, sample_rate)),
+            current: Vec::new(),
+            current_start_ns: 0,
+            frame_energy: Vec::new(),
+            tail: Vec::new(),
+        }
+    }
+
+    /// 切分配置
+    pub fn config(&self) -> &SegmenterConfig {
+        &self.config
+    }
+
+    /// 是否有未输出的语音段
+    pub fn in_segment(&self) -> bool {
+        self.phase != Phase::Idle
+    }
+
+    /// 输入一帧单声道音频及其 VAD 状态，返回已完成的语音段
+    pub fn push(&mut self, frame: &AudioFrame, state: VadState) -> Vec<Utterance> {
+        let samples = frame.samples.as_slice();
+        let mut utterances = Vec::new();
+        match self.phase {
+            Phase::Idle => {
+                if state == VadState::Silence {
+                    self.leading.push(samples);
+                } else {
+                    self.start(frame.timestamp_ns);
+                    self.append(samples, state, &mut utterances);
+                }
+            }
+            Phase::Active => self.append(samples, state, &mut utterances),
+            Phase::Closing => {
+                if state == VadState::Silence {
+                    self.tail.extend_from_slice(samples);
+                    let trailing = ms_to_samples(self.config.trailing_padding_ms, self.sample_rate);
+                    let gap = ms_to_samples(self.config.min_gap_ms, self.sample_rate);
+                    if self.tail.len() >= trailing.max(gap) {
+                        utterances.push(self.finish());
+                    }
+                } else if self.tail.len() < ms_to_samples(self.config.min_gap_ms, self.sample_rate) {
+                    // 间隔过短，与上一语音段合并
+                    let tail = std::mem::take(&mut self.tail);
+                    self.record(&tail);
+                    self.phase = Phase::Active;
+                    self.append(samples, state, &mut utterances);
+                } else {
+                    utterances.push(self.finish());
+                    self.start(frame.timestamp_ns);
+                    self.append(samples, state, &mut utterances);
+                }
+            }
+        }
+        utterances
+    }
+
+    /// 输出进行中的语音段 (如会话结束时)
+    pub fn flush(&mut self) -> Option<Utterance> {
+        let utterance = self.in_segment().then(|| self.finish());
+        self.leading.clear();
+        utterance
+    }
+
+    /// 清空全部状态
+    pub fn reset(&mut self) {
+        self.phase = Phase::Idle;
+        self.leading.clear();
+        self.current.clear();
+        self.frame_energy.clear();
+        self.tail.clear();
+    }
+
+    /// 以首部填充开始新的语音段
+    ///
+    /// # Arguments
+    /// * `timestamp_ns` - 触发语音段的首帧时间
+    fn start(&mut self, timestamp_ns: u128) {
+        let leading = self.leading.drain();
+        self.current.clear();
+        self.frame_energy.clear();
+        self.current_start_ns =
+            timestamp_ns.saturating_sub(frames_to_ns(leading.len() as u64, self.sample_rate));
+        self.record(&leading);
+        self.phase = Phase::Active;
+    }
+
+    /// 向进行中的语音段追加一帧
+    fn append(&mut self, samples: &[f32], state: VadState, utterances: &mut Vec<Utterance>) {
+        self.record(samples);
+        match state {
+            VadState::Speech => {
+                if let Some(utterance) = self.split_if_too_long() {
+                    utterances.push(utterance);
+                }
+            }
+            VadState::Ending => {
+                self.tail.clear();
+                self.phase = Phase::Closing;
+            }
+            VadState::Silence => {
+                // VAD 判定语音过短而丢弃，音频留作下一语音段的首部填充
+                self.leading.push(&self.current);
+                self.current.clear();
+                self.frame_energy.clear();
+                self.phase = Phase::Idle;
+            }
+        }
+    }
+
+    /// 记录样本及其能量
+    fn record(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let energy = samples.iter().map(|&x| x * x).sum::<f32>() / samples.len() as f32;
+        self.frame_energy.push((self.current.len(), samples.len(), energy));
+        self.current.extend_from_slice(samples);
+    }
+
+    /// 超过最大时长时在后半段最安静的帧中点切分，输出前半部分
+    fn split_if_too_long(&mut self) -> Option<Utterance> {
+        let max = ms_to_samples(self.config.max_segment_ms, self.sample_rate);
+        if max == 0 || self.current.len() < max {
+            return None;
+        }
+        let half = self.current.len() / 2;
+        let split = self.frame_energy
+            .iter()
+            .filter(|(offset, _, _)| *offset >= half)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(offset, len, _)| offset + len / 2)
+            .unwrap_or(self.current.len());
+
+        let rest = self.current.split_off(split);
+        let first = std::mem::replace(&mut self.current, rest);
+        let utterance = self.utterance(first, true);
+        self.current_start_ns = utterance.end_ns;
+        self.frame_energy = self.frame_energy
+            .iter()
+            .filter(|(offset, len, _)| offset + len > split)
+            .map(|&(offset, len, energy)| {
+                let start = offset.max(split);
+                (start - split, offset + len - start, energy)
+            })
+            .collect();
+        Some(utterance)
+    }
+
+    /// 结束当前语音段，尾部填充之外的静音留作下一语音段的首部填充
+    fn finish(&mut self) -> Utterance {
+        let trailing = ms_to_samples(self.config.trailing_padding_ms, self.sample_rate);
+        let tail = std::mem::take(&mut self.tail);
+        let keep = trailing.min(tail.len());
+        let mut samples = std::mem::take(&mut self.current);
+        samples.extend_from_slice(&tail[..keep]);
+        self.leading.push(&tail[keep..]);
+        self.frame_energy.clear();
+        self.phase = Phase::Idle;
+        self.utterance(samples, false)
+    }
+
+    fn utterance(&self, samples: Vec<f32>, forced_split: bool) -> Utterance {
+        let end_ns = self.current_start_ns + frames_to_ns(samples.len() as u64, self.sample_rate);
+        Utterance {
+            samples,
+            sample_rate: self.sample_rate,
+            start_ns: self.current_start_ns,
+            end_ns,
+            forced_split,
+        }
+    }
+}
+
+/// 对整段单声道音频运行 VAD 并切分语音段 (批量转写)
+///
+/// # Arguments
+/// * `samples` - 单声道样本
+/// * `sample_rate` - 采样率
+/// * `vad` - VAD 配置，`sample_rate` 以参数为准
+/// * `config` - 切分配置
+pub fn split_utterances(
+    samples: &[f32],
+    sample_rate: u32,
+    vad: VadConfig,
+    config: SegmenterConfig,
+) -> Vec<Utterance> {
+    let mut detector = VoiceActivityDetector::new(VadConfig { sample_rate, ..vad });
+    let mut segmenter = Segmenter::new(sample_rate, config);
+    let frame_size = ms_to_samples(BATCH_FRAME_MS, sample_rate).max(1);
+
+    let mut utterances = Vec::new();
+    for (i, chunk) in samples.chunks(frame_size).enumerate() {
+        let state = detector.detect(chunk);
+        let timestamp_ns = frames_to_ns((i * frame_size) as u64, sample_rate);
+        let frame = AudioFrame::new(chunk.to_vec(), sample_rate, 1, timestamp_ns);
+        utterances.extend(segmenter.push(&frame, state));
+    }
+    utterances.extend(segmenter.flush());
+    utterances
+}
+
+/// 时长 (毫秒) 转换为样本数
+fn ms_to_samples(ms: u32, sample_rate: u32) -> usize {
+    (sample_rate as u64 * ms as u64 / 1000) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 1000;
+    /// 10 样本 = 10ms @ 1kHz
+    const FRAME: usize = 10;
+
+    fn config() -> SegmenterConfig {
+        SegmenterConfig {
+            leading_padding_ms: 30,
+            trailing_padding_ms: 20,
+            max_segment_ms: 0,
+            min_gap_ms: 50,
+        }
+    }
+
+    /// 按 (状态, 帧数, 幅度) 依次输入，返回全部输出
+    fn run(segmenter: &mut Segmenter, script: &[(VadState, usize, f32)]) -> Vec<Utterance> {
+        let mut index = 0;
+        let mut utterances = Vec::new();
+        for &(state, frames, amplitude) in script {
+            for _ in 0..frames {
+                let timestamp_ns = frames_to_ns((index * FRAME) as u64, RATE);
+                let frame = AudioFrame::new(vec![amplitude; FRAME], RATE, 1, timestamp_ns);
+                utterances.extend(segmenter.push(&frame, state));
+                index += 1;
+            }
+        }
+        utterances
+    }
+
+    #[test]
+    fn test_segmenter_pads_utterance() {
+        let mut segmenter = Segmenter::new(RATE, config());
+        let utterances = run(&mut segmenter, &[
+            (VadState::Silence, 10, 0.0),
+            (VadState::Speech, 8, 0.5),
+            (VadState::Ending, 1, 0.0),
+            (VadState::Silence, 10, 0.0),
+        ]);
+        assert_eq!(utterances.len(), 1);
+        let utterance = &utterances[0];
+        // 30ms 首部填充 + 90ms 语音 + 20ms 尾部填充
+        assert_eq!(utterance.start_ns, 70_000_000);
+        assert_eq!(utterance.end_ns, 210_000_000);
+        assert_eq!(utterance.samples.len(), 140);
+        assert_eq!(utterance.duration_ms(), 140);
+        assert!(!utterance.forced_split);
+        assert_eq!(utterance.samples[30], 0.5);
+        assert!(!segmenter.in_segment());
+    }
+
+    #[test]
+    fn test_segmenter_merges_short_gaps() {
+        let mut segmenter = Segmenter::new(RATE, config());
+        // 间隔 30ms < 50ms，合并
+        let utterances = run(&mut segmenter, &[
+            (VadState::Speech, 5, 0.5),
+            (VadState::Ending, 1, 0.0),
+            (VadState::Silence, 3, 0.0),
+            (VadState::Speech, 5, 0.5),
+            (VadState::Ending, 1, 0.0),
+            (VadState::Silence, 5, 0.0),
+        ]);
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(utterances[0].samples.len(), 170);
+
+        // 间隔 80ms，分为两段，第二段的首部填充不与第一段的尾部填充重叠
+        let mut segmenter = Segmenter::new(RATE, config());
+        let utterances = run(&mut segmenter, &[
+            (VadState::Speech, 5, 0.5),
+            (VadState::Ending, 1, 0.0),
+            (VadState::Silence, 8, 0.0),
+            (VadState::Speech, 5, 0.5),
+            (VadState::Ending, 1, 0.0),
+        ]);
+        assert_eq!(utterances.len(), 1);
+        let last = segmenter.flush().unwrap();
+        assert_eq!(utterances[0].end_ns, 80_000_000);
+        assert_eq!(last.start_ns, 110_000_000);
+        assert_eq!(last.end_ns, 200_000_000);
+        assert!(segmenter.flush().is_none());
+    }
+
+    #[test]
+    fn test_segmenter_drops_discarded_speech() {
+        let mut segmenter = Segmenter::new(RATE, config());
+        let utterances = run(&mut segmenter, &[
+            (VadState::Speech, 3, 0.5),
+            (VadState::Silence, 10, 0.0),
+        ]);
+        assert!(utterances.is_empty());
+        assert!(!segmenter.in_segment());
+        assert!(segmenter.flush().is_none());
+    }
+
+    #[test]
+    fn test_segmenter_forces_split_at_quietest_frame() {
+        let mut segmenter = Segmenter::new(RATE, SegmenterConfig {
+            max_segment_ms: 200,
+            ..config()
+        });
+        let utterances = run(&mut segmenter, &[
+            (VadState::Speech, 14, 0.5),
+            (VadState::Speech, 1, 0.05),
+            (VadState::Speech, 5, 0.5),
+            (VadState::Speech, 5, 0.5),
+            (VadState::Ending, 1, 0.0),
+            (VadState::Silence, 5, 0.0),
+        ]);
+        assert_eq!(utterances.len(), 2);
+        // 在第 15 帧 (140 - 150 样本) 中点切分
+        assert!(utterances[0].forced_split);
+        assert_eq!(utterances[0].samples.len(), 145);
+        assert_eq!(utterances[1].start_ns, utterances[0].end_ns);
+        assert!(!utterances[1].forced_split);
+        assert_eq!(utterances[1].samples.len(), 260 + 20 - 145);
+        assert!(utterances.iter().all(|u| u.samples.len() <= 200));
+    }
+
+    #[test]
+    fn test_split_utterances_runs_vad() {
+        let rate = 16000;
+        let burst = |len: usize| -> Vec<f32> {
+            (0..len).map(|n| 0.3 * (2.0 * std::f32::consts::PI * 440.0 * n as f32 / rate as f32).sin()).collect()
+        };
+        let silence = |len: usize| vec![0.0f32; len];
+        let mut samples = silence(8000);
+        samples.extend(burst(8000));
+        samples.extend(silence(24000));
+        samples.extend(burst(8000));
+        samples.extend(silence(24000));
+
+        let utterances = split_utterances(&samples, rate, VadConfig::default(), SegmenterConfig::default());
+        assert_eq!(utterances.len(), 2);
+        // 首部填充 200ms，语音从 500ms 与 2500ms 开始
+        assert_eq!(utterances[0].start_ns, 300_000_000);
+        assert_eq!(utterances[1].start_ns, 2_300_000_000);
+        assert!(utterances[0].end_ns < utterances[1].start_ns);
+        assert!(utterances.iter().all(|u| u.sample_rate == rate));
+    }
+}
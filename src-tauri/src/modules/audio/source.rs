@@ -0,0 +1,691 @@
+//! 音频源抽象
+//!
+//! 将采集管线与 cpal 解耦：除真实设备外，还可以从 WAV/PCM 文件或
+//! 合成信号读取音频，便于在无声卡环境下确定性地驱动 VAD、重采样和转写路径
+
+use super::capture::{AudioCapturer, AudioConfig, AudioFrame, frames_to_ns};
+use super::mix::MixedSource;
+use crate::error::AudioError;
+use crate::modules::config::{InputSourceSettings, MultiInputMode};
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 音频源
+///
+/// 采集线程只依赖该 trait，不关心数据来自设备、文件还是合成信号
+pub trait AudioSource {
+    /// 配置音频源
+    fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError>;
+
+    /// 开始产出音频
+    fn start(&mut self) -> Result<(), AudioError>;
+
+    /// 停止产出音频
+    fn stop(&mut self) -> Result<(), AudioError>;
+
+    /// 是否正在运行
+    fn is_running(&self) -> bool;
+
+    /// 实际输出采样率
+    fn sample_rate(&self) -> u32;
+
+    /// 实际输出通道数
+    fn channels(&self) -> u16;
+
+    /// 读取最多 `max_samples` 个交错样本，暂无数据时返回 None
+    fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame>;
+
+    /// 有限音频源是否已读完
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    /// 设备 ID，非设备源为 None
+    fn device_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// 流是否因设备移除或失效而中断
+    fn is_stream_lost(&self) -> bool {
+        false
+    }
+
+    /// 各输出通道的标签 (如 "mic"、"system")，无标签时为空
+    fn channel_labels(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+impl AudioSource for AudioCapturer {
+    fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError> {
+        AudioCapturer::configure(self, config)
+    }
+
+    fn start(&mut self) -> Result<(), AudioError> {
+        AudioCapturer::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), AudioError> {
+        AudioCapturer::stop(self)
+    }
+
+    fn is_running(&self) -> bool {
+        AudioCapturer::is_running(self)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.negotiated_config()
+            .map(|n| n.sample_rate)
+            .unwrap_or(self.config().sample_rate)
+    }
+
+    fn channels(&self) -> u16 {
+        self.negotiated_config()
+            .map(|n| n.channels)
+            .unwrap_or(self.config().channels)
+    }
+
+    fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame> {
+        AudioCapturer::read_frame(self, max_samples)
+    }
+
+    fn device_id(&self) -> Option<&str> {
+        AudioCapturer::device_id(self)
+    }
+
+    fn is_stream_lost(&self) -> bool {
+        AudioCapturer::is_stream_lost(self)
+    }
+}
+
+/// 回放速度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlaybackSpeed {
+    /// 按采样率实时产出，模拟真实设备
+    #[default]
+    RealTime,
+    /// 不等待，尽快产出全部数据
+    AsFastAsPossible,
+}
+
+/// 原始 PCM 样本编码 (小端)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcmEncoding {
+    /// 16 位有符号整数
+    I16,
+    /// 32 位浮点
+    F32,
+}
+
+/// 原始 PCM 文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub encoding: PcmEncoding,
+}
+
+/// 按回放速度控制产出节奏
+#[derive(Debug)]
+struct Pacer {
+    speed: PlaybackSpeed,
+    started_at: Option<Instant>,
+    /// 已产出的帧数 (每帧包含所有通道)
+    frames_emitted: u64,
+}
+
+impl Pacer {
+    fn new(speed: PlaybackSpeed) -> Self {
+        Self { speed, started_at: None, frames_emitted: 0 }
+    }
+
+    fn start(&mut self) {
+        self.started_at = Some(Instant::now());
+    }
+
+    fn stop(&mut self) {
+        self.started_at = None;
+    }
+
+    fn is_running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// 本次最多可产出的帧数
+    fn budget(&self, sample_rate: u32, max_frames: usize) -> usize {
+        match (self.speed, self.started_at) {
+            (_, None) => 0,
+            (PlaybackSpeed::AsFastAsPossible, Some(_)) => max_frames,
+            (PlaybackSpeed::RealTime, Some(started_at)) => {
+                let due = (started_at.elapsed().as_secs_f64() * sample_rate as f64) as u64;
+                (due.saturating_sub(self.frames_emitted) as usize).min(max_frames)
+            }
+        }
+    }
+
+    /// 记录产出并返回该段起始时间戳 (纳秒)
+    fn advance(&mut self, sample_rate: u32, frames: usize) -> u128 {
+        let timestamp_ns = frames_to_ns(self.frames_emitted, sample_rate);
+        self.frames_emitted += frames as u64;
+        timestamp_ns
+    }
+}
+
+/// 文件音频源
+///
+/// 支持 WAV 与原始 PCM，数据在打开时一次性解码到内存
+#[derive(Debug)]
+pub struct FileSource {
+    /// 交错 f32 样本
+    samples: Vec<f32>,
+    sample_rate: u32,
+    channels: u16,
+    /// 下一个读取位置 (样本)
+    position: usize,
+    pacer: Pacer,
+}
+
+impl FileSource {
+    /// 打开 WAV 文件
+    pub fn open_wav(path: impl AsRef<Path>, speed: PlaybackSpeed) -> Result<Self, AudioError> {
+        let path = path.as_ref();
+        let reader = hound::WavReader::open(path)
+            .map_err(|e| AudioError::SourceFailed(format!("{}: {}", path.display(), e)))?;
+        let spec = reader.spec();
+
+        let samples: Result<Vec<f32>, hound::Error> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.into_samples::<f32>().collect(),
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect()
+            }
+        };
+        let samples = samples
+            .map_err(|e| AudioError::SourceFailed(format!("{}: {}", path.display(), e)))?;
+
+        Ok(Self::from_samples(samples, spec.sample_rate, spec.channels, speed))
+    }
+
+    /// 打开无文件头的原始 PCM 文件
+    pub fn open_pcm(
+        path: impl AsRef<Path>,
+        format: PcmFormat,
+        speed: PlaybackSpeed,
+    ) -> Result<Self, AudioError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| AudioError::SourceFailed(format!("{}: {}", path.display(), e)))?;
+        Ok(Self::from_samples(
+            decode_pcm(&bytes, format.encoding),
+            format.sample_rate,
+            format.channels,
+            speed,
+        ))
+    }
+
+    /// 从内存中的交错样本创建
+    pub fn from_samples(samples: Vec<f32>, sample_rate: u32, channels: u16, speed: PlaybackSpeed) -> Self {
+        let channels = channels.max(1);
+        // 丢弃不完整的尾帧
+        let mut samples = samples;
+        samples.truncate(samples.len() - samples.len() % channels as usize);
+        Self {
+            samples,
+            sample_rate,
+            channels,
+            position: 0,
+            pacer: Pacer::new(speed),
+        }
+    }
+
+    /// 总时长
+    pub fn duration(&self) -> Duration {
+        let frames = (self.samples.len() / self.channels as usize) as f64;
+        Duration::from_secs_f64(frames / self.sample_rate.max(1) as f64)
+    }
+
+    /// 剩余样本数
+    pub fn remaining(&self) -> usize {
+        self.samples.len() - self.position
+    }
+}
+
+impl AudioSource for FileSource {
+    /// 文件格式固定，忽略设备与格式请求
+    fn configure(&mut self, _config: AudioConfig) -> Result<(), AudioError> {
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), AudioError> {
+        self.pacer.start();
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), AudioError> {
+        self.pacer.stop();
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.pacer.is_running()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame> {
+        let channels = self.channels as usize;
+        let max_frames = (max_samples / channels).min(self.remaining() / channels);
+        let frames = self.pacer.budget(self.sample_rate, max_frames);
+        if frames == 0 {
+            return None;
+        }
+
+        let end = self.position + frames * channels;
+        let samples = self.samples[self.position..end].to_vec();
+        self.position = end;
+        let timestamp_ns = self.pacer.advance(self.sample_rate, frames);
+        Some(AudioFrame::new(samples, self.sample_rate, self.channels, timestamp_ns))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+/// 合成信号
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    /// 正弦波
+    Tone { frequency: f32, amplitude: f32 },
+    /// 均匀分布白噪声 (固定种子，结果可复现)
+    WhiteNoise { amplitude: f32, seed: u64 },
+    /// 静音
+    Silence,
+}
+
+/// 合成音频源
+///
+/// 所有通道输出相同信号
+#[derive(Debug)]
+pub struct SyntheticSource {
+    signal: Signal,
+    sample_rate: u32,
+    channels: u16,
+    /// 总帧数，None 表示无限
+    total_frames: Option<u64>,
+    /// 已生成帧数
+    generated: u64,
+    /// 噪声生成器状态
+    noise_state: u64,
+    pacer: Pacer,
+}
+
+impl SyntheticSource {
+    /// 创建合成源
+    ///
+    /// # Arguments
+    /// * `signal` - 信号类型
+    /// * `sample_rate` - 采样率
+    /// * `channels` - 通道数
+    /// * `duration` - 时长，None 表示无限
+    /// * `speed` - 回放速度
+    pub fn new(
+        signal: Signal,
+        sample_rate: u32,
+        channels: u16,
+        duration: Option<Duration>,
+        speed: PlaybackSpeed,
+    ) -> Self {
+        let mut source = Self {
+            signal,
+            sample_rate,
+            channels: channels.max(1),
+            total_frames: None,
+            generated: 0,
+            noise_state: 0,
+            pacer: Pacer::new(speed),
+        };
+        source.set_duration(duration);
+        source.reset_noise();
+        source
+    }
+
+    /// 生成最多 `frames` 帧交错样本
+    pub fn generate(&mut self, frames: usize) -> Vec<f32> {
+        let frames = match self.total_frames {
+            Some(total) => frames.min(total.saturating_sub(self.generated) as usize),
+            None => frames,
+        };
+
+        let mut samples = Vec::with_capacity(frames * self.channels as usize);
+        for _ in 0..frames {
+            let value = self.next_value();
+            samples.extend(std::iter::repeat_n(value, self.channels as usize));
+            self.generated += 1;
+        }
+        samples
+    }
+
+    fn set_duration(&mut self, duration: Option<Duration>) {
+        self.total_frames = duration.map(|d| (d.as_secs_f64() * self.sample_rate as f64).round() as u64);
+    }
+
+    fn reset_noise(&mut self) {
+        if let Signal::WhiteNoise { seed, .. } = self.signal {
+            // xorshift 状态不能为 0
+            self.noise_state = seed.max(1);
+        }
+    }
+
+    fn next_value(&mut self) -> f32 {
+        match self.signal {
+            Signal::Tone { frequency, amplitude } => {
+                let t = self.generated as f64 / self.sample_rate as f64;
+                amplitude * (2.0 * PI as f64 * frequency as f64 * t).sin() as f32
+            }
+            Signal::WhiteNoise { amplitude, .. } => {
+                // xorshift64*
+                let mut x = self.noise_state;
+                x ^= x >> 12;
+                x ^= x << 25;
+                x ^= x >> 27;
+                self.noise_state = x;
+                let r = x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40;
+                let unit = r as f32 / (1u64 << 24) as f32;
+                amplitude * (unit * 2.0 - 1.0)
+            }
+            Signal::Silence => 0.0,
+        }
+    }
+}
+
+impl AudioSource for SyntheticSource {
+    /// 采用请求的采样率与通道数，并从头重新生成
+    fn configure(&mut self, config: AudioConfig) -> Result<(), AudioError> {
+        if config.sample_rate == 0 {
+            return Err(AudioError::ConfigurationFailed("Sample rate must be positive".to_string()));
+        }
+        let duration = self
+            .total_frames
+            .map(|frames| Duration::from_secs_f64(frames as f64 / self.sample_rate as f64));
+        self.sample_rate = config.sample_rate;
+        self.channels = config.channels.max(1);
+        self.generated = 0;
+        self.set_duration(duration);
+        self.reset_noise();
+        self.pacer = Pacer::new(self.pacer.speed);
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), AudioError> {
+        self.pacer.start();
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), AudioError> {
+        self.pacer.stop();
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.pacer.is_running()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn read_frame(&mut self, max_samples: usize) -> Option<AudioFrame> {
+        let frames = self.pacer.budget(self.sample_rate, max_samples / self.channels as usize);
+        let samples = self.generate(frames);
+        if samples.is_empty() {
+            return None;
+        }
+        let timestamp_ns = self.pacer.advance(self.sample_rate, samples.len() / self.channels as usize);
+        Some(AudioFrame::new(samples, self.sample_rate, self.channels, timestamp_ns))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.total_frames.is_some_and(|total| self.generated >= total)
+    }
+}
+
+/// 音频源类型
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SourceKind {
+    /// 真实输入设备
+    #[default]
+    Device,
+    /// WAV 文件
+    WavFile { path: PathBuf, speed: PlaybackSpeed },
+    /// 原始 PCM 文件
+    PcmFile { path: PathBuf, format: PcmFormat, speed: PlaybackSpeed },
+    /// 合成信号
+    Synthetic { signal: Signal, duration: Option<Duration>, speed: PlaybackSpeed },
+    /// 多个输入设备同时采集，第一路为主输入
+    MultiDevice { inputs: Vec<InputSourceSettings>, mode: MultiInputMode },
+}
+
+impl SourceKind {
+    /// 创建对应的音频源
+    ///
+    /// 合成源的采样率与通道数在 `configure` 时由 [`AudioConfig`] 决定
+    pub fn create(&self) -> Result<Box<dyn AudioSource>, AudioError> {
+        Ok(match self {
+            SourceKind::Device => Box::new(AudioCapturer::new()),
+            SourceKind::WavFile { path, speed } => Box::new(FileSource::open_wav(path, *speed)?),
+            SourceKind::PcmFile { path, format, speed } => {
+                Box::new(FileSource::open_pcm(path, *format, *speed)?)
+            }
+            SourceKind::Synthetic { signal, duration, speed } => {
+                let defaults = AudioConfig::default();
+                Box::new(SyntheticSource::new(*signal, defaults.sample_rate, defaults.channels, *duration, *speed))
+            }
+            SourceKind::MultiDevice { inputs, mode } => {
+                // 各采集器共用时间零点，时间戳可直接比较
+                let epoch = Instant::now();
+                let mut mixed = MixedSource::new(*mode);
+                for input in inputs {
+                    mixed.add_input(
+                        input.label.clone(),
+                        Box::new(AudioCapturer::with_epoch(epoch)),
+                        input.device_id.clone(),
+                        input.gain_db,
+                    );
+                }
+                Box::new(mixed)
+            }
+        })
+    }
+
+    /// 是否为实时输入设备 (文件与合成源没有热键前的音频)
+    pub fn is_live(&self) -> bool {
+        matches!(self, SourceKind::Device | SourceKind::MultiDevice { .. })
+    }
+}
+
+/// 解码小端 PCM 字节，忽略不完整的尾部样本
+fn decode_pcm(bytes: &[u8], encoding: PcmEncoding) -> Vec<f32> {
+    match encoding {
+        PcmEncoding::I16 => bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect(),
+        PcmEncoding::F32 => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn drain(source: &mut dyn AudioSource, chunk: usize) -> Vec<f32> {
+        let mut samples = Vec::new();
+        while let Some(frame) = source.read_frame(chunk) {
+            samples.extend(frame.samples);
+        }
+        samples
+    }
+
+    #[test]
+    fn test_wav_source_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..1600 {
+            writer.write_sample(if i % 2 == 0 { 16384i16 } else { -16384 }).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let mut source = FileSource::open_wav(&path, PlaybackSpeed::AsFastAsPossible).unwrap();
+        assert_eq!(source.sample_rate(), 16000);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.duration(), Duration::from_millis(50));
+
+        // 未启动时不产出
+        assert!(source.read_frame(512).is_none());
+        source.start().unwrap();
+
+        // 读取大小按整帧对齐
+        let frame = source.read_frame(301).unwrap();
+        assert_eq!(frame.samples.len(), 300);
+        assert_eq!(frame.timestamp_ns, 0);
+        assert_eq!(frame.samples[0], 0.5);
+        assert_eq!(frame.samples[1], -0.5);
+
+        let frame = source.read_frame(300).unwrap();
+        assert_eq!(frame.timestamp_ns, 150 * 1_000_000_000 / 16000);
+
+        let rest = drain(&mut source, 512);
+        assert_eq!(rest.len(), 1600 - 600);
+        assert!(source.is_finished());
+    }
+
+    #[test]
+    fn test_wav_source_missing_file() {
+        let result = FileSource::open_wav("/nonexistent/file.wav", PlaybackSpeed::RealTime);
+        assert!(matches!(result, Err(AudioError::SourceFailed(_))));
+    }
+
+    #[test]
+    fn test_pcm_source() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.pcm");
+        let bytes: Vec<u8> = [0.25f32, -0.25, 1.0].iter().flat_map(|s| s.to_le_bytes()).collect();
+        std::fs::write(&path, bytes).unwrap();
+
+        let format = PcmFormat { sample_rate: 8000, channels: 1, encoding: PcmEncoding::F32 };
+        let mut source = FileSource::open_pcm(&path, format, PlaybackSpeed::AsFastAsPossible).unwrap();
+        source.start().unwrap();
+        assert_eq!(drain(&mut source, 1024), vec![0.25, -0.25, 1.0]);
+    }
+
+    #[test]
+    fn test_decode_pcm_i16() {
+        let bytes = [0x00, 0x40, 0x00, 0x80, 0xff];
+        assert_eq!(decode_pcm(&bytes, PcmEncoding::I16), vec![0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_realtime_pacing() {
+        let mut source = FileSource::from_samples(vec![0.0; 16000], 16000, 1, PlaybackSpeed::RealTime);
+        source.start().unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+
+        let read = drain(&mut source, 16000).len();
+        // 约 50ms 的数据，远少于全部 1 秒
+        assert!((800..8000).contains(&read), "unexpected sample count {}", read);
+        assert!(!source.is_finished());
+    }
+
+    #[test]
+    fn test_synthetic_tone() {
+        let mut source = SyntheticSource::new(
+            Signal::Tone { frequency: 1000.0, amplitude: 0.5 },
+            16000,
+            2,
+            Some(Duration::from_millis(100)),
+            PlaybackSpeed::AsFastAsPossible,
+        );
+        source.start().unwrap();
+        let samples = drain(&mut source, 320);
+        assert_eq!(samples.len(), 1600 * 2);
+        assert!(source.is_finished());
+
+        // 双通道相同
+        assert!(samples.chunks(2).all(|f| f[0] == f[1]));
+        let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.01);
+        // 1kHz @ 16kHz: 第 4 个样本为正峰值
+        assert!((samples[8] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_synthetic_noise_is_deterministic() {
+        let signal = Signal::WhiteNoise { amplitude: 0.1, seed: 42 };
+        let mut a = SyntheticSource::new(signal, 16000, 1, None, PlaybackSpeed::AsFastAsPossible);
+        let mut b = SyntheticSource::new(signal, 16000, 1, None, PlaybackSpeed::AsFastAsPossible);
+        let samples = a.generate(1000);
+        assert_eq!(samples, b.generate(1000));
+        assert!(samples.iter().all(|s| s.abs() <= 0.1));
+
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 0.01);
+        assert!(!a.is_finished());
+    }
+
+    #[test]
+    fn test_synthetic_configure() {
+        let mut source = SyntheticSource::new(
+            Signal::Silence,
+            16000,
+            1,
+            Some(Duration::from_millis(10)),
+            PlaybackSpeed::AsFastAsPossible,
+        );
+        let config = AudioConfig { sample_rate: 48000, channels: 2, ..Default::default() };
+        AudioSource::configure(&mut source, config).unwrap();
+        source.start().unwrap();
+
+        let samples = drain(&mut source, 4096);
+        assert_eq!(samples.len(), 480 * 2);
+        assert!(samples.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_source_kind_create() {
+        let kind = SourceKind::Synthetic {
+            signal: Signal::Silence,
+            duration: None,
+            speed: PlaybackSpeed::AsFastAsPossible,
+        };
+        let source = kind.create().unwrap();
+        assert_eq!(source.sample_rate(), AudioConfig::default().sample_rate);
+        assert!(source.device_id().is_none());
+        assert!(!source.is_stream_lost());
+    }
+}
@@ -0,0 +1,257 @@
+//! 频谱特征语音检测模块
+//!
+//! 纯能量检测会被键盘敲击、风扇等非语音声音触发。
+//! 这里对每个 VAD 帧计算语音频带 (300 - 3400 Hz) 能量、谱平坦度和过零率，
+//! 并跟踪语音频带的底噪，综合得到该帧的语音评分
+
+use super::noise_floor::NoiseFloorTracker;
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+
+/// 语音频带下限 (Hz)
+const BAND_LOW_HZ: f32 = 300.0;
+/// 语音频带上限 (Hz)
+const BAND_HIGH_HZ: f32 = 3400.0;
+/// 语音频带能量占比下限
+const MIN_BAND_RATIO: f32 = 0.5;
+/// 语音频带内谱平坦度上限 (浊音有谐波结构，平坦度低；白噪声、敲击接近 1)
+const MAX_FLATNESS: f32 = 0.4;
+/// 过零率范围 (每样本)，低于下限多为低频嗡声，高于上限多为宽带噪声
+const ZCR_RANGE: (f32, f32) = (0.02, 0.3);
+/// 语音频带能量需高出底噪的幅度 (VAD 能量刻度)
+const MIN_SNR_DB: f32 = 10.0;
+/// 语音频带底噪的估计窗口 (帧数，约 1.5 秒 @ 20ms/帧)
+const NOISE_WINDOW_FRAMES: usize = 75;
+/// 能量下限，避免数字静音得到负无穷
+const MIN_ENERGY: f32 = 1e-12;
+
+/// 单帧频谱特征
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralFeatures {
+    /// 整帧能量 (VAD 能量刻度，即均方值的 20·log10)
+    pub energy_db: f32,
+    /// 语音频带能量 (VAD 能量刻度)
+    pub band_energy_db: f32,
+    /// 语音频带能量占总能量的比例
+    pub band_ratio: f32,
+    /// 语音频带内的谱平坦度 (几何平均 / 算术平均，0.0 - 1.0)
+    pub flatness: f32,
+    /// 过零率 (每样本)
+    pub zero_crossing_rate: f32,
+}
+
+/// 频谱特征检测器
+///
+/// [`SpectralDetector::score`] 返回 0.0 - 1.0 的语音评分：
+/// 整帧能量超过绝对阈值且语音频带能量高出底噪时，
+/// 按频带占比、谱平坦度、过零率三项中满足的比例计分，否则为 0
+pub struct SpectralDetector {
+    sample_rate: u32,
+    /// (帧长, FFT)，帧长变化时重建
+    fft: Option<(usize, Arc<dyn RealToComplex<f32>>)>,
+    window: Vec<f32>,
+    time: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    /// 语音频带底噪
+    noise_floor: NoiseFloorTracker,
+}
+
+impl SpectralDetector {
+    /// 创建检测器
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 输入采样率
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1),
+            fft: None,
+            window: Vec::new(),
+            time: Vec::new(),
+            spectrum: Vec::new(),
+            scratch: Vec::new(),
+            noise_floor: NoiseFloorTracker::new(NOISE_WINDOW_FRAMES),
+        }
+    }
+
+    /// 当前底噪估计 (VAD 能量刻度)
+    pub fn noise_floor_db(&self) -> Option<f32> {
+        self.noise_floor.floor_db()
+    }
+
+    /// 计算一帧的频谱特征
+    pub fn analyze(&mut self, frame: &[f32]) -> SpectralFeatures {
+        let len = frame.len();
+        let mean_square = if len == 0 {
+            0.0
+        } else {
+            frame.iter().map(|&x| x * x).sum::<f32>() / len as f32
+        };
+        let zero_crossings = frame
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count();
+        let zero_crossing_rate = zero_crossings as f32 / len.saturating_sub(1).max(1) as f32;
+
+        let (band_ratio, flatness) = self.band_features(frame);
+        SpectralFeatures {
+            energy_db: to_db(mean_square),
+            band_energy_db: to_db(mean_square * band_ratio),
+            band_ratio,
+            flatness,
+            zero_crossing_rate,
+        }
+    }
+
+    /// 计算一帧的语音评分并更新底噪
+    ///
+    /// # Arguments
+    /// * `frame` - 单声道样本
+    /// * `threshold_db` - 整帧能量的绝对阈值 (VAD 能量刻度)
+    pub fn score(&mut self, frame: &[f32], threshold_db: f32) -> f32 {
+        let features = self.analyze(frame);
+        // 估计满一个窗口前，底噪不高于绝对阈值以下 MIN_SNR_DB，开头即为语音时也能检出
+        let floor = match self.noise_floor.floor_db() {
+            Some(floor) if self.noise_floor.is_settled() => floor,
+            Some(floor) => floor.min(threshold_db - MIN_SNR_DB),
+            None => threshold_db - MIN_SNR_DB,
+        };
+        self.noise_floor.update(10f32.powf(features.band_energy_db / 20.0));
+
+        let gate = features.energy_db > threshold_db
+            && features.band_energy_db > floor + MIN_SNR_DB;
+        if !gate {
+            return 0.0;
+        }
+        let votes = [
+            features.band_ratio >= MIN_BAND_RATIO,
+            features.flatness <= MAX_FLATNESS,
+            (ZCR_RANGE.0..=ZCR_RANGE.1).contains(&features.zero_crossing_rate),
+        ];
+        votes.iter().filter(|&&v| v).count() as f32 / votes.len() as f32
+    }
+
+    /// 重置底噪估计
+    pub fn reset(&mut self) {
+        self.noise_floor.reset();
+    }
+
+    /// 返回 (语音频带能量占比, 语音频带谱平坦度)
+    fn band_features(&mut self, frame: &[f32]) -> (f32, f32) {
+        let len = frame.len();
+        if len < 2 {
+            return (0.0, 1.0);
+        }
+        let fft_size = len.next_power_of_two();
+        if !matches!(self.fft, Some((l, _)) if l == len) {
+            let fft = RealFftPlanner::<f32>::new().plan_fft_forward(fft_size);
+            self.time = vec![0.0; fft_size];
+            self.spectrum = fft.make_output_vec();
+            self.scratch = fft.make_scratch_vec();
+            self.window = (0..len)
+                .map(|n| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * n as f32 / len as f32).cos())
+                .collect();
+            self.fft = Some((len, fft));
+        }
+        let Some((_, fft)) = self.fft.as_ref() else {
+            return (0.0, 1.0);
+        };
+
+        for ((t, &x), &w) in self.time.iter_mut().zip(frame).zip(&self.window) {
+            *t = x * w;
+        }
+        self.time[len..].fill(0.0);
+        if fft.process_with_scratch(&mut self.time, &mut self.spectrum, &mut self.scratch).is_err() {
+            return (0.0, 1.0);
+        }
+
+        let bin_hz = self.sample_rate as f32 / fft_size as f32;
+        let power: Vec<f32> = self.spectrum.iter().map(|c| c.norm_sqr()).collect();
+        // 不计直流分量
+        let total: f32 = power.iter().skip(1).sum();
+        let band: Vec<f32> = power
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| (BAND_LOW_HZ..=BAND_HIGH_HZ).contains(&(*k as f32 * bin_hz)))
+            .map(|(_, &p)| p)
+            .collect();
+        if total <= 0.0 || band.is_empty() {
+            return (0.0, 1.0);
+        }
+
+        let band_total: f32 = band.iter().sum();
+        let log_mean = band.iter().map(|&p| (p + MIN_ENERGY).ln()).sum::<f32>() / band.len() as f32;
+        let arith_mean = band_total / band.len() as f32 + MIN_ENERGY;
+        let flatness = (log_mean.exp() / arith_mean).clamp(0.0, 1.0);
+        (band_total / total, flatness)
+    }
+}
+
+impl std::fmt::Debug for SpectralDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpectralDetector")
+            .field("sample_rate", &self.sample_rate)
+            .field("noise_floor", &self.noise_floor)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 均方值转换为 VAD 能量刻度 (dB)
+fn to_db(mean_square: f32) -> f32 {
+    20.0 * mean_square.max(MIN_ENERGY).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::test_signals::{noise, tone};
+
+    #[test]
+    fn test_spectral_features_distinguish_tone_and_noise() {
+        let mut detector = SpectralDetector::new(16000);
+
+        let voiced = detector.analyze(&tone(1000.0, 0.3, 16000, 320));
+        assert!(voiced.band_ratio > 0.9);
+        assert!(voiced.flatness < 0.1);
+        assert!((voiced.zero_crossing_rate - 0.125).abs() < 0.01);
+
+        let white = detector.analyze(&noise(0.3, 320, 42));
+        assert!(white.band_ratio < 0.5, "{:?}", white);
+        assert!(white.flatness > MAX_FLATNESS, "{:?}", white);
+        assert!(white.zero_crossing_rate > ZCR_RANGE.1, "{:?}", white);
+
+        let hum = detector.analyze(&tone(120.0, 0.3, 16000, 320));
+        assert!(hum.band_ratio < MIN_BAND_RATIO);
+        assert!(hum.zero_crossing_rate < ZCR_RANGE.0);
+    }
+
+    #[test]
+    fn test_spectral_noise_floor_tracks_background() {
+        let mut detector = SpectralDetector::new(16000);
+        // 持续的频带内噪声使底噪上升，评分随之归零
+        let hiss = tone(1000.0, 0.1, 16000, 320);
+        assert_eq!(detector.score(&noise(0.001, 320, 7), -50.0), 0.0);
+        assert_eq!(detector.score(&hiss, -50.0), 1.0);
+        for _ in 0..250 {
+            detector.score(&hiss, -50.0);
+        }
+        assert_eq!(detector.score(&hiss, -50.0), 0.0);
+
+        // 底噪下降很快
+        for _ in 0..20 {
+            detector.score(&noise(0.001, 320, 9), -50.0);
+        }
+        assert_eq!(detector.score(&hiss, -50.0), 1.0);
+
+        detector.reset();
+        assert!(detector.noise_floor_db().is_none());
+    }
+
+    #[test]
+    fn test_spectral_detector_handles_short_frames() {
+        let mut detector = SpectralDetector::new(16000);
+        assert_eq!(detector.score(&[], -50.0), 0.0);
+        assert_eq!(detector.score(&[0.5], -50.0), 0.0);
+    }
+}
@@ -0,0 +1,19 @@
+//! 测试用信号
+//!
+//! 由 [`SyntheticSource`] 生成的单声道样本，供各模块测试共用
+
+use super::source::{PlaybackSpeed, Signal, SyntheticSource};
+
+/// 单声道正弦波
+pub fn tone(frequency: f32, amplitude: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+    generate(Signal::Tone { frequency, amplitude }, sample_rate, len)
+}
+
+/// 可复现的单声道白噪声
+pub fn noise(amplitude: f32, len: usize, seed: u64) -> Vec<f32> {
+    generate(Signal::WhiteNoise { amplitude, seed }, 16000, len)
+}
+
+fn generate(signal: Signal, sample_rate: u32, len: usize) -> Vec<f32> {
+    SyntheticSource::new(signal, sample_rate, 1, None, PlaybackSpeed::AsFastAsPossible).generate(len)
+}
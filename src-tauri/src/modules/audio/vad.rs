@@ -1,27 +1,64 @@
 //! 语音活动检测模块
 //!
-//! 使用能量检测进行语音活动识别
+//! 使用能量检测或频谱特征检测进行语音活动识别
 
+use super::noise_floor::NoiseFloorTracker;
+use super::spectral_vad::SpectralDetector;
 use std::f32::consts::LN_10;
 
-/// VAD 级别 (用于 WebSocket 配置)
+/// VAD 级别 (本地检测预设与 WebSocket 配置)，定义在配置模块以便持久化
+pub use crate::modules::config::VadLevel;
+
+/// VAD 检测方式
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum VadLevel {
-    /// 激进模式 - 更容易检测到语音，可能有更多误检
+pub enum VadMode {
+    /// 短时能量超过阈值即为语音
     #[default]
-    Aggressive,
-    /// 平衡模式 - 正常的检测灵敏度
-    Balanced,
-    /// 宽松模式 - 更难检测到语音，可能遗漏短暂的语音
-    Relaxed,
+    Energy,
+    /// 频谱特征 (语音频带能量、谱平坦度、过零率) 结合自适应底噪，
+    /// 不易被键盘敲击、风扇等非语音声音触发
+    Spectral,
+}
+
+/// 频谱模式下判为语音的平滑评分下限
+///
+/// 单帧评分满分时，平滑因子为默认值 0.3 需连续两帧才能超过，可滤除单帧的敲击声
+const SPECTRAL_SPEECH_SCORE: f32 = 0.5;
+
+/// 自适应阈值配置
+///
+/// 以最小统计持续估计背景底噪，语音阈值取底噪加上固定幅度，
+/// 同一配置在安静办公室与嘈杂咖啡馆中都能使用
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveThreshold {
+    /// 语音阈值高出底噪的幅度 (dB，与 `threshold_db` 同一刻度)
+    pub margin_db: f32,
+    /// 底噪估计窗口 (帧数)，应长于一般的连续发音时长
+    pub window_frames: usize,
+}
+
+impl Default for AdaptiveThreshold {
+    fn default() -> Self {
+        Self {
+            margin_db: 15.0,
+            window_frames: 75,  // 约 1.5s @ 20ms/帧
+        }
+    }
 }
 
 /// VAD 配置
 #[derive(Debug, Clone, Copy)]
 pub struct VadConfig {
+    /// 检测方式
+    pub mode: VadMode,
+    /// 输入采样率，频谱特征检测据此换算频带
+    pub sample_rate: u32,
     /// 语音检测阈值 (dB)，低于此值认为是静音
     /// 默认 -50 dB 适合安静环境，-40 dB 适合嘈杂环境
     pub threshold_db: f32,
+    /// 自适应阈值，None 表示使用固定的 `threshold_db`；
+    /// 启用时 `threshold_db` 作为阈值下限
+    pub adaptive: Option<AdaptiveThreshold>,
     /// 平滑因子 (0.0 - 1.0)，用于平滑能量计算
     /// 值越大平滑越强，但响应延迟越大
     pub smoothing_factor: f32,
@@ -34,7 +71,10 @@ pub struct VadConfig {
 impl Default for VadConfig {
     fn default() -> Self {
         Self {
+            mode: VadMode::Energy,
+            sample_rate: 16000,
             threshold_db: -50.0,
+            adaptive: None,
             smoothing_factor: 0.3,
             silence_timeout_frames: 15,  // 约 300ms @ 20ms/帧
             min_speech_frames: 3,        // 约 60ms
@@ -42,6 +82,39 @@ impl Default for VadConfig {
     }
 }
 
+impl VadConfig {
+    /// VAD 级别对应的预设
+    ///
+    /// 平衡模式与默认配置相同；宽松模式额外启用自适应阈值，背景噪声不易触发
+    pub fn from_level(level: VadLevel) -> Self {
+        match level {
+            VadLevel::Aggressive => Self {
+                threshold_db: -55.0,
+                smoothing_factor: 0.2,
+                silence_timeout_frames: 25,  // 约 500ms
+                min_speech_frames: 2,
+                ..Self::default()
+            },
+            VadLevel::Balanced => Self::default(),
+            VadLevel::Relaxed => Self {
+                threshold_db: -45.0,
+                adaptive: Some(AdaptiveThreshold::default()),
+                smoothing_factor: 0.4,
+                silence_timeout_frames: 10,  // 约 200ms
+                min_speech_frames: 5,        // 约 100ms
+                ..Self::default()
+            },
+        }
+    }
+
+    /// 语音阈值对应的帧 RMS 电平 (dBFS)
+    ///
+    /// `threshold_db` 按均方能量取 20·log10，数值是 RMS 电平的两倍
+    pub fn threshold_dbfs(&self) -> f32 {
+        self.threshold_db / 2.0
+    }
+}
+
 /// VAD 状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VadState {
@@ -55,12 +128,19 @@ pub enum VadState {
 
 /// 语音活动检测器
 ///
-/// 使用短时能量 (Short-Time Energy) 进行语音检测
+/// 默认使用短时能量 (Short-Time Energy) 进行语音检测，
+/// [`VadMode::Spectral`] 时按频谱特征评分判断，两种方式共用同一状态机
 #[derive(Debug)]
 pub struct VoiceActivityDetector {
     config: VadConfig,
     /// 平滑后的能量值 (线性)
     smoothed_energy: f32,
+    /// 底噪跟踪器，仅启用自适应阈值时存在
+    noise_floor: Option<NoiseFloorTracker>,
+    /// 频谱特征检测器，仅 [`VadMode::Spectral`] 时存在
+    spectral: Option<SpectralDetector>,
+    /// 平滑后的频谱语音评分
+    smoothed_score: f32,
     /// 连续静音帧数
     silence_frames: usize,
     /// 连续语音帧数
@@ -81,12 +161,35 @@ impl VoiceActivityDetector {
         Self {
             config,
             smoothed_energy: 0.0,
+            noise_floor: config.adaptive.map(|adaptive| NoiseFloorTracker::new(adaptive.window_frames)),
+            spectral: (config.mode == VadMode::Spectral).then(|| SpectralDetector::new(config.sample_rate)),
+            smoothed_score: 0.0,
             silence_frames: 0,
             speech_frames: 0,
             state: VadState::Silence,
         }
     }
 
+    /// 更新配置 (如切换 VAD 级别)，保留当前状态机状态
+    ///
+    /// 检测方式或自适应窗口变化时重建对应的检测器
+    pub fn set_config(&mut self, config: VadConfig) {
+        if config.mode != self.config.mode || config.sample_rate != self.config.sample_rate {
+            self.spectral = (config.mode == VadMode::Spectral).then(|| SpectralDetector::new(config.sample_rate));
+            self.smoothed_score = 0.0;
+        }
+        let window = |config: &VadConfig| config.adaptive.map(|adaptive| adaptive.window_frames);
+        if window(&config) != window(&self.config) {
+            self.noise_floor = config.adaptive.map(|adaptive| NoiseFloorTracker::new(adaptive.window_frames));
+        }
+        self.config = config;
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> &VadConfig {
+        &self.config
+    }
+
     /// 检测音频帧中是否包含语音
     ///
     /// # Arguments
@@ -97,6 +200,10 @@ impl VoiceActivityDetector {
     pub fn detect(&mut self, frame: &[f32]) -> VadState {
         // 计算当前帧的 RMS 能量
         let energy = self.calculate_energy(frame);
+        if let Some(noise_floor) = self.noise_floor.as_mut() {
+            noise_floor.update(energy);
+        }
+        let threshold_db = self.threshold_db();
 
         // 应用平滑 - 先保存旧值用于平滑计算
         let old_smoothed = self.smoothed_energy;
@@ -114,8 +221,20 @@ impl VoiceActivityDetector {
         // 转换为 dB
         let dbfs = self.energy_to_dbfs(detection_energy);
 
-        // 判断是否超过阈值
-        let is_speech = dbfs > self.config.threshold_db;
+        // 判断是否超过阈值，频谱模式下按平滑后的语音评分判断
+        let is_speech = match self.spectral.as_mut() {
+            Some(spectral) => {
+                let score = spectral.score(frame, threshold_db);
+                self.smoothed_score = if self.config.smoothing_factor > 0.0 {
+                    self.config.smoothing_factor * score
+                        + (1.0 - self.config.smoothing_factor) * self.smoothed_score
+                } else {
+                    score
+                };
+                self.smoothed_score >= SPECTRAL_SPEECH_SCORE
+            }
+            None => dbfs > threshold_db,
+        };
 
         // 状态机更新
         match self.state {
@@ -178,6 +297,13 @@ impl VoiceActivityDetector {
     /// 重置 VAD 状态
     pub fn reset(&mut self) {
         self.smoothed_energy = 0.0;
+        self.smoothed_score = 0.0;
+        if let Some(noise_floor) = self.noise_floor.as_mut() {
+            noise_floor.reset();
+        }
+        if let Some(spectral) = self.spectral.as_mut() {
+            spectral.reset();
+        }
         self.silence_frames = 0;
         self.speech_frames = 0;
         self.state = VadState::Silence;
@@ -193,6 +319,24 @@ impl VoiceActivityDetector {
         self.energy_to_dbfs(self.smoothed_energy)
     }
 
+    /// 当前生效的语音阈值 (dB)
+    ///
+    /// 自适应阈值在底噪估计满一个窗口后生效，此前使用 `threshold_db`
+    pub fn threshold_db(&self) -> f32 {
+        match (self.config.adaptive, self.noise_floor_db()) {
+            (Some(adaptive), Some(floor)) => (floor + adaptive.margin_db).max(self.config.threshold_db),
+            _ => self.config.threshold_db,
+        }
+    }
+
+    /// 背景底噪估计 (dB)，未启用自适应阈值或估计未满一个窗口时返回 None
+    pub fn noise_floor_db(&self) -> Option<f32> {
+        self.noise_floor
+            .as_ref()
+            .filter(|tracker| tracker.is_settled())
+            .and_then(|tracker| tracker.floor_db())
+    }
+
     /// 检查是否正在语音中
     pub fn is_speaking(&self) -> bool {
         matches!(self.state, VadState::Speech)
@@ -207,6 +351,8 @@ impl VoiceActivityDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::modules::audio::test_signals::noise;
+    use std::f32::consts::PI;
 
     #[test]
     fn test_vad_silence_detection() {
@@ -243,6 +389,7 @@ mod tests {
             silence_timeout_frames: 2,
             min_speech_frames: 1,
             smoothing_factor: 0.0,
+            ..Default::default()
         };
         let mut vad = VoiceActivityDetector::new(config);
 
@@ -296,4 +443,267 @@ mod tests {
         // RMS of constant 0.5 = 0.5
         assert!((energy - 0.25).abs() < 0.0001);
     }
+
+    /// 标注样本的类别
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Label {
+        Background,
+        Speech,
+        /// 键盘敲击、风扇等非语音声音
+        Noise,
+    }
+
+    const RATE: f32 = 16000.0;
+
+    /// 按样本数生成一段夹具信号
+    type Generator = fn(usize) -> Vec<f32>;
+
+    /// 类语音信号：150Hz 基频的谐波经两个共振峰加权，并按 4Hz 音节节奏调幅
+    fn voiced(len: usize) -> Vec<f32> {
+        let formant = |f: f32| {
+            (-((f - 600.0) / 150.0).powi(2)).exp() + 0.5 * (-((f - 1400.0) / 250.0).powi(2)).exp()
+        };
+        (0..len)
+            .map(|n| {
+                let t = n as f32 / RATE;
+                let envelope = 0.75 + 0.25 * (2.0 * PI * 4.0 * t).sin();
+                let sum: f32 = (1..=24)
+                    .map(|k| k as f32 * 150.0)
+                    .map(|f| formant(f) * (2.0 * PI * f * t).sin())
+                    .sum();
+                0.2 * envelope * sum
+            })
+            .collect()
+    }
+
+    /// 键盘敲击：每 120ms 一次快速衰减的宽带脉冲
+    fn clicks(len: usize) -> Vec<f32> {
+        let burst = noise(1.0, 100, 3);
+        (0..len)
+            .map(|n| {
+                let offset = n % 1920;
+                burst.get(offset).map_or(0.0, |b| b * (-(offset as f32) / 30.0).exp())
+            })
+            .collect()
+    }
+
+    /// 风扇：100Hz 嗡声及其谐波叠加宽带噪声
+    fn fan(len: usize) -> Vec<f32> {
+        let hiss = noise(0.04, len, 5);
+        (0..len)
+            .map(|n| {
+                let t = n as f32 / RATE;
+                0.15 * (2.0 * PI * 100.0 * t).sin() + 0.05 * (2.0 * PI * 200.0 * t).sin() + hiss[n]
+            })
+            .collect()
+    }
+
+    /// 标注夹具：背景噪声上依次叠加语音、敲击声和风扇声
+    fn labeled_fixture() -> (Vec<f32>, Vec<Label>) {
+        let segments: [(Label, f32, Generator); 11] = [
+            (Label::Background, 1.0, |len| vec![0.0; len]),
+            (Label::Speech, 1.0, voiced),
+            (Label::Background, 1.0, |len| vec![0.0; len]),
+            (Label::Noise, 1.5, clicks),
+            (Label::Background, 0.5, |len| vec![0.0; len]),
+            (Label::Speech, 1.0, voiced),
+            (Label::Background, 1.0, |len| vec![0.0; len]),
+            (Label::Noise, 1.5, fan),
+            (Label::Background, 0.5, |len| vec![0.0; len]),
+            (Label::Speech, 1.0, voiced),
+            (Label::Background, 0.5, |len| vec![0.0; len]),
+        ];
+        let mut samples = Vec::new();
+        let mut labels = Vec::new();
+        for (label, seconds, generate) in segments {
+            let len = (seconds * RATE) as usize;
+            samples.extend(generate(len));
+            labels.extend(std::iter::repeat_n(label, len));
+        }
+        for (sample, background) in samples.iter_mut().zip(noise(0.001, labels.len(), 11)) {
+            *sample += background;
+        }
+        (samples, labels)
+    }
+
+    /// 返回 (语音帧召回率, 非语音声音的误检率)
+    fn evaluate(mode: VadMode) -> (f32, f32) {
+        let (samples, labels) = labeled_fixture();
+        let mut vad = VoiceActivityDetector::new(VadConfig { mode, ..Default::default() });
+        let (mut speech, mut detected, mut noise, mut false_alarms) = (0, 0, 0, 0);
+        for (frame, frame_labels) in samples.chunks_exact(320).zip(labels.chunks_exact(320)) {
+            let active = vad.detect(frame) != VadState::Silence;
+            match frame_labels[0] {
+                Label::Speech => {
+                    speech += 1;
+                    detected += active as usize;
+                }
+                Label::Noise => {
+                    noise += 1;
+                    false_alarms += active as usize;
+                }
+                Label::Background => {}
+            }
+        }
+        (detected as f32 / speech as f32, false_alarms as f32 / noise as f32)
+    }
+
+    #[test]
+    fn test_spectral_vad_rejects_non_speech_noise() {
+        let (energy_recall, energy_false_alarm) = evaluate(VadMode::Energy);
+        let (spectral_recall, spectral_false_alarm) = evaluate(VadMode::Spectral);
+
+        // 能量检测把敲击声和风扇声当成语音
+        assert!(energy_recall > 0.95, "energy recall {}", energy_recall);
+        assert!(energy_false_alarm > 0.8, "energy false alarm {}", energy_false_alarm);
+        // 频谱检测同样检出语音，但几乎不被非语音声音触发
+        assert!(spectral_recall > 0.9, "spectral recall {}", spectral_recall);
+        assert!(spectral_false_alarm < 0.05, "spectral false alarm {}", spectral_false_alarm);
+    }
+
+    #[test]
+    fn test_spectral_vad_reset() {
+        let mut vad = VoiceActivityDetector::new(VadConfig { mode: VadMode::Spectral, ..Default::default() });
+        let speech = voiced(3200);
+        for frame in speech.chunks_exact(320) {
+            vad.detect(frame);
+        }
+        assert!(vad.is_speaking());
+        vad.reset();
+        assert_eq!(vad.state(), VadState::Silence);
+    }
+
+    /// 在指定背景噪声下说话，返回 (背景噪声帧的误检率, 语音帧召回率)
+    ///
+    /// 前 2 秒只有背景噪声，其后语音与背景噪声各 1 秒交替两次，
+    /// 只统计第 2 秒之后的帧，并跳过语音结束后 0.5 秒 (能量平滑的拖尾与静音超时)
+    fn evaluate_environment(config: VadConfig, noise_amplitude: f32, speech_amplitude: f32) -> (f32, f32) {
+        let mut vad = VoiceActivityDetector::new(config);
+        let background = noise(noise_amplitude, 16000 * 6, 13);
+        let (mut noise_frames, mut false_alarms, mut speech_frames, mut detected) = (0, 0, 0, 0);
+        for (index, frame) in background.chunks_exact(320).enumerate() {
+            let second = index / 50;
+            let is_speech = second == 2 || second == 4;
+            let frame: Vec<f32> = frame
+                .iter()
+                .enumerate()
+                .map(|(n, &b)| {
+                    let t = (index * 320 + n) as f32 / RATE;
+                    let tone = speech_amplitude * (2.0 * PI * 500.0 * t).sin();
+                    b + if is_speech { tone } else { 0.0 }
+                })
+                .collect();
+            let active = vad.detect(&frame) != VadState::Silence;
+            if second < 2 {
+                continue;
+            }
+            if is_speech {
+                speech_frames += 1;
+                detected += active as usize;
+            } else if index % 50 >= 25 {
+                noise_frames += 1;
+                false_alarms += active as usize;
+            }
+        }
+        (false_alarms as f32 / noise_frames as f32, detected as f32 / speech_frames as f32)
+    }
+
+    #[test]
+    fn test_adaptive_threshold_works_in_quiet_and_noisy_rooms() {
+        let adaptive = VadConfig {
+            threshold_db: -60.0,
+            adaptive: Some(AdaptiveThreshold::default()),
+            ..Default::default()
+        };
+        // 安静办公室
+        let (false_alarm, recall) = evaluate_environment(adaptive, 0.001, 0.2);
+        assert_eq!(false_alarm, 0.0);
+        assert!(recall > 0.95, "{}", recall);
+        // 嘈杂咖啡馆 (底噪约 -42dB)，说话声也更大
+        let (false_alarm, recall) = evaluate_environment(adaptive, 0.15, 0.6);
+        assert_eq!(false_alarm, 0.0);
+        assert!(recall > 0.95, "{}", recall);
+
+        // 同一固定阈值在咖啡馆中把背景噪声当成语音
+        let fixed = VadConfig { threshold_db: -60.0, ..Default::default() };
+        let (false_alarm, _) = evaluate_environment(fixed, 0.15, 0.6);
+        assert_eq!(false_alarm, 1.0);
+    }
+
+    #[test]
+    fn test_adaptive_threshold_tracks_noise_floor() {
+        let config = VadConfig {
+            threshold_db: -60.0,
+            adaptive: Some(AdaptiveThreshold { margin_db: 10.0, window_frames: 20 }),
+            ..Default::default()
+        };
+        let mut vad = VoiceActivityDetector::new(config);
+        assert_eq!(vad.threshold_db(), -60.0);
+        assert!(vad.noise_floor_db().is_none());
+
+        // 底噪远低于下限时使用 threshold_db
+        for _ in 0..20 {
+            vad.detect(&vec![0.001; 320]);
+        }
+        assert_eq!(vad.threshold_db(), -60.0);
+
+        // 底噪 -40dB (均方 0.01)，经最小统计的偏差补偿 (×1.5，约 +3.5dB) 估计为约 -36.5dB，
+        // 阈值为底噪加 10dB，约 -26.5dB
+        for _ in 0..40 {
+            vad.detect(&vec![0.1; 320]);
+        }
+        let floor = vad.noise_floor_db().unwrap();
+        assert!((floor - -36.5).abs() < 0.5, "{}", floor);
+        assert!((vad.threshold_db() - (floor + 10.0)).abs() < 1e-4);
+
+        vad.reset();
+        assert!(vad.noise_floor_db().is_none());
+        assert_eq!(vad.threshold_db(), -60.0);
+    }
+
+    #[test]
+    fn test_vad_config_from_level() {
+        let aggressive = VadConfig::from_level(VadLevel::Aggressive);
+        let balanced = VadConfig::from_level(VadLevel::Balanced);
+        let relaxed = VadConfig::from_level(VadLevel::Relaxed);
+
+        // 激进模式阈值更低、结束更慢，宽松模式相反
+        assert!(aggressive.threshold_db < balanced.threshold_db);
+        assert!(balanced.threshold_db < relaxed.threshold_db);
+        assert!(aggressive.silence_timeout_frames > relaxed.silence_timeout_frames);
+        assert!(aggressive.min_speech_frames < relaxed.min_speech_frames);
+        assert_eq!(balanced.threshold_db, VadConfig::default().threshold_db);
+        assert!(relaxed.adaptive.is_some());
+
+        // 激进模式检出平衡模式漏掉的轻声 (约 -52dB)
+        let quiet = vec![0.05; 320];
+        let mut aggressive = VoiceActivityDetector::new(aggressive);
+        let mut balanced = VoiceActivityDetector::new(balanced);
+        for _ in 0..30 {
+            aggressive.detect(&quiet);
+            balanced.detect(&quiet);
+        }
+        assert!(aggressive.is_speaking());
+        assert_eq!(balanced.state(), VadState::Silence);
+    }
+
+    #[test]
+    fn test_vad_set_config_keeps_state() {
+        let mut vad = VoiceActivityDetector::new(VadConfig::from_level(VadLevel::Balanced));
+        vad.detect(&[0.5; 320]);
+        assert!(vad.is_speaking());
+
+        vad.set_config(VadConfig {
+            smoothing_factor: 0.0,
+            silence_timeout_frames: 2,
+            min_speech_frames: 1,
+            ..VadConfig::from_level(VadLevel::Relaxed)
+        });
+        assert!(vad.is_speaking());
+        assert_eq!(vad.config().threshold_db, -45.0);
+
+        // 新的静音超时生效
+        assert_eq!(vad.detect(&[0.0; 320]), VadState::Speech);
+        assert_eq!(vad.detect(&[0.0; 320]), VadState::Ending);
+    }
 }
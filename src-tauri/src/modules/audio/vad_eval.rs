@@ -0,0 +1,543 @@
+//! VAD 离线评估模块
+//!
+//! 在带标注的 WAV 夹具上运行 [`VoiceActivityDetector`]，按帧统计精确率、召回率、
+//! 误检率、漏检时长和起止延迟，用于判断 [`VadConfig`] 的改动是否有效，
+//! 并支持在参数网格上批量评估。
+//!
+//! 标注文件与 WAV 同名、扩展名为 `.txt`，使用 Audacity 标签格式：
+//! 每行 `开始秒<TAB>结束秒[<TAB>标签]`，列出的区间为语音。
+//!
+//! 只在测试中编译，不进入发布构建。对本地夹具目录运行评估：
+//! `VAD_EVAL_DIR=<目录> cargo test vad_eval -- --ignored --nocapture`，
+//! 同时设置 `VAD_EVAL_SWEEP=1` 时进行参数网格评估
+
+use super::capture::AudioFrame;
+use super::source::{AudioSource, FileSource, PlaybackSpeed};
+use super::vad::{VadConfig, VadMode, VadState, VoiceActivityDetector};
+use crate::error::AudioError;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// 评估的 VAD 帧长 (毫秒)
+pub const EVAL_FRAME_MS: u32 = 20;
+
+/// 夹具读取错误
+#[derive(Debug, Error)]
+pub enum EvalError {
+    /// 标注行格式错误
+    #[error("Invalid label on line {line}: {text}")]
+    InvalidLabel { line: usize, text: String },
+
+    /// 读取文件或目录失败
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+
+    /// 解码 WAV 失败
+    #[error(transparent)]
+    Audio(#[from] AudioError),
+}
+
+/// 语音区间标注
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpeechLabels {
+    /// 按开始时间排序的 (开始秒, 结束秒)
+    intervals: Vec<(f64, f64)>,
+}
+
+impl SpeechLabels {
+    /// 解析 Audacity 标签文本，忽略空行与 `#` 开头的注释
+    pub fn parse(text: &str) -> Result<Self, EvalError> {
+        let mut intervals = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || EvalError::InvalidLabel { line: index + 1, text: line.to_string() };
+            let mut fields = line.split_whitespace();
+            let mut time = || fields.next().and_then(|f| f.parse::<f64>().ok()).ok_or_else(invalid);
+            let (start, end) = (time()?, time()?);
+            if !(start >= 0.0 && end >= start) {
+                return Err(invalid());
+            }
+            intervals.push((start, end));
+        }
+        intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { intervals })
+    }
+
+    /// 读取标注文件
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, EvalError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|source| EvalError::Io { path: path.to_path_buf(), source })?;
+        Self::parse(&text)
+    }
+
+    /// 由语音区间创建
+    pub fn from_intervals(mut intervals: Vec<(f64, f64)>) -> Self {
+        intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { intervals }
+    }
+
+    /// 语音区间 (秒)
+    pub fn intervals(&self) -> &[(f64, f64)] {
+        &self.intervals
+    }
+
+    /// 指定时刻是否为语音
+    pub fn is_speech(&self, seconds: f64) -> bool {
+        self.intervals.iter().any(|&(start, end)| (start..end).contains(&seconds))
+    }
+}
+
+/// 带标注的评估夹具
+#[derive(Debug, Clone)]
+pub struct VadFixture {
+    /// 夹具名称 (文件名)
+    pub name: String,
+    /// 单声道样本
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub labels: SpeechLabels,
+}
+
+impl VadFixture {
+    /// 读取 WAV 夹具及同名的 `.txt` 标注，多声道音频混合为单声道
+    pub fn load(wav_path: impl AsRef<Path>) -> Result<Self, EvalError> {
+        let wav_path = wav_path.as_ref();
+        let labels = SpeechLabels::from_file(wav_path.with_extension("txt"))?;
+        let mut source = FileSource::open_wav(wav_path, PlaybackSpeed::AsFastAsPossible)?;
+        source.start()?;
+        let frame = source
+            .read_frame(source.remaining())
+            .unwrap_or_else(|| AudioFrame::new(Vec::new(), source.sample_rate(), 1, 0))
+            .to_mono();
+        Ok(Self {
+            name: wav_path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+            samples: frame.samples,
+            sample_rate: frame.sample_rate,
+            labels,
+        })
+    }
+
+    /// 读取目录下所有带标注的 WAV 夹具 (按文件名排序)
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<Self>, EvalError> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|source| EvalError::Io { path: dir.to_path_buf(), source })?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")))
+            .filter(|path| path.with_extension("txt").exists())
+            .collect();
+        paths.sort();
+        paths.iter().map(Self::load).collect()
+    }
+}
+
+/// 帧级评估指标
+///
+/// 保存原始计数，多个夹具的结果可用 [`VadMetrics::merge`] 合并
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VadMetrics {
+    /// 帧长 (毫秒)
+    pub frame_ms: u32,
+    /// 标注为语音且检出的帧数
+    pub true_positives: usize,
+    /// 标注为非语音但检出的帧数
+    pub false_positives: usize,
+    /// 标注为语音但未检出的帧数
+    pub false_negatives: usize,
+    /// 标注为非语音且未检出的帧数
+    pub true_negatives: usize,
+    /// 标注的语音段数
+    pub segments: usize,
+    /// 完全未检出的语音段数
+    pub missed_segments: usize,
+    /// 检出语音段的起始延迟之和 (帧)
+    onset_frames: usize,
+    /// 检出语音段的结束延迟之和 (帧)
+    offset_frames: usize,
+}
+
+impl VadMetrics {
+    /// 由逐帧的标注与检测结果计算
+    ///
+    /// 起始延迟为语音段开始到首次检出的时间，结束延迟为语音段结束到检测回到静音的时间
+    /// (最多到下一语音段开始)，均只统计检出的语音段
+    pub fn from_frames(reference: &[bool], detected: &[bool], frame_ms: u32) -> Self {
+        let mut metrics = Self { frame_ms, ..Default::default() };
+        for (&expected, &active) in reference.iter().zip(detected) {
+            match (expected, active) {
+                (true, true) => metrics.true_positives += 1,
+                (false, true) => metrics.false_positives += 1,
+                (true, false) => metrics.false_negatives += 1,
+                (false, false) => metrics.true_negatives += 1,
+            }
+        }
+
+        let len = reference.len().min(detected.len());
+        let segments = speech_runs(&reference[..len]);
+        for (i, &(start, end)) in segments.iter().enumerate() {
+            metrics.segments += 1;
+            let Some(onset) = (start..end).find(|&f| detected[f]) else {
+                metrics.missed_segments += 1;
+                continue;
+            };
+            metrics.onset_frames += onset - start;
+            let limit = segments.get(i + 1).map_or(len, |next| next.0);
+            let release = (end..limit).find(|&f| !detected[f]).unwrap_or(limit);
+            metrics.offset_frames += release - end;
+        }
+        metrics
+    }
+
+    /// 合并另一组指标 (帧长需一致)
+    pub fn merge(&mut self, other: &VadMetrics) {
+        if self.frame_ms == 0 {
+            self.frame_ms = other.frame_ms;
+        }
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.false_negatives += other.false_negatives;
+        self.true_negatives += other.true_negatives;
+        self.segments += other.segments;
+        self.missed_segments += other.missed_segments;
+        self.onset_frames += other.onset_frames;
+        self.offset_frames += other.offset_frames;
+    }
+
+    /// 精确率：检出帧中真正为语音的比例
+    pub fn precision(&self) -> f32 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    /// 召回率：语音帧中被检出的比例
+    pub fn recall(&self) -> f32 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    /// F1 分数
+    pub fn f1(&self) -> f32 {
+        let (precision, recall) = (self.precision(), self.recall());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+
+    /// 误检率：非语音帧中被检出的比例
+    pub fn false_alarm_rate(&self) -> f32 {
+        ratio(self.false_positives, self.false_positives + self.true_negatives)
+    }
+
+    /// 漏检的语音时长 (毫秒)
+    pub fn missed_speech_ms(&self) -> u64 {
+        self.false_negatives as u64 * self.frame_ms as u64
+    }
+
+    /// 平均起始延迟 (毫秒)，没有检出的语音段时为 None
+    pub fn onset_latency_ms(&self) -> Option<f32> {
+        self.mean_latency_ms(self.onset_frames)
+    }
+
+    /// 平均结束延迟 (毫秒)，没有检出的语音段时为 None
+    pub fn offset_latency_ms(&self) -> Option<f32> {
+        self.mean_latency_ms(self.offset_frames)
+    }
+
+    fn mean_latency_ms(&self, total_frames: usize) -> Option<f32> {
+        let detected = self.segments - self.missed_segments;
+        (detected > 0).then(|| (total_frames * self.frame_ms as usize) as f32 / detected as f32)
+    }
+}
+
+impl fmt::Display for VadMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let latency = |value: Option<f32>| value.map_or_else(|| "-".to_string(), |v| format!("{:.0}ms", v));
+        write!(
+            f,
+            "precision {:.3}  recall {:.3}  f1 {:.3}  false alarm {:.3}  missed {}ms  onset {}  offset {}  segments {}/{}",
+            self.precision(),
+            self.recall(),
+            self.f1(),
+            self.false_alarm_rate(),
+            self.missed_speech_ms(),
+            latency(self.onset_latency_ms()),
+            latency(self.offset_latency_ms()),
+            self.segments - self.missed_segments,
+            self.segments,
+        )
+    }
+}
+
+/// 在单段音频上评估 VAD 配置
+///
+/// 按 [`EVAL_FRAME_MS`] 分帧，以帧中点的标注作为该帧的参考结果，
+/// VAD 状态不为 `Silence` (含静音超时内的拖尾) 即视为检出
+pub fn evaluate(samples: &[f32], sample_rate: u32, labels: &SpeechLabels, config: VadConfig) -> VadMetrics {
+    let mut vad = VoiceActivityDetector::new(VadConfig { sample_rate, ..config });
+    let frame_size = (sample_rate * EVAL_FRAME_MS / 1000).max(1) as usize;
+    let (reference, detected): (Vec<bool>, Vec<bool>) = samples
+        .chunks_exact(frame_size)
+        .enumerate()
+        .map(|(index, frame)| {
+            let center = (index as f64 + 0.5) * frame_size as f64 / sample_rate as f64;
+            (labels.is_speech(center), vad.detect(frame) != VadState::Silence)
+        })
+        .unzip();
+    VadMetrics::from_frames(&reference, &detected, EVAL_FRAME_MS)
+}
+
+/// 在多个夹具上评估同一配置，返回合并后的指标
+pub fn evaluate_fixtures(fixtures: &[VadFixture], config: VadConfig) -> VadMetrics {
+    let mut metrics = VadMetrics { frame_ms: EVAL_FRAME_MS, ..Default::default() };
+    for fixture in fixtures {
+        metrics.merge(&evaluate(&fixture.samples, fixture.sample_rate, &fixture.labels, config));
+    }
+    metrics
+}
+
+/// 参数网格，为空的维度沿用基准配置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParameterSweep {
+    pub modes: Vec<VadMode>,
+    pub threshold_db: Vec<f32>,
+    pub smoothing_factor: Vec<f32>,
+    pub silence_timeout_frames: Vec<usize>,
+}
+
+impl ParameterSweep {
+    /// 常用的参数网格
+    pub fn standard() -> Self {
+        Self {
+            modes: vec![VadMode::Energy, VadMode::Spectral],
+            threshold_db: vec![-60.0, -55.0, -50.0, -45.0, -40.0],
+            smoothing_factor: vec![0.2, 0.3, 0.5],
+            silence_timeout_frames: vec![10, 15, 25],
+        }
+    }
+
+    /// 展开为配置列表 (笛卡尔积)
+    pub fn configs(&self, base: VadConfig) -> Vec<VadConfig> {
+        fn or_base<T: Copy>(values: &[T], base: T) -> Vec<T> {
+            if values.is_empty() { vec![base] } else { values.to_vec() }
+        }
+        let mut configs = Vec::new();
+        for mode in or_base(&self.modes, base.mode) {
+            for threshold_db in or_base(&self.threshold_db, base.threshold_db) {
+                for smoothing_factor in or_base(&self.smoothing_factor, base.smoothing_factor) {
+                    for silence_timeout_frames in or_base(&self.silence_timeout_frames, base.silence_timeout_frames) {
+                        configs.push(VadConfig {
+                            mode,
+                            threshold_db,
+                            smoothing_factor,
+                            silence_timeout_frames,
+                            ..base
+                        });
+                    }
+                }
+            }
+        }
+        configs
+    }
+
+    /// 在夹具上评估网格中的每个配置，按 F1 分数从高到低排序
+    pub fn run(&self, fixtures: &[VadFixture], base: VadConfig) -> Vec<(VadConfig, VadMetrics)> {
+        let mut results: Vec<(VadConfig, VadMetrics)> = self
+            .configs(base)
+            .into_iter()
+            .map(|config| (config, evaluate_fixtures(fixtures, config)))
+            .collect();
+        results.sort_by(|a, b| b.1.f1().total_cmp(&a.1.f1()));
+        results
+    }
+}
+
+/// 配置的简短描述，用于评估报告
+pub fn describe_config(config: &VadConfig) -> String {
+    format!(
+        "{:?} threshold {}dB smoothing {} timeout {} min speech {}{}",
+        config.mode,
+        config.threshold_db,
+        config.smoothing_factor,
+        config.silence_timeout_frames,
+        config.min_speech_frames,
+        if config.adaptive.is_some() { " adaptive" } else { "" },
+    )
+}
+
+/// 连续为 true 的区间 [开始, 结束)
+fn speech_runs(frames: &[bool]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (index, &speech) in frames.iter().enumerate() {
+        match (speech, start) {
+            (true, None) => start = Some(index),
+            (false, Some(s)) => {
+                runs.push((s, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        runs.push((s, frames.len()));
+    }
+    runs
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f32 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::audio::VadLevel;
+    use std::f32::consts::PI;
+    use tempfile::tempdir;
+
+    const RATE: u32 = 16000;
+
+    /// 1 秒静音、1 秒 500Hz 音调交替，共 5 秒
+    fn fixture_samples() -> Vec<f32> {
+        (0..RATE as usize * 5)
+            .map(|n| {
+                let t = n as f32 / RATE as f32;
+                if (t as usize) % 2 == 1 { 0.2 * (2.0 * PI * 500.0 * t).sin() } else { 0.0 }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_speech_labels_parse() {
+        let labels = SpeechLabels::parse("# 注释\n1.0\t2.0\tspeech\n\n0.25 0.5\n").unwrap();
+        assert_eq!(labels.intervals(), &[(0.25, 0.5), (1.0, 2.0)]);
+        assert!(labels.is_speech(1.5));
+        assert!(!labels.is_speech(2.0));
+
+        assert!(matches!(SpeechLabels::parse("1.0"), Err(EvalError::InvalidLabel { line: 1, .. })));
+        assert!(SpeechLabels::parse("2.0\t1.0").is_err());
+        assert!(matches!(SpeechLabels::from_file("missing.txt"), Err(EvalError::Io { .. })));
+    }
+
+    #[test]
+    fn test_metrics_from_frames() {
+        // 两个语音段：第一段晚 2 帧检出、拖尾 3 帧，第二段未检出
+        let reference = [false, true, true, true, true, false, false, false, false, true, true, false];
+        let detected = [true, false, false, true, true, true, true, true, false, false, false, false];
+        let metrics = VadMetrics::from_frames(&reference, &detected, 20);
+        assert_eq!(metrics.true_positives, 2);
+        assert_eq!(metrics.false_positives, 4);
+        assert_eq!(metrics.false_negatives, 4);
+        assert_eq!(metrics.true_negatives, 2);
+        assert!((metrics.precision() - 2.0 / 6.0).abs() < 1e-6);
+        assert!((metrics.recall() - 2.0 / 6.0).abs() < 1e-6);
+        assert!((metrics.false_alarm_rate() - 4.0 / 6.0).abs() < 1e-6);
+        assert_eq!(metrics.missed_speech_ms(), 80);
+        assert_eq!(metrics.segments, 2);
+        assert_eq!(metrics.missed_segments, 1);
+        assert_eq!(metrics.onset_latency_ms(), Some(40.0));
+        assert_eq!(metrics.offset_latency_ms(), Some(60.0));
+
+        let mut merged = metrics;
+        merged.merge(&metrics);
+        assert_eq!(merged.segments, 4);
+        assert_eq!(merged.onset_latency_ms(), Some(40.0));
+        assert!((merged.precision() - metrics.precision()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_wav_fixture() {
+        let dir = tempdir().unwrap();
+        let wav_path = dir.path().join("tones.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&wav_path, spec).unwrap();
+        for sample in fixture_samples() {
+            writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        std::fs::write(dir.path().join("tones.txt"), "1.0\t2.0\tspeech\n3.0\t4.0\tspeech\n").unwrap();
+        // 没有标注的 WAV 不参与评估
+        std::fs::copy(&wav_path, dir.path().join("unlabeled.wav")).unwrap();
+
+        let fixtures = VadFixture::load_dir(dir.path()).unwrap();
+        assert_eq!(fixtures.len(), 1);
+        assert_eq!(fixtures[0].name, "tones.wav");
+        assert_eq!(fixtures[0].sample_rate, RATE);
+
+        let metrics = evaluate_fixtures(&fixtures, VadConfig::default());
+        assert_eq!(metrics.segments, 2);
+        assert_eq!(metrics.missed_segments, 0);
+        assert!(metrics.recall() > 0.95, "{}", metrics);
+        assert_eq!(metrics.onset_latency_ms(), Some(0.0));
+        // 结束延迟为能量平滑的拖尾加上静音超时
+        let offset = metrics.offset_latency_ms().unwrap();
+        let timeout_ms = (VadConfig::default().silence_timeout_frames as u32 * EVAL_FRAME_MS) as f32;
+        assert!(offset >= timeout_ms && offset <= timeout_ms + 200.0, "{}", metrics);
+    }
+
+    #[test]
+    fn test_parameter_sweep() {
+        let sweep = ParameterSweep {
+            threshold_db: vec![-50.0, -10.0],
+            silence_timeout_frames: vec![5, 15],
+            ..Default::default()
+        };
+        let base = VadConfig::from_level(VadLevel::Balanced);
+        let configs = sweep.configs(base);
+        assert_eq!(configs.len(), 4);
+        assert!(configs.iter().all(|c| c.mode == base.mode && c.smoothing_factor == base.smoothing_factor));
+
+        let fixture = VadFixture {
+            name: "tones".to_string(),
+            samples: fixture_samples(),
+            sample_rate: RATE,
+            labels: SpeechLabels::from_intervals(vec![(1.0, 2.0), (3.0, 4.0)]),
+        };
+        let results = sweep.run(std::slice::from_ref(&fixture), base);
+        // 阈值过高时检不出语音，排在最后
+        assert_eq!(results[0].0.threshold_db, -50.0);
+        assert_eq!(results.last().unwrap().1.recall(), 0.0);
+        assert!(results[0].1.f1() >= results[1].1.f1());
+    }
+
+    /// 对 `VAD_EVAL_DIR` 下的夹具评估各 VAD 级别，设置 `VAD_EVAL_SWEEP` 时进行参数网格评估
+    #[test]
+    #[ignore = "requires VAD_EVAL_DIR with labeled WAV fixtures"]
+    fn vad_eval_fixtures() {
+        let dir = std::env::var("VAD_EVAL_DIR").expect("VAD_EVAL_DIR is not set");
+        let fixtures = VadFixture::load_dir(&dir).unwrap();
+        assert!(!fixtures.is_empty(), "no labeled WAV fixtures in {}", dir);
+
+        for level in [VadLevel::Aggressive, VadLevel::Balanced, VadLevel::Relaxed] {
+            let config = VadConfig::from_level(level);
+            println!("{:?}: {}", level, describe_config(&config));
+            for fixture in &fixtures {
+                let metrics = evaluate(&fixture.samples, fixture.sample_rate, &fixture.labels, config);
+                println!("  {:<32} {}", fixture.name, metrics);
+            }
+            println!("  {:<32} {}", "total", evaluate_fixtures(&fixtures, config));
+        }
+
+        if std::env::var_os("VAD_EVAL_SWEEP").is_some() {
+            println!("Parameter sweep (best first):");
+            for (config, metrics) in ParameterSweep::standard().run(&fixtures, VadConfig::default()) {
+                println!("  {:<60} {}", describe_config(&config), metrics);
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! 音频设备热插拔监听
+//!
+//! 周期性轮询输入设备列表 (仅名称与 ID)，设备增减时重新查询设备能力、
+//! 通知前端并广播最新列表，采集线程据此在设备移除时切换到备用设备
+
+use super::capture::{AudioCapturer, AudioDeviceInfo, DeviceSummary};
+use crate::events::EventEmitter;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::watch;
+
+/// 默认轮询间隔
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+/// 停止检查粒度，避免停止时长时间阻塞
+const STOP_CHECK: Duration = Duration::from_millis(100);
+
+/// 设备列表变化
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceListChange {
+    /// 新增的设备
+    pub added: Vec<DeviceSummary>,
+    /// 移除的设备
+    pub removed: Vec<DeviceSummary>,
+}
+
+impl DeviceListChange {
+    /// 是否无变化
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 比较两次设备列表 (按设备 ID)
+pub fn diff_devices(old: &[DeviceSummary], new: &[DeviceSummary]) -> DeviceListChange {
+    DeviceListChange {
+        added: new
+            .iter()
+            .filter(|d| !old.iter().any(|o| o.id == d.id))
+            .cloned()
+            .collect(),
+        removed: old
+            .iter()
+            .filter(|d| !new.iter().any(|n| n.id == d.id))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// 选择故障切换的目标设备
+///
+/// 按优先级列表 (设备 ID 或名称) 选择第一个仍然可用的设备，
+/// 跳过已丢失的设备；返回 None 表示回退到系统默认设备
+pub fn select_failover_device(
+    devices: &[DeviceSummary],
+    lost_id: Option<&str>,
+    priority: &[String],
+) -> Option<DeviceSummary> {
+    priority.iter().find_map(|wanted| {
+        devices
+            .iter()
+            .filter(|d| Some(d.id.as_str()) != lost_id)
+            .find(|d| &d.id == wanted || &d.name == wanted)
+            .cloned()
+    })
+}
+
+/// 设备监听器
+///
+/// 在后台线程中轮询设备列表，通过 watch 通道广播最新列表。
+/// 设备能力查询开销较高，只在列表变化时重新查询并缓存
+pub struct DeviceWatcher {
+    /// 轮询间隔
+    interval: Duration,
+    /// 轮询线程是否运行
+    running: Arc<AtomicBool>,
+    /// 最新设备列表
+    devices_tx: Arc<watch::Sender<Vec<DeviceSummary>>>,
+    /// 普通输入设备的能力缓存
+    capabilities: Arc<Mutex<Vec<AudioDeviceInfo>>>,
+    /// 轮询线程句柄
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Default for DeviceWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceWatcher {
+    /// 创建监听器
+    pub fn new() -> Self {
+        Self::with_interval(DEFAULT_POLL_INTERVAL)
+    }
+
+    /// 使用指定轮询间隔创建监听器
+    pub fn with_interval(interval: Duration) -> Self {
+        let initial = AudioCapturer::list_devices().unwrap_or_default();
+        let (devices_tx, _) = watch::channel(initial);
+        Self {
+            interval,
+            running: Arc::new(AtomicBool::new(false)),
+            devices_tx: Arc::new(devices_tx),
+            capabilities: Arc::new(Mutex::new(probe_capabilities())),
+            thread: None,
+        }
+    }
+
+    /// 启动轮询线程
+    pub fn start(&mut self, app: AppHandle) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+
+        self.running.store(true, Ordering::SeqCst);
+        let running = self.running.clone();
+        let devices_tx = self.devices_tx.clone();
+        let capabilities = self.capabilities.clone();
+        let interval = self.interval;
+
+        let thread = std::thread::Builder::new()
+            .name("audio-flow-device-watcher".to_string())
+            .spawn(move || run_watcher(app, interval, running, devices_tx, capabilities));
+
+        match thread {
+            Ok(thread) => {
+                self.thread = Some(thread);
+                Ok(())
+            }
+            Err(e) => {
+                self.running.store(false, Ordering::SeqCst);
+                Err(e)
+            }
+        }
+    }
+
+    /// 停止轮询线程
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// 检查是否正在轮询
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 订阅设备列表变化
+    pub fn subscribe(&self) -> watch::Receiver<Vec<DeviceSummary>> {
+        self.devices_tx.subscribe()
+    }
+
+    /// 当前设备列表
+    pub fn devices(&self) -> Vec<DeviceSummary> {
+        self.devices_tx.borrow().clone()
+    }
+
+    /// 缓存的普通输入设备能力 (不含监听源)
+    ///
+    /// 轮询线程在设备列表变化时刷新；未在轮询时重新查询
+    pub fn capabilities(&self) -> Vec<AudioDeviceInfo> {
+        if self.is_running() {
+            self.capabilities.lock().clone()
+        } else {
+            probe_capabilities()
+        }
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 轮询线程主循环
+fn run_watcher(
+    app: AppHandle,
+    interval: Duration,
+    running: Arc<AtomicBool>,
+    devices_tx: Arc<watch::Sender<Vec<DeviceSummary>>>,
+    capabilities: Arc<Mutex<Vec<AudioDeviceInfo>>>,
+) {
+    let emitter = EventEmitter::new(app);
+
+    while running.load(Ordering::SeqCst) {
+        let devices = match AudioCapturer::list_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                tracing::warn!("Failed to list audio devices: {}", e);
+                sleep_while_running(&running, interval);
+                continue;
+            }
+        };
+
+        let change = diff_devices(&devices_tx.borrow(), &devices);
+        if !change.is_empty() {
+            for device in &change.added {
+                tracing::info!("Audio device added: {} ({})", device.name, device.id);
+            }
+            for device in &change.removed {
+                tracing::info!("Audio device removed: {} ({})", device.name, device.id);
+            }
+            *capabilities.lock() = probe_capabilities();
+            emitter.emit_audio_devices_changed(&change.added, &change.removed, &devices);
+            devices_tx.send_replace(devices);
+        }
+
+        sleep_while_running(&running, interval);
+    }
+}
+
+/// 查询设备能力，失败时返回空列表
+fn probe_capabilities() -> Vec<AudioDeviceInfo> {
+    AudioCapturer::probe_input_devices().unwrap_or_else(|e| {
+        tracing::warn!("Failed to probe audio devices: {}", e);
+        Vec::new()
+    })
+}
+
+/// 分段休眠，以便及时响应停止
+fn sleep_while_running(running: &AtomicBool, duration: Duration) {
+    let mut remaining = duration;
+    while !remaining.is_zero() && running.load(Ordering::SeqCst) {
+        let step = remaining.min(STOP_CHECK);
+        std::thread::sleep(step);
+        remaining -= step;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, name: &str) -> DeviceSummary {
+        DeviceSummary { id: id.to_string(), name: name.to_string() }
+    }
+
+    #[test]
+    fn test_diff_devices() {
+        let old = vec![device("alsa:hw:0", "Built-in"), device("alsa:hw:1", "USB Mic")];
+        let new = vec![device("alsa:hw:0", "Built-in"), device("alsa:hw:2", "Headset")];
+        let change = diff_devices(&old, &new);
+        assert_eq!(change.added, vec![device("alsa:hw:2", "Headset")]);
+        assert_eq!(change.removed, vec![device("alsa:hw:1", "USB Mic")]);
+        assert!(diff_devices(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_select_failover_by_priority() {
+        let devices = vec![device("alsa:hw:0", "Built-in"), device("alsa:hw:2", "Headset")];
+        let priority = vec![
+            "USB Mic".to_string(),
+            "Headset".to_string(),
+            "alsa:hw:0".to_string(),
+        ];
+        // USB Mic 已拔出，按优先级选择 Headset (按名称匹配)
+        let selected = select_failover_device(&devices, Some("alsa:hw:1"), &priority);
+        assert_eq!(selected, Some(device("alsa:hw:2", "Headset")));
+    }
+
+    #[test]
+    fn test_select_failover_skips_lost_device() {
+        let devices = vec![device("alsa:hw:0", "Built-in"), device("alsa:hw:2", "Headset")];
+        let priority = vec!["alsa:hw:2".to_string(), "Built-in".to_string()];
+        // 列表尚未刷新时，丢失的设备仍可能出现在列表中
+        let selected = select_failover_device(&devices, Some("alsa:hw:2"), &priority);
+        assert_eq!(selected, Some(device("alsa:hw:0", "Built-in")));
+    }
+
+    #[test]
+    fn test_select_failover_falls_back_to_default() {
+        let devices = vec![device("alsa:hw:0", "Built-in")];
+        assert_eq!(select_failover_device(&devices, None, &[]), None);
+        assert_eq!(
+            select_failover_device(&devices, None, &["Missing".to_string()]),
+            None
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! 配置管理器
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use arc_swap::ArcSwap;
@@ -29,6 +30,229 @@ pub struct AudioSettings {
     pub sample_rate: u32,
     pub noise_suppression: bool,
     pub auto_gain: bool,
+    /// 当前设备移除时按顺序尝试的备用设备 (ID 或名称)，均不可用时使用默认设备
+    #[serde(default)]
+    pub fallback_devices: Vec<String>,
+    /// 会话录音
+    #[serde(default)]
+    pub recording: RecordingSettings,
+    /// 预录时长 (毫秒)，None 表示使用默认值，0 表示关闭
+    #[serde(default)]
+    pub preroll_ms: Option<u32>,
+    /// 待机采集：会话之间保持麦克风打开，热键按下前的预录时长音频计入会话
+    #[serde(default)]
+    pub standby_capture: bool,
+    /// 采集处理链 (按执行顺序)，为空时使用默认链
+    #[serde(default)]
+    pub processing: Vec<ProcessingStageSettings>,
+    /// 麦克风校准结果，None 表示未校准
+    #[serde(default)]
+    pub calibration: Option<CalibrationSettings>,
+    /// 按设备 ID 保存的通道路由，未配置的设备平均所有通道
+    #[serde(default)]
+    pub channel_routing: HashMap<String, ChannelRouting>,
+    /// 与输入设备同时采集的系统音频 (回环) 设备 ID，两路分别作为带标签的通道
+    #[serde(default)]
+    pub loopback_device: Option<String>,
+    /// 同时采集的多路输入 (第一路为主输入)，非空时优先于 `loopback_device`
+    #[serde(default)]
+    pub inputs: Vec<InputSourceSettings>,
+    /// 多路输入的合并方式
+    #[serde(default)]
+    pub input_mode: MultiInputMode,
+    /// VAD 级别，决定本地检测参数与服务端 VAD 参数
+    #[serde(default)]
+    pub vad_level: VadLevel,
+    /// 免手动停止的听写模式
+    #[serde(default)]
+    pub hands_free: HandsFreeSettings,
+    /// 重采样质量
+    #[serde(default)]
+    pub resampler: ResamplerSettings,
+}
+
+/// VAD 级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum VadLevel {
+    /// 激进模式 - 更容易检测到语音，可能有更多误检
+    Aggressive,
+    /// 平衡模式 - 正常的检测灵敏度
+    #[default]
+    Balanced,
+    /// 宽松模式 - 更难检测到语音，可能遗漏短暂的语音
+    Relaxed,
+}
+
+/// 多路输入中的一路
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputSourceSettings {
+    /// 设备 ID，None 表示使用 `input_device`
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// 标签 (如 "me"、"remote")，随转写结果发送
+    pub label: String,
+    /// 输入增益 (dB)
+    #[serde(default)]
+    pub gain_db: f32,
+}
+
+/// 多路输入的合并方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MultiInputMode {
+    /// 每路输入作为一个带标签的通道，叠加后发送，转写结果标注语音能量最大的一路的标签
+    #[default]
+    Separate,
+    /// 按增益叠加为单声道
+    Mix,
+}
+
+/// 多通道输入混合为单声道的方式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ChannelRouting {
+    /// 平均所有通道
+    #[default]
+    Average,
+    /// 只使用一个通道 (从 0 开始)
+    Single { channel: u16 },
+    /// 平均指定的若干通道
+    Subset { channels: Vec<u16> },
+    /// 自适应选择能量最大的通道
+    Loudest,
+}
+
+/// 麦克风校准设置
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationSettings {
+    /// VAD 阈值 (VAD 能量刻度)
+    pub threshold_db: f32,
+    /// VAD 平滑因子
+    pub smoothing_factor: f32,
+    /// 输入增益 (dB)
+    pub gain_db: f32,
+}
+
+/// 处理链阶段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessingStageKind {
+    DcRemoval,
+    HighPass,
+    Gain,
+    NoiseSuppression,
+    AutoGain,
+    Downmix,
+    Resample,
+}
+
+/// 处理链阶段设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessingStageSettings {
+    pub kind: ProcessingStageKind,
+    /// 旁路该阶段
+    #[serde(default)]
+    pub bypass: bool,
+    /// 高通截止频率 (Hz)，None 表示使用默认值
+    #[serde(default)]
+    pub cutoff_hz: Option<f32>,
+    /// 固定增益 (dB)
+    #[serde(default)]
+    pub gain_db: Option<f32>,
+}
+
+impl ProcessingStageSettings {
+    /// 使用默认参数的阶段
+    pub fn new(kind: ProcessingStageKind) -> Self {
+        Self { kind, bypass: false, cutoff_hz: None, gain_db: None }
+    }
+}
+
+/// 录音文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RecordingFormat {
+    #[default]
+    Wav,
+    Flac,
+}
+
+/// 会话录音设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingSettings {
+    pub enabled: bool,
+    pub format: RecordingFormat,
+    /// 录制设备原始音频
+    pub raw: bool,
+    /// 录制重采样后发送给 Scribe 的 16kHz 音频
+    pub resampled: bool,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: RecordingFormat::Wav,
+            raw: false,
+            resampled: true,
+        }
+    }
+}
+
+/// 免手动停止 (hands-free) 听写设置
+///
+/// 启用后按一次快捷键开始听写，说完后静音达到设定时长即自动结束并注入文本
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandsFreeSettings {
+    pub enabled: bool,
+    /// 语音结束后自动停止前的静音时长 (毫秒)
+    pub trailing_silence_ms: u32,
+    /// 开始后一直未检测到语音时自动停止的时长 (毫秒)
+    pub initial_silence_ms: u32,
+    /// 单次会话最长时长 (毫秒)，0 表示不限制
+    pub max_session_ms: u32,
+    /// 自动停止前在悬浮窗显示倒计时的时长 (毫秒)
+    pub countdown_ms: u32,
+}
+
+impl Default for HandsFreeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trailing_silence_ms: 2000,
+            initial_silence_ms: 8000,
+            max_session_ms: 120_000,
+            countdown_ms: 1000,
+        }
+    }
+}
+
+/// 重采样质量档位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ResamplerQuality {
+    /// 三次多项式插值 - CPU 占用最低，降采样时高频会混叠到语音频带
+    Fast,
+    /// 中等长度的 sinc 插值，带抗混叠低通
+    #[default]
+    Balanced,
+    /// 长 sinc 插值，阻带衰减更高，CPU 占用最高
+    High,
+}
+
+/// 重采样设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResamplerSettings {
+    pub quality: ResamplerQuality,
+    /// 单次处理的输入帧数，越大 CPU 开销越低、延迟越高
+    pub chunk_size: u32,
+}
+
+impl Default for ResamplerSettings {
+    fn default() -> Self {
+        Self {
+            quality: ResamplerQuality::Balanced,
+            chunk_size: 128,
+        }
+    }
 }
 
 /// 输入设置
@@ -240,6 +464,56 @@ mod tests {
                 noise_suppression: true,
                 auto_gain: false,
                 input_device: Some("Microphone".to_string()),
+                fallback_devices: vec!["Headset".to_string()],
+                preroll_ms: Some(300),
+                standby_capture: true,
+                processing: vec![
+                    ProcessingStageSettings {
+                        cutoff_hz: Some(100.0),
+                        ..ProcessingStageSettings::new(ProcessingStageKind::HighPass)
+                    },
+                    ProcessingStageSettings::new(ProcessingStageKind::Downmix),
+                    ProcessingStageSettings {
+                        bypass: true,
+                        ..ProcessingStageSettings::new(ProcessingStageKind::AutoGain)
+                    },
+                ],
+                calibration: Some(CalibrationSettings {
+                    threshold_db: -84.0,
+                    smoothing_factor: 0.4,
+                    gain_db: 6.0,
+                }),
+                channel_routing: HashMap::from([
+                    ("usb-interface".to_string(), ChannelRouting::Single { channel: 0 }),
+                    ("array-mic".to_string(), ChannelRouting::Subset { channels: vec![0, 2] }),
+                    ("headset".to_string(), ChannelRouting::Loudest),
+                ]),
+                loopback_device: Some("monitor:sink.monitor".to_string()),
+                inputs: vec![
+                    InputSourceSettings { device_id: None, label: "me".to_string(), gain_db: 0.0 },
+                    InputSourceSettings {
+                        device_id: Some("monitor:sink.monitor".to_string()),
+                        label: "remote".to_string(),
+                        gain_db: -6.0,
+                    },
+                ],
+                input_mode: MultiInputMode::Mix,
+                vad_level: VadLevel::Relaxed,
+                hands_free: HandsFreeSettings {
+                    enabled: true,
+                    trailing_silence_ms: 1500,
+                    ..Default::default()
+                },
+                resampler: ResamplerSettings {
+                    quality: ResamplerQuality::High,
+                    chunk_size: 480,
+                },
+                recording: RecordingSettings {
+                    enabled: true,
+                    format: RecordingFormat::Flac,
+                    raw: true,
+                    resampled: false,
+                },
             },
             input: InputSettings {
                 injection_method: InjectionMethod::Clipboard,
@@ -266,6 +540,46 @@ mod tests {
         assert_eq!(parsed.audio.sample_rate, config.audio.sample_rate);
         assert_eq!(parsed.input.injection_method, config.input.injection_method);
         assert_eq!(parsed.hotkeys.listen_key, config.hotkeys.listen_key);
+        assert_eq!(parsed.audio.fallback_devices, config.audio.fallback_devices);
+        assert_eq!(parsed.audio.recording.format, RecordingFormat::Flac);
+        assert_eq!(parsed.audio.preroll_ms, Some(300));
+        assert!(parsed.audio.standby_capture);
+        assert_eq!(parsed.audio.processing, config.audio.processing);
+        assert_eq!(parsed.audio.calibration, config.audio.calibration);
+        assert_eq!(parsed.audio.channel_routing, config.audio.channel_routing);
+        assert_eq!(parsed.audio.loopback_device, config.audio.loopback_device);
+        assert_eq!(parsed.audio.inputs, config.audio.inputs);
+        assert_eq!(parsed.audio.input_mode, MultiInputMode::Mix);
+        assert_eq!(parsed.audio.vad_level, VadLevel::Relaxed);
+        assert_eq!(parsed.audio.hands_free, config.audio.hands_free);
+        assert_eq!(parsed.audio.resampler, config.audio.resampler);
+        assert!(parsed.audio.recording.raw);
+    }
+
+    #[test]
+    fn test_audio_settings_without_fallback_devices() {
+        // 旧版配置没有 fallback_devices 字段
+        let toml_str = r#"
+            input_device = "Microphone"
+            sample_rate = 16000
+            noise_suppression = false
+            auto_gain = false
+        "#;
+        let settings: AudioSettings = toml::from_str(toml_str).unwrap();
+        assert_eq!(settings.input_device, Some("Microphone".to_string()));
+        assert!(settings.fallback_devices.is_empty());
+        assert!(!settings.recording.enabled);
+        assert!(settings.recording.resampled);
+        assert!(settings.processing.is_empty());
+        assert!(settings.calibration.is_none());
+        assert!(settings.channel_routing.is_empty());
+        assert!(settings.loopback_device.is_none());
+        assert!(settings.inputs.is_empty());
+        assert_eq!(settings.input_mode, MultiInputMode::Separate);
+        assert_eq!(settings.vad_level, VadLevel::Balanced);
+        assert!(!settings.hands_free.enabled);
+        assert_eq!(settings.resampler, ResamplerSettings::default());
+        assert_eq!(settings.resampler.quality, ResamplerQuality::Balanced);
     }
 
     #[test]
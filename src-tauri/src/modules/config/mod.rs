@@ -3,5 +3,10 @@
 pub mod manager;
 pub mod secure_storage;
 
-pub use manager::{ConfigManager, UserConfig, ApiConfig, AudioSettings, InputSettings, UiSettings, HotkeySettings};
+pub use manager::{
+    ConfigManager, UserConfig, ApiConfig, AudioSettings, InputSettings, UiSettings, HotkeySettings,
+    CalibrationSettings, ChannelRouting, HandsFreeSettings, InputSourceSettings, MultiInputMode,
+    ProcessingStageKind, ProcessingStageSettings, RecordingFormat, RecordingSettings, ResamplerQuality,
+    ResamplerSettings, VadLevel,
+};
 pub use secure_storage::{SecureStorage, SecureStorageError, ApiKeyStorage, ElevenLabsKeyStorage};
@@ -12,3 +12,4 @@ pub mod notification;
 pub mod events;
 pub mod window;
 pub mod lifecycle;
+pub mod session;
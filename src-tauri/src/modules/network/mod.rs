@@ -6,4 +6,6 @@ pub mod websocket;
 pub mod scribe_client;
 
 pub use websocket::{WebSocketClient, WebSocketConfig, ConnectionState, WsMessage, MessageBuilder};
-pub use scribe_client::{ScribeClient, ScribeConfig, ScribeEvent, TranscriptionResult, TranscriptionParser};
+pub use scribe_client::{
+    ScribeClient, ScribeConfig, ScribeEvent, ServerVadSettings, TranscriptionResult, TranscriptionParser,
+};
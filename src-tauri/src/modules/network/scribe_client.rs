@@ -35,6 +35,47 @@ impl Default for ScribeConfig {
     }
 }
 
+/// 服务端 VAD 参数，随配置消息发送
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ServerVadSettings {
+    /// 语音判定阈值 (0.0 - 1.0)，越高越不容易判为语音
+    pub vad_threshold: f32,
+    /// 静音持续多久后提交转写 (秒)
+    pub vad_silence_threshold_secs: f32,
+    /// 最短语音时长 (毫秒)
+    pub min_speech_duration_ms: u32,
+    /// 最短静音时长 (毫秒)
+    pub min_silence_duration_ms: u32,
+}
+
+impl ServerVadSettings {
+    /// VAD 级别对应的服务端参数，与本地的 [`VadConfig::from_level`] 取向一致
+    ///
+    /// [`VadConfig::from_level`]: crate::modules::audio::VadConfig::from_level
+    pub fn from_level(level: VadLevel) -> Self {
+        match level {
+            VadLevel::Aggressive => Self {
+                vad_threshold: 0.3,
+                vad_silence_threshold_secs: 1.0,
+                min_speech_duration_ms: 50,
+                min_silence_duration_ms: 100,
+            },
+            VadLevel::Balanced => Self {
+                vad_threshold: 0.4,
+                vad_silence_threshold_secs: 1.5,
+                min_speech_duration_ms: 100,
+                min_silence_duration_ms: 100,
+            },
+            VadLevel::Relaxed => Self {
+                vad_threshold: 0.6,
+                vad_silence_threshold_secs: 2.0,
+                min_speech_duration_ms: 250,
+                min_silence_duration_ms: 200,
+            },
+        }
+    }
+}
+
 /// Scribe 事件
 ///
 /// 从 WebSocket 接收的转写事件
@@ -116,6 +157,8 @@ pub struct ScribeClient {
     last_transcript: Arc<Mutex<Option<String>>>,
     /// 累计的 partial transcript
     partial_buffer: Arc<Mutex<String>>,
+    /// VAD 级别，连接时发送对应的服务端 VAD 参数
+    vad_level: VadLevel,
 }
 
 impl Default for ScribeClient {
@@ -144,6 +187,7 @@ impl ScribeClient {
             session_id: Arc::new(Mutex::new(None)),
             last_transcript: Arc::new(Mutex::new(None)),
             partial_buffer: Arc::new(Mutex::new(String::new())),
+            vad_level: VadLevel::default(),
         }
     }
 
@@ -165,6 +209,7 @@ impl ScribeClient {
         self.ws_client.send_init_config(
             &self.config.model_id,
             &self.config.language_code,
+            &ServerVadSettings::from_level(self.vad_level),
         ).await?;
 
         Ok(())
@@ -393,14 +438,14 @@ impl ScribeClient {
 
     /// VAD 级别
     pub fn vad_level(&self) -> VadLevel {
-        // 默认返回 Balanced，实际实现可以根据配置调整
-        VadLevel::Balanced
+        self.vad_level
     }
 
     /// 设置 VAD 级别
-    pub fn set_vad_level(&mut self, _level: VadLevel) {
-        // VAD 设置在 WebSocket 消息中发送
-        // 实际实现需要在配置时发送 VAD 设置
+    ///
+    /// 服务端 VAD 参数在配置消息中发送，下次连接时生效
+    pub fn set_vad_level(&mut self, level: VadLevel) {
+        self.vad_level = level;
     }
 }
 
@@ -470,6 +515,21 @@ mod tests {
         assert_eq!(result.unwrap().text, "hello world");
     }
 
+    #[test]
+    fn test_server_vad_settings_follow_level() {
+        let aggressive = ServerVadSettings::from_level(VadLevel::Aggressive);
+        let balanced = ServerVadSettings::from_level(VadLevel::Balanced);
+        let relaxed = ServerVadSettings::from_level(VadLevel::Relaxed);
+        assert!(aggressive.vad_threshold < balanced.vad_threshold);
+        assert!(balanced.vad_threshold < relaxed.vad_threshold);
+        assert!(aggressive.min_speech_duration_ms < relaxed.min_speech_duration_ms);
+
+        let mut client = ScribeClient::default();
+        assert_eq!(client.vad_level(), VadLevel::Balanced);
+        client.set_vad_level(VadLevel::Relaxed);
+        assert_eq!(client.vad_level(), VadLevel::Relaxed);
+    }
+
     #[test]
     fn test_scribe_event_serialization() {
         let event = ScribeEvent::PartialTranscript {
@@ -2,6 +2,7 @@
 //!
 //! 使用 tokio-tungstenite 实现 WebSocket 连接管理
 
+use super::scribe_client::ServerVadSettings;
 use crate::error::NetworkError;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use futures_util::sink::SinkExt;
@@ -267,15 +268,10 @@ impl WebSocketClient {
         &mut self,
         model_id: &str,
         language_code: &str,
+        vad: &ServerVadSettings,
     ) -> Result<(), NetworkError> {
-        let payload = serde_json::json!({
-            "model_id": model_id,
-            "language_code": language_code,
-            "encoding": "pcm_16000",
-            "message_type": "configure"
-        });
-
-        self.send_text(&payload.to_string()).await
+        let payload = MessageBuilder::configure_message(model_id, language_code, vad);
+        self.send_text(&payload).await
     }
 
     /// 接收消息 (非阻塞)
@@ -351,12 +347,17 @@ impl MessageBuilder {
         }).to_string()
     }
 
-    /// 构建配置消息
-    pub fn configure_message(model_id: &str, language_code: &str) -> String {
+    /// 构建配置消息 (含服务端 VAD 参数)
+    pub fn configure_message(model_id: &str, language_code: &str, vad: &ServerVadSettings) -> String {
         serde_json::json!({
             "model_id": model_id,
             "language_code": language_code,
             "encoding": "pcm_16000",
+            "commit_strategy": "vad",
+            "vad_threshold": vad.vad_threshold,
+            "vad_silence_threshold_secs": vad.vad_silence_threshold_secs,
+            "min_speech_duration_ms": vad.min_speech_duration_ms,
+            "min_silence_duration_ms": vad.min_silence_duration_ms,
             "message_type": "configure"
         }).to_string()
     }
@@ -365,6 +366,7 @@ impl MessageBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::modules::audio::VadLevel;
 
     #[test]
     fn test_connection_state_display() {
@@ -383,9 +385,15 @@ mod tests {
 
     #[test]
     fn test_message_builder_configure() {
-        let message = MessageBuilder::configure_message("model_id", "en");
+        let vad = ServerVadSettings::from_level(VadLevel::Relaxed);
+        let message = MessageBuilder::configure_message("model_id", "en", &vad);
         assert!(message.contains("model_id"));
         assert!(message.contains("language_code"));
         assert!(message.contains("configure"));
+
+        let value: serde_json::Value = serde_json::from_str(&message).unwrap();
+        assert_eq!(value["commit_strategy"], "vad");
+        assert_eq!(value["min_speech_duration_ms"], vad.min_speech_duration_ms);
+        assert!((value["vad_threshold"].as_f64().unwrap() - vad.vad_threshold as f64).abs() < 1e-6);
     }
 }
@@ -0,0 +1,1765 @@
+//! 听写会话模块
+//!
+//! 串联音频采集 → 单声道混合 → 重采样 → VAD → Scribe 转写 → 文本注入的完整管线
+
+use crate::error::{AppError, AudioError, NetworkError};
+use crate::events::EventEmitter;
+use crate::modules::audio::{
+    AgcConfig, AudioConfig, AudioFrame, AudioProcessor, AudioSource, AutoGain, AutoStop,
+    AutoStopConfig, AutoStopEvent, AutoStopReason, DEFAULT_PREROLL_MS, DeviceSummary, DeviceWatcher,
+    Downmix, FrameHistory, LevelMeter, MICROPHONE_LABEL, NoiseSuppression, PreRollBuffer,
+    ProcessingChain, RecorderConfig, Resample, ResamplerConfig, SYSTEM_AUDIO_LABEL, SessionRecorder,
+    SourceKind, StageStats, VadConfig, VadState, VoiceActivityDetector, frames_to_ns,
+    select_failover_device,
+};
+use crate::modules::config::{
+    AudioSettings, ChannelRouting, InputSourceSettings, MultiInputMode, ProcessingStageKind,
+    ProcessingStageSettings,
+};
+use crate::modules::input::InputManager;
+use crate::modules::network::{ScribeClient, ScribeEvent};
+use crate::state::AppState;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::Mutex as TauriMutex;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// Scribe 要求的输入采样率
+pub const TARGET_SAMPLE_RATE: u32 = 16000;
+/// VAD 分帧时长 (毫秒)
+const VAD_FRAME_MS: u32 = 20;
+/// 单次从环形缓冲区读取的最大样本数
+const READ_CHUNK_SAMPLES: usize = 4800;
+/// 环形缓冲区为空时的等待间隔
+const IDLE_POLL: Duration = Duration::from_millis(5);
+/// 采集线程与网络任务之间的通道容量 (帧)
+const AUDIO_CHANNEL_CAPACITY: usize = 256;
+/// 转写事件轮询间隔
+const RECEIVE_POLL: Duration = Duration::from_millis(50);
+/// 单次接收等待上限，避免长时间占用客户端锁
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(20);
+/// 采集结束后等待最终转写结果的时长
+const COMMIT_GRACE: Duration = Duration::from_millis(1500);
+/// 设备切换失败后的重试间隔
+const FAILOVER_RETRY: Duration = Duration::from_millis(1000);
+/// 待机采集读取缓冲区的间隔
+const STANDBY_POLL: Duration = Duration::from_millis(50);
+/// 录音文件子目录 (位于数据目录下)
+const RECORDINGS_DIR: &str = "recordings";
+
+/// 会话配置
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// 音频源，默认为输入设备
+    pub source: SourceKind,
+    /// 采集配置
+    pub audio: AudioConfig,
+    /// VAD 配置
+    pub vad: VadConfig,
+    /// 发送给 Scribe 的采样率
+    pub output_rate: u32,
+    /// 设备移除时的备用设备优先级列表 (ID 或名称)
+    pub fallback_devices: Vec<String>,
+    /// 会话录音，None 表示不录音
+    pub recording: Option<RecorderConfig>,
+    /// 预录时长 (毫秒)，语音段开始时随首帧发送
+    pub preroll_ms: u32,
+    /// 会话之间保持音频源运行，保留热键按下前 `preroll_ms` 的音频
+    pub standby_capture: bool,
+    /// 是否对发送的音频降噪，开启时启用处理链中的降噪阶段 (没有时追加)
+    pub noise_suppression: bool,
+    /// VAD 之前的处理链阶段 (按执行顺序)
+    pub processing: Vec<ProcessingStageSettings>,
+    /// 按设备 ID 的通道路由
+    pub channel_routing: HashMap<String, ChannelRouting>,
+    /// 免手动停止，None 表示需手动停止
+    pub auto_stop: Option<AutoStopConfig>,
+    /// 重采样质量与块大小
+    pub resampler: ResamplerConfig,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            source: SourceKind::Device,
+            audio: AudioConfig::default(),
+            vad: VadConfig::default(),
+            output_rate: TARGET_SAMPLE_RATE,
+            fallback_devices: Vec::new(),
+            recording: None,
+            preroll_ms: DEFAULT_PREROLL_MS,
+            standby_capture: false,
+            noise_suppression: false,
+            processing: ProcessingChain::default_settings(false),
+            channel_routing: HashMap::new(),
+            auto_stop: None,
+            resampler: ResamplerConfig::default(),
+        }
+    }
+}
+
+impl SessionConfig {
+    /// 根据用户音频设置构建会话配置
+    ///
+    /// # Arguments
+    /// * `settings` - 用户音频设置
+    /// * `data_dir` - 应用数据目录 ([`AppConfig::data_dir`])，录音保存在其下
+    ///
+    /// [`AppConfig::data_dir`]: crate::modules::lifecycle::AppConfig::data_dir
+    pub fn from_settings(settings: &AudioSettings, data_dir: &Path) -> Self {
+        let mut config = Self::default();
+        config.audio.device_id = settings.input_device.clone();
+        // 多路输入优先，回环设备等同于 "mic" 与 "system" 两路分开采集
+        if !settings.inputs.is_empty() {
+            config.source = SourceKind::MultiDevice {
+                inputs: settings.inputs.clone(),
+                mode: settings.input_mode,
+            };
+        } else if let Some(loopback_device) = &settings.loopback_device {
+            config.source = SourceKind::MultiDevice {
+                inputs: vec![
+                    InputSourceSettings { device_id: None, label: MICROPHONE_LABEL.to_string(), gain_db: 0.0 },
+                    InputSourceSettings {
+                        device_id: Some(loopback_device.clone()),
+                        label: SYSTEM_AUDIO_LABEL.to_string(),
+                        gain_db: 0.0,
+                    },
+                ],
+                mode: MultiInputMode::Separate,
+            };
+        }
+        if settings.sample_rate > 0 {
+            config.audio.sample_rate = settings.sample_rate;
+        }
+        config.fallback_devices = settings.fallback_devices.clone();
+        if let Some(preroll_ms) = settings.preroll_ms {
+            config.preroll_ms = preroll_ms;
+        }
+        config.standby_capture = settings.standby_capture;
+        config.noise_suppression = settings.noise_suppression;
+        config.vad = VadConfig::from_level(settings.vad_level);
+        // 未自定义处理链时由 auto_gain 控制默认链中的自动增益
+        config.processing = if settings.processing.is_empty() {
+            ProcessingChain::default_settings(settings.auto_gain)
+        } else {
+            settings.processing.clone()
+        };
+        // 校准结果覆盖 VAD 阈值，并在未手动配置增益时补充输入增益
+        if let Some(calibration) = settings.calibration {
+            config.vad.threshold_db = calibration.threshold_db;
+            config.vad.smoothing_factor = calibration.smoothing_factor;
+            let has_gain = config.processing.iter().any(|s| s.kind == ProcessingStageKind::Gain);
+            if calibration.gain_db != 0.0 && !has_gain {
+                config.processing.insert(0, ProcessingStageSettings {
+                    gain_db: Some(calibration.gain_db),
+                    ..ProcessingStageSettings::new(ProcessingStageKind::Gain)
+                });
+            }
+        }
+        config.channel_routing = settings.channel_routing.clone();
+        config.auto_stop = AutoStopConfig::from_settings(&settings.hands_free)
+            .map(|auto_stop| auto_stop.with_vad_hang(vad_hang(&config.vad)));
+        config.resampler = ResamplerConfig::from_settings(&settings.resampler);
+        config.recording = RecorderConfig::from_settings(
+            &settings.recording,
+            data_dir.join(RECORDINGS_DIR),
+        );
+        config
+    }
+
+    /// 指定设备的通道路由，未配置时平均所有通道
+    pub fn channel_routing_for(&self, device_id: Option<&str>) -> ChannelRouting {
+        device_id
+            .and_then(|id| self.channel_routing.get(id))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// 会话实时指标
+///
+/// 由采集线程写入，命令线程读取
+#[derive(Debug, Default)]
+pub struct SessionMetrics {
+    /// 最近一帧的 RMS 音量 (f32 位模式)
+    volume_level: AtomicU32,
+    /// 最近一帧是否为语音
+    is_speech: AtomicBool,
+    /// 各标签通道发送的语音量，用于标注转写结果的说话方
+    speakers: parking_lot::Mutex<SpeakerActivity>,
+}
+
+/// 各标签通道自上次提交转写以来发送的语音帧数
+#[derive(Debug, Default)]
+struct SpeakerActivity {
+    labels: Vec<String>,
+    frames: Vec<u64>,
+}
+
+impl SessionMetrics {
+    /// 更新指标
+    pub fn update(&self, volume_level: f32, is_speech: bool) {
+        self.volume_level.store(volume_level.to_bits(), Ordering::Relaxed);
+        self.is_speech.store(is_speech, Ordering::Relaxed);
+    }
+
+    /// 重置指标
+    pub fn reset(&self) {
+        self.update(0.0, false);
+        *self.speakers.lock() = SpeakerActivity::default();
+    }
+
+    /// 设置音频源的通道标签，为空表示不标注说话方
+    pub fn set_channel_labels(&self, labels: Vec<String>) {
+        let frames = vec![0; labels.len()];
+        *self.speakers.lock() = SpeakerActivity { labels, frames };
+    }
+
+    /// 累计发送的语音帧数，`channel` 为语音所取自的通道，没有对应标签时忽略
+    pub fn record_speaker(&self, channel: u16, frames: usize) {
+        if let Some(total) = self.speakers.lock().frames.get_mut(channel as usize) {
+            *total += frames as u64;
+        }
+    }
+
+    /// 取出发送语音最多的通道标签并清零累计值，没有语音时返回 None
+    ///
+    /// 在收到提交的转写时调用，累计区间与转写片段大致对应；
+    /// 数量相同时取排在前面的通道
+    pub fn take_speaker(&self) -> Option<String> {
+        let mut speakers = self.speakers.lock();
+        let (index, &frames) = speakers
+            .frames
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))?;
+        if frames == 0 {
+            return None;
+        }
+        let label = speakers.labels[index].clone();
+        speakers.frames.iter_mut().for_each(|f| *f = 0);
+        Some(label)
+    }
+
+    /// 获取音量
+    pub fn volume_level(&self) -> f32 {
+        f32::from_bits(self.volume_level.load(Ordering::Relaxed))
+    }
+
+    /// 是否为语音
+    pub fn is_speech(&self) -> bool {
+        self.is_speech.load(Ordering::Relaxed)
+    }
+}
+
+/// 会话状态快照
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionSnapshot {
+    /// 会话是否运行中
+    pub is_active: bool,
+    /// 已持续时长 (毫秒)
+    pub duration_ms: u64,
+    /// 当前音量 (线性 RMS, 0.0 - 1.0)
+    pub volume_level: f32,
+    /// 当前是否检测到语音
+    pub is_speech: bool,
+}
+
+/// 语音处理管线
+///
+/// 处理链 (默认为单声道混合 → 重采样 → 自动增益，可选降噪) → VAD 分帧，
+/// 产出需要发送给 Scribe 的语音帧。
+/// 带标签的多路输入在处理链之前叠加为单声道，见 [`SpeechPipeline::set_channel_labels`]。
+/// 静音帧进入预录缓冲，语音段开始时先于语音帧发送。
+/// 输出帧携带首个样本的采集时间，与采集端使用同一时间轴
+pub struct SpeechPipeline {
+    /// VAD 之前的处理链，输出为目标采样率的单声道音频
+    chain: ProcessingChain,
+    /// 当前输入采样率
+    input_rate: u32,
+    /// 输出采样率
+    output_rate: u32,
+    vad: VoiceActivityDetector,
+    /// 输入是否为带标签的多路输入，否则交给处理链按通道路由混合
+    labeled: bool,
+    /// VAD 帧长 (样本数)
+    frame_size: usize,
+    /// 尚未凑满一帧的样本
+    pending: Vec<f32>,
+    /// `pending` 首个样本的采集时间 (纳秒)
+    pending_ts: u128,
+    /// 语音段开始前的静音帧
+    preroll: PreRollBuffer,
+    /// 是否处于语音段中
+    in_segment: bool,
+    metrics: Arc<SessionMetrics>,
+    /// 会话录音器
+    recorder: Option<SessionRecorder>,
+}
+
+impl SpeechPipeline {
+    /// 创建处理管线
+    ///
+    /// # Arguments
+    /// * `input_rate` - 采集设备的实际采样率
+    /// * `config` - 会话配置
+    /// * `metrics` - 共享指标
+    pub fn new(
+        input_rate: u32,
+        config: &SessionConfig,
+        metrics: Arc<SessionMetrics>,
+    ) -> Result<Self, AudioError> {
+        let frame_size = (config.output_rate * VAD_FRAME_MS / 1000) as usize;
+        let mut chain =
+            ProcessingChain::from_settings(&config.processing, config.output_rate, config.resampler)?;
+        // VAD 需要目标采样率的单声道音频，自定义链中缺少的阶段追加到末尾
+        if !chain.is_active(Downmix::NAME) {
+            chain.push(Box::new(Downmix::default()));
+        }
+        if !chain.is_active(Resample::NAME) {
+            chain.push(Box::new(Resample::with_config(config.output_rate, config.resampler)));
+        }
+        // 降噪需要单声道输入，已有的降噪阶段在未旁路的单声道混合之后才直接启用
+        if config.noise_suppression {
+            let stats = chain.stats();
+            match chain.position(NoiseSuppression::NAME) {
+                Some(index) if stats[..index].iter().any(|s| s.name == Downmix::NAME && !s.bypass) => {
+                    chain.set_bypass(index, false);
+                }
+                _ => chain.push(Box::new(NoiseSuppression::new())),
+            }
+        }
+        let mut pipeline = Self {
+            chain,
+            input_rate,
+            output_rate: config.output_rate,
+            vad: VoiceActivityDetector::new(VadConfig { sample_rate: config.output_rate, ..config.vad }),
+            labeled: false,
+            frame_size,
+            pending: Vec::with_capacity(frame_size * 2),
+            pending_ts: 0,
+            preroll: PreRollBuffer::with_duration(config.preroll_ms, config.output_rate),
+            in_segment: false,
+            metrics,
+            recorder: None,
+        };
+        pipeline.set_agc_gate(&config.vad);
+        Ok(pipeline)
+    }
+
+    /// 处理一帧采集数据，返回需要发送的语音帧
+    pub fn process(&mut self, frame: AudioFrame) -> Result<Vec<AudioFrame>, AudioError> {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.write_raw(&frame);
+        }
+        let (frame, speaker) = if self.labeled {
+            let speaker = loudest_channel(&frame).map(|channel| (channel, frame.frames()));
+            (frame.sum_channels(), speaker)
+        } else {
+            (frame, None)
+        };
+        let processed = self.chain.process(frame)?;
+        let chunks = self.push(&processed);
+        if let Some((channel, frames)) = speaker.filter(|_| self.vad.state() != VadState::Silence) {
+            self.metrics.record_speaker(channel, frames);
+        }
+        Ok(chunks)
+    }
+
+    /// 刷新管线中的剩余数据
+    pub fn flush(&mut self) -> Result<Vec<AudioFrame>, AudioError> {
+        let mut chunks = Vec::new();
+        for tail in self.chain.flush()? {
+            chunks.extend(self.push(&tail));
+        }
+
+        // 不足一帧的尾部仅在语音中时发送
+        if self.vad.state() != VadState::Silence && !self.pending.is_empty() {
+            let samples = std::mem::take(&mut self.pending);
+            chunks.push(self.output_frame(samples, self.pending_ts));
+        }
+        self.pending.clear();
+        Ok(chunks)
+    }
+
+    /// 切换输入采样率 (如设备切换后)
+    ///
+    /// 保留 VAD 状态，返回处理链中剩余数据产生的语音帧。
+    /// 处理链各阶段在收到新采样率的首帧时按其重建
+    pub fn set_input_rate(&mut self, input_rate: u32) -> Result<Vec<AudioFrame>, AudioError> {
+        if input_rate == self.input_rate {
+            return Ok(Vec::new());
+        }
+        self.input_rate = input_rate;
+        let mut chunks = Vec::new();
+        for tail in self.chain.flush()? {
+            chunks.extend(self.push(&tail));
+        }
+        Ok(chunks)
+    }
+
+    /// 设置音频源的通道标签 (分通道的多路输入)
+    ///
+    /// 有标签时各路输入 (已按各自增益调整) 叠加后发送，多方同时说话时都会被转写；
+    /// 每帧按能量最大的一路累计说话方，转写结果标注该段语音最多的一路。
+    /// 为空表示普通多通道设备，由处理链按通道路由混合
+    pub fn set_channel_labels(&mut self, labels: Vec<String>) {
+        self.labeled = !labels.is_empty();
+        self.metrics.set_channel_labels(labels);
+    }
+
+    /// 设置通道路由 (如设备切换后)，应用到处理链中所有单声道混合阶段
+    pub fn set_channel_routing(&mut self, routing: ChannelRouting) {
+        self.replace_stages(Downmix::NAME, || Box::new(Downmix::new(routing.clone())));
+    }
+
+    /// 按 VAD 阈值设置自动增益的噪声门限
+    ///
+    /// AGC 位于 VAD 之前，门限以下的底噪不会被放大，VAD 不会因此停在语音状态
+    fn set_agc_gate(&mut self, vad: &VadConfig) {
+        let config = AgcConfig::default().with_vad_threshold(vad.threshold_dbfs());
+        self.replace_stages(AutoGain::NAME, || Box::new(AutoGain::new(config)));
+    }
+
+    /// 替换处理链中所有指定名称的阶段
+    fn replace_stages(&mut self, name: &str, make: impl Fn() -> Box<dyn AudioProcessor>) {
+        let positions: Vec<usize> = self.chain
+            .names()
+            .iter()
+            .enumerate()
+            .filter(|(_, stage)| **stage == name)
+            .map(|(i, _)| i)
+            .collect();
+        for index in positions {
+            self.chain.replace(index, make());
+        }
+    }
+
+    /// 设置会话录音器
+    pub fn set_recorder(&mut self, recorder: SessionRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// 取出会话录音器
+    pub fn take_recorder(&mut self) -> Option<SessionRecorder> {
+        self.recorder.take()
+    }
+
+    /// 更新 VAD 配置 (如切换 VAD 级别)，保留当前语音段状态
+    pub fn set_vad_config(&mut self, config: VadConfig) {
+        self.vad.set_config(VadConfig { sample_rate: self.output_rate, ..config });
+        self.set_agc_gate(&config);
+    }
+
+    /// 当前 VAD 状态
+    pub fn vad_state(&self) -> VadState {
+        self.vad.state()
+    }
+
+    /// 处理链各阶段的统计
+    pub fn processing_stats(&self) -> Vec<StageStats> {
+        self.chain.stats()
+    }
+
+    /// 按 VAD 帧长切分并检测
+    fn push(&mut self, resampled: &AudioFrame) -> Vec<AudioFrame> {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.write_resampled(&resampled.samples, self.output_rate);
+        }
+        // 以最新数据的时间戳回推缓冲起点，丢帧造成的时间跳变不会累积
+        self.pending_ts = resampled
+            .timestamp_ns
+            .saturating_sub(frames_to_ns(self.pending.len() as u64, self.output_rate));
+        self.pending.extend_from_slice(&resampled.samples);
+
+        let frame_ns = frames_to_ns(self.frame_size as u64, self.output_rate);
+        let mut chunks = Vec::new();
+        while self.pending.len() >= self.frame_size {
+            let frame: Vec<f32> = self.pending.drain(..self.frame_size).collect();
+            let timestamp_ns = self.pending_ts;
+            self.pending_ts += frame_ns;
+            let state = self.vad.detect(&frame);
+            let is_speech = state != VadState::Silence;
+
+            self.metrics.update(rms(&frame), is_speech);
+            if is_speech {
+                // 语音段开始，先发送预录音频 (紧邻当前帧之前)
+                if !self.in_segment {
+                    let preroll = self.preroll.drain();
+                    let preroll_ts = timestamp_ns
+                        .saturating_sub(frames_to_ns(preroll.len() as u64, self.output_rate));
+                    chunks.extend(preroll.chunks(self.frame_size).enumerate().map(|(i, chunk)| {
+                        let offset = frames_to_ns((i * self.frame_size) as u64, self.output_rate);
+                        self.output_frame(chunk.to_vec(), preroll_ts + offset)
+                    }));
+                    self.in_segment = true;
+                }
+                chunks.push(self.output_frame(frame, timestamp_ns));
+            } else {
+                self.in_segment = false;
+                self.preroll.push(&frame);
+            }
+        }
+        chunks
+    }
+
+    /// 构建输出采样率的单声道帧
+    fn output_frame(&self, samples: Vec<f32>, timestamp_ns: u128) -> AudioFrame {
+        AudioFrame::new(samples, self.output_rate, 1, timestamp_ns)
+    }
+}
+
+/// 听写会话
+///
+/// 管理采集线程与网络任务的生命周期
+pub struct DictationSession {
+    /// 采集线程是否运行
+    running: Arc<AtomicBool>,
+    /// 实时指标
+    metrics: Arc<SessionMetrics>,
+    /// 会话开始时间
+    started_at: Option<Instant>,
+    /// 采集循环句柄
+    capture_thread: Option<CaptureHandle>,
+    /// 待机采集，未开启时为 None
+    standby: Option<Standby>,
+    /// 网络任务句柄
+    network_task: Option<tauri::async_runtime::JoinHandle<()>>,
+    /// 运行中会话的 VAD 配置更新通道
+    vad_tx: Option<watch::Sender<VadConfig>>,
+}
+
+impl Default for DictationSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DictationSession {
+    /// 创建空闲的会话
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            metrics: Arc::new(SessionMetrics::default()),
+            started_at: None,
+            capture_thread: None,
+            standby: None,
+            network_task: None,
+            vad_tx: None,
+        }
+    }
+
+    /// 按配置启动、重建或停止待机采集
+    ///
+    /// 会话运行中时不做处理，下次启动会话时按会话配置调整
+    pub fn set_standby(&mut self, config: &SessionConfig) {
+        if self.is_active() {
+            return;
+        }
+        if !config.standby_capture || config.preroll_ms == 0 || !config.source.is_live() {
+            self.standby = None;
+            return;
+        }
+        if self.standby.as_ref().is_some_and(|standby| standby.matches(config)) {
+            return;
+        }
+        self.standby = match Standby::spawn(config) {
+            Ok(standby) => Some(standby),
+            Err(e) => {
+                tracing::warn!("Failed to start standby capture: {}", e);
+                None
+            }
+        };
+    }
+
+    /// 检查会话是否运行中
+    pub fn is_active(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// 采集线程是否仍在采集 (自动停止后为 false，直到调用 `stop`)
+    pub fn is_capturing(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 启动会话
+    ///
+    /// 采集器在独立线程中创建 (cpal Stream 不保证可跨线程移动)，
+    /// 开启待机采集时由待机线程接管其正在运行的音频源。
+    /// 处理后的语音帧通过通道交给网络任务发送
+    pub async fn start(&mut self, app: &AppHandle, config: SessionConfig) -> Result<(), AppError> {
+        if self.is_active() {
+            return Err(AppError::Internal("Session already running".to_string()));
+        }
+
+        // 连接建立时已收到的 Scribe 会话 ID，会话中收到新 ID 时更新
+        let session_id: SharedSessionId = {
+            let client = app.state::<TauriMutex<ScribeClient>>();
+            let guard = client.lock().await;
+            if !guard.is_connected() {
+                return Err(NetworkError::ConnectionLost.into());
+            }
+            Arc::new(parking_lot::Mutex::new(guard.session_id()))
+        };
+
+        let devices_rx = app.state::<TauriMutex<DeviceWatcher>>().lock().await.subscribe();
+        let (audio_tx, audio_rx) = mpsc::channel(AUDIO_CHANNEL_CAPACITY);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (vad_tx, vad_rx) = watch::channel(config.vad);
+
+        self.set_standby(&config);
+        self.metrics.reset();
+        self.running.store(true, Ordering::SeqCst);
+
+        let ctx = CaptureContext {
+            app: app.clone(),
+            config,
+            running: self.running.clone(),
+            metrics: self.metrics.clone(),
+            devices_rx,
+            vad_rx,
+            session_id: session_id.clone(),
+        };
+        let (done_tx, done_rx) = oneshot::channel();
+        let request = CaptureRequest { ctx, audio_tx, ready_tx, done_tx };
+
+        // 优先交给待机线程，待机线程已退出时改为独立采集线程
+        let request = match &self.standby {
+            Some(standby) => match standby.requests.send(request) {
+                Ok(()) => None,
+                Err(std::sync::mpsc::SendError(request)) => {
+                    self.standby = None;
+                    Some(request)
+                }
+            },
+            None => Some(request),
+        };
+        let capture_thread = match request {
+            None => CaptureHandle::Standby(done_rx),
+            Some(request) => CaptureHandle::Thread(
+                std::thread::Builder::new()
+                    .name("audio-flow-capture".to_string())
+                    .spawn(move || run_capture(request))
+                    .map_err(|e| AppError::SystemError(e.to_string()))?,
+            ),
+        };
+
+        // 等待采集器启动结果
+        let ready = ready_rx
+            .await
+            .unwrap_or_else(|_| Err(AudioError::CaptureFailed("Capture thread exited".to_string())));
+        if let Err(e) = ready {
+            self.running.store(false, Ordering::SeqCst);
+            capture_thread.wait().await;
+            return Err(e.into());
+        }
+
+        self.capture_thread = Some(capture_thread);
+        self.vad_tx = Some(vad_tx);
+        self.network_task = Some(tauri::async_runtime::spawn(run_network(
+            app.clone(),
+            audio_rx,
+            session_id,
+            self.metrics.clone(),
+        )));
+        self.started_at = Some(Instant::now());
+
+        tracing::info!("Dictation session started");
+        Ok(())
+    }
+
+    /// 停止会话，返回停止前的状态快照
+    ///
+    /// 网络任务会在发送完剩余音频、等待最终转写后自行退出
+    pub async fn stop(&mut self) -> SessionSnapshot {
+        let snapshot = self.snapshot();
+
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.capture_thread.take() {
+            handle.wait().await;
+        }
+        self.network_task = None;
+        self.vad_tx = None;
+        self.started_at = None;
+        self.metrics.reset();
+
+        tracing::info!("Dictation session stopped after {}ms", snapshot.duration_ms);
+        snapshot
+    }
+
+    /// 更新运行中会话的 VAD 配置，在采集线程处理下一帧前生效
+    ///
+    /// 会话未运行时忽略，下次启动时使用启动配置
+    pub fn set_vad_config(&self, config: VadConfig) {
+        if let Some(vad_tx) = &self.vad_tx {
+            let _ = vad_tx.send(config);
+        }
+    }
+
+    /// 获取当前状态快照
+    pub fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            is_active: self.is_active(),
+            duration_ms: self
+                .started_at
+                .map(|t| t.elapsed().as_millis() as u64)
+                .unwrap_or(0),
+            volume_level: self.metrics.volume_level(),
+            is_speech: self.metrics.is_speech(),
+        }
+    }
+}
+
+/// 采集线程与网络任务共享的 Scribe 会话 ID
+type SharedSessionId = Arc<parking_lot::Mutex<Option<String>>>;
+
+/// 会话采集循环的句柄
+enum CaptureHandle {
+    /// 会话独占的采集线程
+    Thread(JoinHandle<()>),
+    /// 在待机线程中运行，循环结束时通知
+    Standby(oneshot::Receiver<()>),
+}
+
+impl CaptureHandle {
+    /// 等待采集循环结束
+    async fn wait(self) {
+        match self {
+            Self::Thread(handle) => {
+                let _ = tauri::async_runtime::spawn_blocking(move || handle.join()).await;
+            }
+            Self::Standby(done_rx) => {
+                let _ = done_rx.await;
+            }
+        }
+    }
+}
+
+/// 一次会话采集所需的上下文与通道
+struct CaptureRequest {
+    ctx: CaptureContext,
+    audio_tx: mpsc::Sender<AudioFrame>,
+    ready_tx: oneshot::Sender<Result<(), AudioError>>,
+    /// 采集循环结束通知
+    done_tx: oneshot::Sender<()>,
+}
+
+/// 待机采集
+///
+/// 会话之间保持音频源运行，只保留最近 `preroll_ms` 的原始音频；
+/// 会话开始时这段音频先于实时音频进入处理管线，热键按下前开口的音节不会丢失。
+/// 音频源不能跨线程移动，会话的采集循环也在待机线程中运行。
+/// 丢弃后待机线程停止音频源并退出
+struct Standby {
+    /// 待机使用的音频源配置，与会话配置不同时重建
+    source: SourceKind,
+    audio: AudioConfig,
+    requests: std::sync::mpsc::Sender<CaptureRequest>,
+    thread: JoinHandle<()>,
+}
+
+impl Standby {
+    fn spawn(config: &SessionConfig) -> Result<Self, AppError> {
+        let (requests, requests_rx) = std::sync::mpsc::channel();
+        let (source, audio, preroll_ms) = (config.source.clone(), config.audio.clone(), config.preroll_ms);
+        let thread = std::thread::Builder::new()
+            .name("audio-flow-standby".to_string())
+            .spawn(move || run_standby(source, audio, preroll_ms, requests_rx))
+            .map_err(|e| AppError::SystemError(e.to_string()))?;
+        Ok(Self { source: config.source.clone(), audio: config.audio.clone(), requests, thread })
+    }
+
+    /// 待机线程仍在运行且音频源配置与会话一致
+    fn matches(&self, config: &SessionConfig) -> bool {
+        !self.thread.is_finished() && self.source == config.source && self.audio == config.audio
+    }
+}
+
+/// 待机线程主循环
+///
+/// 音频源打开失败、流中断或在会话中切换到其他设备后退出，下次会话重新创建
+fn run_standby(
+    kind: SourceKind,
+    audio: AudioConfig,
+    preroll_ms: u32,
+    requests: std::sync::mpsc::Receiver<CaptureRequest>,
+) {
+    let started = kind.create().and_then(|mut source| {
+        source.configure(audio)?;
+        source.start()?;
+        Ok(source)
+    });
+    let mut source = match started {
+        Ok(source) => source,
+        Err(e) => {
+            tracing::warn!("Standby capture unavailable: {}", e);
+            return;
+        }
+    };
+    let device_id = source.device_id().map(str::to_string);
+    let mut history = FrameHistory::with_duration(preroll_ms);
+    tracing::info!("Standby capture started");
+
+    loop {
+        match requests.recv_timeout(STANDBY_POLL) {
+            Ok(request) => {
+                capture_session(request.ctx, source.as_mut(), history.drain(), request.audio_tx, request.ready_tx);
+                let _ = request.done_tx.send(());
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                let read_size = frame_aligned_read_size(source.channels());
+                while let Some(frame) = source.read_frame(read_size) {
+                    history.push(frame);
+                }
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        if source.is_stream_lost() || source.is_finished() || source.device_id() != device_id.as_deref() {
+            break;
+        }
+    }
+
+    let _ = source.stop();
+    tracing::info!("Standby capture stopped");
+}
+
+/// 采集线程所需的上下文
+struct CaptureContext {
+    app: AppHandle,
+    config: SessionConfig,
+    running: Arc<AtomicBool>,
+    metrics: Arc<SessionMetrics>,
+    /// 设备列表变化通知
+    devices_rx: watch::Receiver<Vec<DeviceSummary>>,
+    /// VAD 配置更新
+    vad_rx: watch::Receiver<VadConfig>,
+    /// Scribe 会话 ID，用于命名录音文件
+    session_id: SharedSessionId,
+}
+
+/// 会话独占的采集线程：打开音频源，运行一次会话后关闭
+fn run_capture(request: CaptureRequest) {
+    let CaptureRequest { ctx, audio_tx, ready_tx, .. } = request;
+    let started = ctx.config.source.create().and_then(|mut source| {
+        source.configure(ctx.config.audio.clone())?;
+        source.start()?;
+        Ok(source)
+    });
+    match started {
+        Ok(mut source) => {
+            capture_session(ctx, source.as_mut(), Vec::new(), audio_tx, ready_tx);
+            let _ = source.stop();
+        }
+        Err(e) => {
+            let _ = ready_tx.send(Err(e));
+        }
+    }
+}
+
+/// 会话采集主循环
+///
+/// `history` 为待机期间保留的热键前音频，先于实时音频处理
+fn capture_session(
+    mut ctx: CaptureContext,
+    source: &mut dyn AudioSource,
+    history: Vec<AudioFrame>,
+    audio_tx: mpsc::Sender<AudioFrame>,
+    ready_tx: oneshot::Sender<Result<(), AudioError>>,
+) {
+    // 以音频源实际的采样率为重采样输入
+    let mut pipeline = match SpeechPipeline::new(source.sample_rate(), &ctx.config, ctx.metrics.clone()) {
+        Ok(mut pipeline) => {
+            pipeline.set_channel_routing(ctx.config.channel_routing_for(source.device_id()));
+            let _ = ready_tx.send(Ok(()));
+            pipeline
+        }
+        Err(e) => {
+            let _ = ready_tx.send(Err(e));
+            return;
+        }
+    };
+
+    let labels = source.channel_labels();
+    if !labels.is_empty() {
+        tracing::info!("Capturing labeled channels: {}", labels.join(", "));
+    }
+    pipeline.set_channel_labels(labels);
+
+    // 录音失败不影响听写
+    if let Some(recording) = ctx.config.recording.clone() {
+        match SessionRecorder::new(recording) {
+            Ok(recorder) => pipeline.set_recorder(recorder),
+            Err(e) => tracing::warn!("Session recording disabled: {}", e),
+        }
+    }
+
+    let emitter = EventEmitter::new(ctx.app.clone());
+    // 输入电平按设备原始音频计算，节流后发送给悬浮窗
+    let mut meter = LevelMeter::default();
+    let mut read_size = frame_aligned_read_size(source.channels());
+    let mut device_lost = false;
+    let mut auto_stop = ctx.config.auto_stop.map(AutoStop::new);
+    let mut auto_stopped = None;
+
+    // 热键前的音频只经过处理管线，不计入电平与自动停止
+    for frame in history {
+        process_frame(&mut pipeline, &audio_tx, frame);
+    }
+
+    while ctx.running.load(Ordering::SeqCst) {
+        device_lost |= source.is_stream_lost() || device_removed(source, &mut ctx.devices_rx);
+        if device_lost {
+            match failover(source, &mut pipeline, &audio_tx, &ctx, &emitter) {
+                Ok(channels) => {
+                    read_size = frame_aligned_read_size(channels);
+                    device_lost = false;
+                }
+                Err(e) => {
+                    tracing::warn!("Audio device failover failed, retrying: {}", e);
+                    std::thread::sleep(FAILOVER_RETRY);
+                }
+            }
+            continue;
+        }
+
+        if ctx.vad_rx.has_changed().unwrap_or(false) {
+            let vad = *ctx.vad_rx.borrow_and_update();
+            tracing::info!("Applying VAD config: threshold {} dB", vad.threshold_db);
+            pipeline.set_vad_config(vad);
+            if let Some(auto_stop) = auto_stop.as_mut() {
+                auto_stop.set_vad_hang(vad_hang(&vad));
+            }
+        }
+
+        let Some(frame) = source.read_frame(read_size) else {
+            // 文件等有限音频源读完后结束采集
+            if source.is_finished() {
+                tracing::info!("Audio source finished");
+                break;
+            }
+            std::thread::sleep(IDLE_POLL);
+            continue;
+        };
+
+        if let Some(reading) = meter.process(&frame) {
+            emitter.emit_audio_level(&reading);
+        }
+        let duration = Duration::from_nanos(frame.duration_ns() as u64);
+        if !process_frame(&mut pipeline, &audio_tx, frame) {
+            tracing::warn!("Audio channel closed, stopping capture");
+            break;
+        }
+
+        match auto_stop.as_mut().and_then(|a| a.update(pipeline.vad_state(), duration)) {
+            Some(AutoStopEvent::Countdown { remaining, reason }) => {
+                emitter.emit_auto_stop_countdown(Some(remaining.as_millis() as u64), Some(reason));
+            }
+            Some(AutoStopEvent::Cancelled) => emitter.emit_auto_stop_countdown(None, None),
+            Some(AutoStopEvent::Stop(reason)) => {
+                tracing::info!("Auto-stopping dictation session: {:?}", reason);
+                auto_stopped = Some(reason);
+                break;
+            }
+            None => {}
+        }
+    }
+
+    match pipeline.flush() {
+        Ok(chunks) => {
+            send_chunks(&audio_tx, chunks);
+        }
+        Err(e) => tracing::error!("Failed to flush audio pipeline: {}", e),
+    }
+    for stage in pipeline.processing_stats() {
+        tracing::debug!(
+            "Processing stage {}: {} calls, avg {:?}, max {:?}, load {:.4}{}",
+            stage.name, stage.calls, stage.average(), stage.max, stage.load(),
+            if stage.bypass { " (bypassed)" } else { "" }
+        );
+    }
+
+    if let Some(recorder) = pipeline.take_recorder() {
+        let session_id = ctx.session_id.lock().clone();
+        recorder.finish(session_id.as_deref());
+    }
+
+    // 剩余音频已交给网络任务，由其等待最终转写并注入；这里只结束会话状态
+    if let Some(reason) = auto_stopped {
+        ctx.running.store(false, Ordering::SeqCst);
+        tauri::async_runtime::spawn(finish_auto_stop(ctx.app.clone(), reason));
+    }
+}
+
+/// 自动停止后结束会话并通知前端
+///
+/// 会话已被手动停止或已开始新会话 (重新开始采集) 时不做处理
+async fn finish_auto_stop(app: AppHandle, reason: AutoStopReason) {
+    let session = app.state::<TauriMutex<DictationSession>>();
+    let mut guard = session.lock().await;
+    if !guard.is_active() || guard.is_capturing() {
+        return;
+    }
+    guard.stop().await;
+    drop(guard);
+
+    app.state::<AppState>().set_recording(false);
+    let emitter = EventEmitter::new(app.clone());
+    emitter.emit_recording_state(false);
+    emitter.emit_auto_stopped(reason);
+}
+
+/// 按整帧读取，避免多通道数据错位
+fn frame_aligned_read_size(channels: u16) -> usize {
+    let channels = channels.max(1) as usize;
+    READ_CHUNK_SAMPLES - READ_CHUNK_SAMPLES % channels
+}
+
+/// 处理并发送一帧数据，通道关闭时返回 false
+fn process_frame(
+    pipeline: &mut SpeechPipeline,
+    audio_tx: &mpsc::Sender<AudioFrame>,
+    frame: AudioFrame,
+) -> bool {
+    match pipeline.process(frame) {
+        Ok(chunks) => send_chunks(audio_tx, chunks),
+        Err(e) => {
+            tracing::error!("Audio processing failed: {}", e);
+            true
+        }
+    }
+}
+
+/// 检查当前设备是否已从设备列表中移除
+fn device_removed(
+    source: &dyn AudioSource,
+    devices_rx: &mut watch::Receiver<Vec<DeviceSummary>>,
+) -> bool {
+    if !devices_rx.has_changed().unwrap_or(false) {
+        return false;
+    }
+    let devices = devices_rx.borrow_and_update();
+    source
+        .device_id()
+        .is_some_and(|id| !devices.iter().any(|d| d.id == id))
+}
+
+/// 在备用设备或默认设备上重建采集流
+///
+/// 网络任务与 Scribe 连接不受影响，返回新设备的通道数
+fn failover(
+    source: &mut dyn AudioSource,
+    pipeline: &mut SpeechPipeline,
+    audio_tx: &mpsc::Sender<AudioFrame>,
+    ctx: &CaptureContext,
+    emitter: &EventEmitter,
+) -> Result<u16, AudioError> {
+    let lost_id = source.device_id().map(str::to_string);
+
+    // 先处理旧设备残留在缓冲区中的数据
+    let read_size = frame_aligned_read_size(source.channels());
+    while let Some(frame) = source.read_frame(read_size) {
+        process_frame(pipeline, audio_tx, frame);
+    }
+    source.stop()?;
+
+    let devices = ctx.devices_rx.borrow().clone();
+    let target = select_failover_device(&devices, lost_id.as_deref(), &ctx.config.fallback_devices);
+
+    let mut audio = ctx.config.audio.clone();
+    audio.device_id = target.as_ref().map(|d| d.id.clone());
+    source.configure(audio)?;
+
+    send_chunks(audio_tx, pipeline.set_input_rate(source.sample_rate())?);
+    pipeline.set_channel_routing(ctx.config.channel_routing_for(source.device_id()));
+    source.start()?;
+
+    let name = target
+        .map(|d| d.name)
+        .or_else(|| {
+            let id = source.device_id()?;
+            devices.iter().find(|d| d.id == id).map(|d| d.name.clone())
+        })
+        .unwrap_or_else(|| "Default".to_string());
+    tracing::info!(
+        "Switched audio device from {:?} to {} ({}Hz, {} channels)",
+        lost_id, name, source.sample_rate(), source.channels()
+    );
+    emitter.emit_audio_device_switched(lost_id.as_deref(), source.device_id(), &name);
+
+    Ok(source.channels())
+}
+
+/// 将语音帧发送到网络任务，通道关闭时返回 false
+fn send_chunks(audio_tx: &mpsc::Sender<AudioFrame>, chunks: Vec<AudioFrame>) -> bool {
+    chunks.into_iter().all(|chunk| audio_tx.blocking_send(chunk).is_ok())
+}
+
+/// 网络任务：发送音频并处理转写事件
+async fn run_network(
+    app: AppHandle,
+    mut audio_rx: mpsc::Receiver<AudioFrame>,
+    session_id: SharedSessionId,
+    metrics: Arc<SessionMetrics>,
+) {
+    let emitter = EventEmitter::new(app.clone());
+    let client = app.state::<TauriMutex<ScribeClient>>();
+    let mut poll = tokio::time::interval(RECEIVE_POLL);
+
+    loop {
+        tokio::select! {
+            chunk = audio_rx.recv() => {
+                let Some(chunk) = chunk else { break };
+                let result = client.lock().await.send_audio(&chunk.samples).await;
+                if let Err(e) = result {
+                    tracing::error!("Failed to send audio: {}", e);
+                    let error = AppError::from(e);
+                    emitter.emit_error(&error.code().to_string(), &error.to_string(), error.is_recoverable());
+                    return;
+                }
+            }
+            _ = poll.tick() => {
+                if let Some(event) = receive_event(&client).await {
+                    handle_event(&app, &emitter, &session_id, &metrics, event).await;
+                }
+            }
+        }
+    }
+
+    // 采集已结束，等待最后的转写结果
+    let deadline = Instant::now() + COMMIT_GRACE;
+    while Instant::now() < deadline {
+        match receive_event(&client).await {
+            Some(event) => {
+                let committed = matches!(event, ScribeEvent::CommittedTranscript { .. });
+                handle_event(&app, &emitter, &session_id, &metrics, event).await;
+                if committed {
+                    break;
+                }
+            }
+            None => tokio::time::sleep(RECEIVE_POLL).await,
+        }
+    }
+}
+
+/// 在限定时间内尝试接收一个转写事件
+async fn receive_event(client: &TauriMutex<ScribeClient>) -> Option<ScribeEvent> {
+    let mut guard = client.lock().await;
+    tokio::time::timeout(RECEIVE_TIMEOUT, guard.receive_event())
+        .await
+        .ok()
+        .flatten()
+}
+
+/// 处理转写事件
+async fn handle_event(
+    app: &AppHandle,
+    emitter: &EventEmitter,
+    current_session_id: &SharedSessionId,
+    metrics: &SessionMetrics,
+    event: ScribeEvent,
+) {
+    match event {
+        ScribeEvent::SessionStarted { session_id, .. } => {
+            tracing::info!("Scribe session started: {}", session_id);
+            *current_session_id.lock() = Some(session_id);
+        }
+        ScribeEvent::PartialTranscript { text, .. } => {
+            emitter.emit_partial_transcript(&clean_transcript(&text));
+        }
+        ScribeEvent::CommittedTranscript { text, confidence, .. } => {
+            let text = clean_transcript(&text);
+            if text.is_empty() {
+                return;
+            }
+            let speaker = metrics.take_speaker();
+            emitter.emit_committed_transcript(&text, confidence, speaker.as_deref());
+
+            let input_manager = app.state::<TauriMutex<InputManager>>();
+            let guard = input_manager.lock().await;
+            if let Err(e) = guard.inject(&text, app).await {
+                tracing::error!("Failed to inject transcript: {}", e);
+                let error = AppError::from(e);
+                emitter.emit_error(&error.code().to_string(), &error.to_string(), error.is_recoverable());
+            }
+        }
+        ScribeEvent::Error { code, message } => {
+            tracing::warn!("Scribe error {}: {}", code, message);
+            emitter.emit_error(&code, &message, true);
+        }
+        ScribeEvent::WordDetails { .. } | ScribeEvent::Disconnected => {}
+    }
+}
+
+/// 清理转写文本中的控制标记
+pub fn clean_transcript(text: &str) -> String {
+    text.replace("【SPEECH_CHANGE】", "")
+        .replace("【SILENCE】", "")
+        .trim()
+        .to_string()
+}
+
+/// VAD 的静音超时时长
+fn vad_hang(vad: &VadConfig) -> Duration {
+    Duration::from_millis(vad.silence_timeout_frames as u64 * VAD_FRAME_MS as u64)
+}
+
+/// 能量最大的通道，能量相同时取排在前面的通道，静音帧返回 None
+fn loudest_channel(frame: &AudioFrame) -> Option<u16> {
+    let (channel, energy) = frame
+        .channel_energy()
+        .into_iter()
+        .enumerate()
+        .fold((0, 0.0f32), |best, (channel, energy)| if energy > best.1 { (channel, energy) } else { best });
+    (energy > 0.0).then_some(channel as u16)
+}
+
+/// 计算 RMS 音量
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: f32 = samples.iter().map(|&x| x * x).sum();
+    (sum_squares / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::config::{
+        CalibrationSettings, HandsFreeSettings, RecordingSettings, ResamplerQuality, ResamplerSettings,
+        VadLevel,
+    };
+
+    fn pipeline(input_rate: u32) -> (SpeechPipeline, Arc<SessionMetrics>) {
+        pipeline_with(input_rate, SessionConfig::default())
+    }
+
+    fn pipeline_with(input_rate: u32, config: SessionConfig) -> (SpeechPipeline, Arc<SessionMetrics>) {
+        let metrics = Arc::new(SessionMetrics::default());
+        let pipeline = SpeechPipeline::new(input_rate, &config, metrics.clone()).unwrap();
+        (pipeline, metrics)
+    }
+
+    fn data_dir() -> std::path::PathBuf {
+        std::path::PathBuf::from("/var/lib/audio-flow")
+    }
+
+    #[test]
+    fn test_session_config_from_settings() {
+        let settings = AudioSettings {
+            input_device: Some("USB Mic".to_string()),
+            sample_rate: 44100,
+            ..Default::default()
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.audio.device_id, Some("USB Mic".to_string()));
+        assert_eq!(config.audio.sample_rate, 44100);
+        assert_eq!(config.output_rate, TARGET_SAMPLE_RATE);
+        assert!(config.fallback_devices.is_empty());
+        assert!(config.recording.is_none());
+        assert_eq!(config.preroll_ms, DEFAULT_PREROLL_MS);
+
+        // 录音保存在应用数据目录下
+        let settings = AudioSettings {
+            recording: RecordingSettings { enabled: true, ..Default::default() },
+            ..Default::default()
+        };
+        let recording = SessionConfig::from_settings(&settings, &data_dir()).recording.unwrap();
+        assert_eq!(recording.directory, data_dir().join(RECORDINGS_DIR));
+        assert!(!config.standby_capture);
+        assert!(!config.noise_suppression);
+        assert_eq!(config.processing, ProcessingChain::default_settings(false));
+
+        // sample_rate 为 0 时保留默认值
+        let config = SessionConfig::from_settings(&AudioSettings::default(), &data_dir());
+        assert_eq!(config.audio.sample_rate, 48000);
+        assert_eq!(config.source, SourceKind::Device);
+
+        let settings = AudioSettings {
+            loopback_device: Some("monitor:sink.monitor".to_string()),
+            ..Default::default()
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        let SourceKind::MultiDevice { inputs, mode } = config.source else {
+            panic!("expected multi-device source");
+        };
+        assert_eq!(mode, MultiInputMode::Separate);
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].label, MICROPHONE_LABEL);
+        assert_eq!(inputs[0].device_id, None);
+        assert_eq!(inputs[1].label, SYSTEM_AUDIO_LABEL);
+        assert_eq!(inputs[1].device_id.as_deref(), Some("monitor:sink.monitor"));
+
+        // 多路输入优先于回环设备
+        let remote = InputSourceSettings {
+            device_id: Some("headset".to_string()),
+            label: "remote".to_string(),
+            gain_db: -3.0,
+        };
+        let settings = AudioSettings {
+            loopback_device: Some("monitor:sink.monitor".to_string()),
+            inputs: vec![remote.clone()],
+            input_mode: MultiInputMode::Mix,
+            ..Default::default()
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.source, SourceKind::MultiDevice {
+            inputs: vec![remote],
+            mode: MultiInputMode::Mix,
+        });
+    }
+
+    #[test]
+    fn test_session_metrics_attributes_speaker() {
+        let metrics = SessionMetrics::default();
+
+        // 未设置标签时不标注
+        metrics.record_speaker(0, 320);
+        assert_eq!(metrics.take_speaker(), None);
+
+        metrics.set_channel_labels(vec!["me".to_string(), "remote".to_string()]);
+        metrics.record_speaker(0, 640);
+        metrics.record_speaker(1, 320);
+        assert_eq!(metrics.take_speaker().as_deref(), Some("me"));
+        // 取出后清零
+        assert_eq!(metrics.take_speaker(), None);
+
+        // 数量相同时取排在前面的通道
+        metrics.record_speaker(1, 320);
+        metrics.record_speaker(0, 320);
+        assert_eq!(metrics.take_speaker().as_deref(), Some("me"));
+
+        // 没有对应标签的通道被忽略
+        metrics.record_speaker(2, 320);
+        assert_eq!(metrics.take_speaker(), None);
+
+        metrics.reset();
+        metrics.record_speaker(0, 320);
+        assert_eq!(metrics.take_speaker(), None);
+    }
+
+    #[test]
+    fn test_pipeline_labeled_channels_mix_inputs() {
+        let (mut pipeline, metrics) = pipeline(16000);
+        pipeline.set_channel_labels(vec![MICROPHONE_LABEL.to_string(), SYSTEM_AUDIO_LABEL.to_string()]);
+
+        // 静音时不计入说话方
+        pipeline.process(AudioFrame::new(vec![0.0; 3200], 16000, 2, 0)).unwrap();
+        assert_eq!(metrics.take_speaker(), None);
+
+        // 远端说话、本地安静：各路叠加发送，电平不因平均而减半
+        let chunks = pipeline.process(AudioFrame::new([0.01, 0.4].repeat(1600), 16000, 2, 100_000_000)).unwrap();
+        let speech: Vec<_> = chunks.iter().filter(|c| c.timestamp_ns >= 100_000_000).collect();
+        assert_eq!(speech.len(), 5);
+        assert!(speech.iter().flat_map(|c| &c.samples).all(|s| (s - 0.41).abs() < 1e-6));
+        assert_eq!(metrics.take_speaker().as_deref(), Some(SYSTEM_AUDIO_LABEL));
+
+        // 双方同时说话：两路都发送，说话方为较响的本地一路
+        let chunks = pipeline.process(AudioFrame::new([0.3, 0.2].repeat(1600), 16000, 2, 200_000_000)).unwrap();
+        let speech: Vec<_> = chunks.iter().filter(|c| c.timestamp_ns >= 200_000_000).collect();
+        assert_eq!(speech.len(), 5);
+        assert!(speech.iter().flat_map(|c| &c.samples).all(|s| (s - 0.5).abs() < 1e-6));
+        assert_eq!(metrics.take_speaker().as_deref(), Some(MICROPHONE_LABEL));
+    }
+
+    #[test]
+    fn test_session_config_channel_routing_per_device() {
+        let settings = AudioSettings {
+            channel_routing: HashMap::from([
+                ("interface".to_string(), ChannelRouting::Single { channel: 0 }),
+            ]),
+            ..Default::default()
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.channel_routing_for(Some("interface")), ChannelRouting::Single { channel: 0 });
+        assert_eq!(config.channel_routing_for(Some("other")), ChannelRouting::Average);
+        assert_eq!(config.channel_routing_for(None), ChannelRouting::Average);
+    }
+
+    #[test]
+    fn test_pipeline_channel_routing_keeps_level() {
+        let (mut pipeline, metrics) = pipeline(16000);
+        pipeline.set_channel_routing(ChannelRouting::Single { channel: 0 });
+        // 仅第一路有信号的双通道输入
+        let samples = (0..3200).flat_map(|n| [if n % 2 == 0 { 0.2 } else { -0.2 }, 0.0]).collect();
+        pipeline.process(AudioFrame::new(samples, 16000, 2, 0)).unwrap();
+        assert!((metrics.volume_level() - 0.2).abs() < 0.01, "level {}", metrics.volume_level());
+    }
+
+    #[test]
+    fn test_session_config_applies_calibration() {
+        let settings = AudioSettings {
+            calibration: Some(CalibrationSettings {
+                threshold_db: -80.0,
+                smoothing_factor: 0.4,
+                gain_db: 6.0,
+            }),
+            ..Default::default()
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.vad.threshold_db, -80.0);
+        assert_eq!(config.vad.smoothing_factor, 0.4);
+        assert_eq!(config.processing[0].kind, ProcessingStageKind::Gain);
+        assert_eq!(config.processing[0].gain_db, Some(6.0));
+        assert_eq!(config.processing.len(), ProcessingChain::default_settings(false).len() + 1);
+
+        // 已手动配置增益时不重复添加
+        let settings = AudioSettings {
+            processing: vec![ProcessingStageSettings {
+                gain_db: Some(3.0),
+                ..ProcessingStageSettings::new(ProcessingStageKind::Gain)
+            }],
+            ..settings
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.processing.len(), 1);
+        assert_eq!(config.processing[0].gain_db, Some(3.0));
+    }
+
+    #[test]
+    fn test_session_config_applies_vad_level() {
+        let settings = AudioSettings { vad_level: VadLevel::Relaxed, ..Default::default() };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        let relaxed = VadConfig::from_level(VadLevel::Relaxed);
+        assert_eq!(config.vad.threshold_db, relaxed.threshold_db);
+        assert_eq!(config.vad.min_speech_frames, relaxed.min_speech_frames);
+        assert_eq!(config.vad.adaptive, relaxed.adaptive);
+
+        // 校准阈值优先于级别预设
+        let settings = AudioSettings {
+            calibration: Some(CalibrationSettings {
+                threshold_db: -80.0,
+                smoothing_factor: 0.4,
+                gain_db: 0.0,
+            }),
+            ..settings
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.vad.threshold_db, -80.0);
+        assert_eq!(config.vad.min_speech_frames, relaxed.min_speech_frames);
+    }
+
+    #[test]
+    fn test_session_config_hands_free() {
+        assert!(SessionConfig::from_settings(&AudioSettings::default(), &data_dir()).auto_stop.is_none());
+
+        let settings = AudioSettings {
+            hands_free: HandsFreeSettings { enabled: true, trailing_silence_ms: 1500, ..Default::default() },
+            ..Default::default()
+        };
+        let auto_stop = SessionConfig::from_settings(&settings, &data_dir()).auto_stop.unwrap();
+        assert_eq!(auto_stop.trailing_silence, Duration::from_millis(1500));
+        // 默认 VAD 静音超时 15 帧
+        assert_eq!(auto_stop.vad_hang, Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_session_config_resampler_quality() {
+        assert_eq!(SessionConfig::from_settings(&AudioSettings::default(), &data_dir()).resampler, ResamplerConfig::default());
+
+        let settings = AudioSettings {
+            resampler: ResamplerSettings { quality: ResamplerQuality::High, chunk_size: 480 },
+            ..Default::default()
+        };
+        let config = SessionConfig::from_settings(&settings, &data_dir());
+        assert_eq!(config.resampler, ResamplerConfig { quality: ResamplerQuality::High, chunk_size: 480 });
+        let pipeline = SpeechPipeline::new(48000, &config, Arc::new(SessionMetrics::default())).unwrap();
+        assert!(pipeline.chain.is_active(Resample::NAME));
+    }
+
+    #[test]
+    fn test_pipeline_set_vad_config_keeps_output_rate() {
+        let (mut pipeline, _) = pipeline(16000);
+        pipeline.set_vad_config(VadConfig::from_level(VadLevel::Aggressive));
+        assert_eq!(pipeline.vad.config().sample_rate, 16000);
+        assert_eq!(pipeline.vad.config().threshold_db, VadConfig::from_level(VadLevel::Aggressive).threshold_db);
+        assert_eq!(pipeline.vad_state(), VadState::Silence);
+    }
+
+    #[test]
+    fn test_pipeline_drops_silence() {
+        let (mut pipeline, metrics) = pipeline(16000);
+        let frame = AudioFrame::new(vec![0.0; 1600], 16000, 1, 0);
+        let chunks = pipeline.process(frame).unwrap();
+        assert!(chunks.is_empty());
+        assert!(!metrics.is_speech());
+        assert_eq!(pipeline.vad_state(), VadState::Silence);
+    }
+
+    #[test]
+    fn test_pipeline_emits_speech_frames() {
+        let (mut pipeline, metrics) = pipeline(16000);
+        let frame = AudioFrame::new(vec![0.5; 1600], 16000, 1, 0);
+        let chunks = pipeline.process(frame).unwrap();
+
+        // 1600 样本 = 5 个 20ms 帧
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(|c| c.samples.len() == 320));
+        assert!(metrics.is_speech());
+        assert!((metrics.volume_level() - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_pipeline_flushes_preroll_on_speech_start() {
+        let config = SessionConfig { preroll_ms: 100, ..Default::default() };
+        let (mut pipeline, _) = pipeline_with(16000, config);
+
+        // 300ms 静音，其中最后 100ms 作为预录
+        let mut silence = vec![0.0; 4800];
+        silence[4800 - 1600..].iter_mut().for_each(|s| *s = 0.001);
+        assert!(pipeline.process(AudioFrame::new(silence, 16000, 1, 0)).unwrap().is_empty());
+
+        let chunks = pipeline.process(AudioFrame::new(vec![0.5; 320], 16000, 1, 300_000_000)).unwrap();
+        // 5 个预录帧 + 1 个语音帧
+        assert_eq!(chunks.len(), 6);
+        assert!(chunks.iter().all(|c| c.samples.len() == 320));
+        assert!(chunks[..5].iter().flat_map(|c| &c.samples).all(|&s| s == 0.001));
+        assert_eq!(chunks[5].samples[0], 0.5);
+        // 预录帧保留原始采集时间
+        let timestamps: Vec<u128> = chunks.iter().map(|c| c.timestamp_ns / 1_000_000).collect();
+        assert_eq!(timestamps, vec![200, 220, 240, 260, 280, 300]);
+
+        // 语音段内不再重复发送预录
+        let chunks = pipeline.process(AudioFrame::new(vec![0.5; 320], 16000, 1, 320_000_000)).unwrap();
+        assert_eq!(chunks.len(), 1);
+    }
+
+    #[test]
+    fn test_pipeline_frame_timestamps() {
+        let (mut pipeline, _) = pipeline(48000);
+        // 两帧 50ms 立体声，第二帧之前丢失了 100ms
+        let first = pipeline.process(AudioFrame::new(vec![0.5; 4800], 48000, 2, 1_000_000_000)).unwrap();
+        let second = pipeline.process(AudioFrame::new(vec![0.5; 4800], 48000, 2, 1_150_000_000)).unwrap();
+
+        assert_eq!(first[0].timestamp_ns, 1_000_000_000);
+        assert_eq!(first[0].sample_rate, TARGET_SAMPLE_RATE);
+        // 帧间隔 20ms
+        assert!(first.windows(2).all(|w| w[1].timestamp_ns - w[0].timestamp_ns == 20_000_000));
+        // 丢帧后时间戳跳变而不是继续累加 (允许重采样缓冲带来的少量滞后)
+        let jump = second[0].timestamp_ns as i128 - first.last().unwrap().timestamp_ns as i128;
+        assert!((115_000_000..=130_000_000).contains(&jump), "unexpected jump {}ns", jump);
+    }
+
+    #[test]
+    fn test_pipeline_noise_suppression() {
+        let settings = AudioSettings { noise_suppression: true, preroll_ms: Some(0), ..Default::default() };
+        let (mut pipeline, _) = pipeline_with(16000, SessionConfig::from_settings(&settings, &data_dir()));
+
+        // 静音段用于估计噪声，不发送
+        let noise: Vec<f32> = (0..4800).map(|i| if i % 2 == 0 { 0.001 } else { -0.001 }).collect();
+        assert!(pipeline.process(AudioFrame::new(noise, 16000, 1, 0)).unwrap().is_empty());
+
+        // 降噪阶段延迟 512 样本 (32ms)，本帧只输出到 368ms，时间戳仍与采集对齐
+        let mut chunks = pipeline.process(AudioFrame::new(vec![0.5; 1600], 16000, 1, 300_000_000)).unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[0].timestamp_ns, 288_000_000);
+
+        // 结束时取出降噪阶段中的延迟样本，语音一直发送到采集结束
+        chunks.extend(pipeline.flush().unwrap());
+        assert_eq!(chunks.last().unwrap().end_timestamp_ns(), 400_000_000);
+    }
+
+    #[test]
+    fn test_pipeline_noise_suppression_enables_chain_stage() {
+        let stage = |kind, bypass| ProcessingStageSettings { bypass, ..ProcessingStageSettings::new(kind) };
+        let settings = AudioSettings {
+            noise_suppression: true,
+            processing: vec![
+                stage(ProcessingStageKind::Downmix, false),
+                stage(ProcessingStageKind::NoiseSuppression, true),
+                stage(ProcessingStageKind::Resample, false),
+            ],
+            ..Default::default()
+        };
+        let (pipeline, _) = pipeline_with(16000, SessionConfig::from_settings(&settings, &data_dir()));
+
+        // 已有的降噪阶段被启用，不重复追加
+        let stats = pipeline.processing_stats();
+        let denoise: Vec<_> = stats.iter().filter(|s| s.name == NoiseSuppression::NAME).collect();
+        assert_eq!(denoise.len(), 1);
+        assert!(!denoise[0].bypass);
+    }
+
+    #[test]
+    fn test_pipeline_auto_gain_trips_vad() {
+        // 约 -37dBFS 的安静麦克风，未开启 AGC 时达不到 VAD 阈值
+        let quiet: Vec<f32> = (0..16000)
+            .map(|n| 0.02 * (2.0 * std::f32::consts::PI * 300.0 * n as f32 / 16000.0).sin())
+            .collect();
+        let (mut pipeline, _) = pipeline(16000);
+        assert!(pipeline.process(AudioFrame::new(quiet.clone(), 16000, 1, 0)).unwrap().is_empty());
+
+        let settings = AudioSettings { auto_gain: true, ..Default::default() };
+        let (mut pipeline, metrics) = pipeline_with(16000, SessionConfig::from_settings(&settings, &data_dir()));
+        let chunks = pipeline.process(AudioFrame::new(quiet, 16000, 1, 0)).unwrap();
+        assert!(!chunks.is_empty());
+        assert!(metrics.is_speech());
+        // 放大后不削波
+        assert!(chunks.iter().flat_map(|c| &c.samples).all(|s| s.abs() < 1.0));
+    }
+
+    #[test]
+    fn test_pipeline_auto_gain_keeps_noise_silent() {
+        use crate::modules::audio::{PlaybackSpeed, Signal, SyntheticSource};
+
+        // 约 -57dBFS 的房间底噪，AGC 不应把它放大成语音
+        let noise = SyntheticSource::new(
+            Signal::WhiteNoise { amplitude: 0.0025, seed: 7 }, 16000, 1, None, PlaybackSpeed::AsFastAsPossible,
+        )
+        .generate(48000);
+        let settings = AudioSettings { auto_gain: true, ..Default::default() };
+        let (mut pipeline, metrics) = pipeline_with(16000, SessionConfig::from_settings(&settings, &data_dir()));
+        for (i, chunk) in noise.chunks(1600).enumerate() {
+            let frame = AudioFrame::new(chunk.to_vec(), 16000, 1, i as u128 * 100_000_000);
+            assert!(pipeline.process(frame).unwrap().is_empty());
+            assert_eq!(pipeline.vad_state(), VadState::Silence);
+        }
+        assert!(!metrics.is_speech());
+
+        // 安静的语音抬高增益后，语音间隙中的底噪同样不会被判为语音
+        let quiet = SyntheticSource::new(
+            Signal::Tone { frequency: 300.0, amplitude: 0.02 }, 16000, 1, None, PlaybackSpeed::AsFastAsPossible,
+        )
+        .generate(16000);
+        assert!(!pipeline.process(AudioFrame::new(quiet, 16000, 1, 3_000_000_000)).unwrap().is_empty());
+        for (i, chunk) in noise.chunks(1600).enumerate() {
+            let timestamp_ns = 4_000_000_000 + i as u128 * 100_000_000;
+            pipeline.process(AudioFrame::new(chunk.to_vec(), 16000, 1, timestamp_ns)).unwrap();
+        }
+        assert_eq!(pipeline.vad_state(), VadState::Silence);
+    }
+
+    #[test]
+    fn test_pipeline_preroll_disabled() {
+        let config = SessionConfig { preroll_ms: 0, ..Default::default() };
+        let (mut pipeline, _) = pipeline_with(16000, config);
+        pipeline.process(AudioFrame::new(vec![0.0; 3200], 16000, 1, 0)).unwrap();
+        let chunks = pipeline.process(AudioFrame::new(vec![0.5; 320], 16000, 1, 0)).unwrap();
+        assert_eq!(chunks.len(), 1);
+    }
+
+    #[test]
+    fn test_pipeline_downmixes_and_resamples() {
+        let (mut pipeline, _) = pipeline(48000);
+        // 100ms 立体声 48kHz
+        let frame = AudioFrame::new(vec![0.5; 9600], 48000, 2, 0);
+        let mut total: usize = pipeline.process(frame).unwrap().iter().map(|c| c.samples.len()).sum();
+        total += pipeline.flush().unwrap().iter().map(|c| c.samples.len()).sum::<usize>();
+        // 输出约为 100ms @ 16kHz
+        assert!((1280..=1760).contains(&total), "unexpected output length {}", total);
+    }
+
+    #[test]
+    fn test_pipeline_switches_input_rate() {
+        let (mut pipeline, _) = pipeline(48000);
+        let frame = AudioFrame::new(vec![0.5; 4800], 48000, 1, 0);
+        let mut total: usize = pipeline.process(frame).unwrap().iter().map(|c| c.samples.len()).sum();
+
+        // 设备切换到 16kHz 后直接透传，VAD 状态保留
+        total += pipeline.set_input_rate(16000).unwrap().iter().map(|c| c.samples.len()).sum::<usize>();
+        assert_eq!(pipeline.vad_state(), VadState::Speech);
+        let frame = AudioFrame::new(vec![0.5; 1600], 16000, 1, 0);
+        let chunks = pipeline.process(frame).unwrap();
+        assert_eq!(chunks.len(), 5);
+        total += chunks.iter().map(|c| c.samples.len()).sum::<usize>();
+
+        // 两段各约 100ms
+        assert!((2880..=3360).contains(&total), "unexpected output length {}", total);
+        assert!(pipeline.set_input_rate(16000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_driven_by_sources() {
+        use crate::modules::audio::{FileSource, PlaybackSpeed, Signal, SyntheticSource};
+
+        // 200ms 静音 + 200ms 1kHz 正弦波，48kHz 立体声
+        let mut silence = SyntheticSource::new(
+            Signal::Silence, 48000, 2, Some(Duration::from_millis(200)), PlaybackSpeed::AsFastAsPossible,
+        );
+        let mut tone = SyntheticSource::new(
+            Signal::Tone { frequency: 1000.0, amplitude: 0.5 },
+            48000, 2, Some(Duration::from_millis(200)), PlaybackSpeed::AsFastAsPossible,
+        );
+        let mut samples = silence.generate(9600);
+        samples.extend(tone.generate(9600));
+        let mut source = FileSource::from_samples(samples, 48000, 2, PlaybackSpeed::AsFastAsPossible);
+
+        let (mut pipeline, _) = pipeline(source.sample_rate());
+        source.start().unwrap();
+
+        let mut speech_samples = 0;
+        while let Some(frame) = source.read_frame(frame_aligned_read_size(source.channels())) {
+            let chunks = pipeline.process(frame).unwrap();
+            speech_samples += chunks.iter().map(|c| c.samples.len()).sum::<usize>();
+        }
+        assert!(source.is_finished());
+
+        // 正弦波部分 (约 200ms @ 16kHz) 加上之前的 200ms 静音预录
+        assert!((5600..=6400).contains(&speech_samples), "unexpected speech length {}", speech_samples);
+    }
+
+    #[test]
+    fn test_pipeline_records_session_audio() {
+        use crate::modules::config::RecordingFormat;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (mut pipeline, _) = pipeline(48000);
+        pipeline.set_recorder(SessionRecorder::new(RecorderConfig {
+            directory: dir.path().to_path_buf(),
+            format: RecordingFormat::Wav,
+            record_raw: true,
+            record_resampled: true,
+        }).unwrap());
+
+        // 静音也会被录制，便于排查漏识别
+        pipeline.process(AudioFrame::new(vec![0.0; 9600], 48000, 2, 0)).unwrap();
+        pipeline.flush().unwrap();
+        let paths = pipeline.take_recorder().unwrap().finish(Some("sess_1"));
+        assert_eq!(paths.len(), 2);
+
+        let raw = hound::WavReader::open(dir.path().join("sess_1-raw.wav")).unwrap();
+        assert_eq!(raw.len(), 9600);
+        let resampled = hound::WavReader::open(dir.path().join("sess_1-16k.wav")).unwrap();
+        assert_eq!(resampled.spec().sample_rate, TARGET_SAMPLE_RATE);
+        assert!((1280..=1760).contains(&resampled.len()), "unexpected length {}", resampled.len());
+    }
+
+    #[test]
+    fn test_frame_aligned_read_size() {
+        assert_eq!(frame_aligned_read_size(1), READ_CHUNK_SAMPLES);
+        assert_eq!(frame_aligned_read_size(0), READ_CHUNK_SAMPLES);
+        assert_eq!(frame_aligned_read_size(7) % 7, 0);
+    }
+
+    #[test]
+    fn test_session_metrics_reset() {
+        let metrics = SessionMetrics::default();
+        metrics.update(0.25, true);
+        assert_eq!(metrics.volume_level(), 0.25);
+        assert!(metrics.is_speech());
+
+        metrics.reset();
+        assert_eq!(metrics.volume_level(), 0.0);
+        assert!(!metrics.is_speech());
+    }
+
+    #[test]
+    fn test_dictation_session_idle_snapshot() {
+        let session = DictationSession::new();
+        assert!(!session.is_active());
+        let snapshot = session.snapshot();
+        assert!(!snapshot.is_active);
+        assert_eq!(snapshot.duration_ms, 0);
+        assert_eq!(snapshot.volume_level, 0.0);
+    }
+
+    #[test]
+    fn test_clean_transcript() {
+        assert_eq!(clean_transcript(" hello【SPEECH_CHANGE】 world【SILENCE】 "), "hello world");
+    }
+}